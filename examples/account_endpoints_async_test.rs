@@ -42,14 +42,7 @@ async fn main() {
     let handle1 = tokio::spawn(async move {
         println!("1. Testing trade_fee_rate endpoint (all pairs)...");
         match account1.trade_fee_rate(None).await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ Trade Fee Rate Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ Trade Fee Rate: {} pairs, first: {:?}\n", response.len(), response.first()),
             Err(e) => println!("✗ Trade Fee Rate Error: {}\n", e),
         }
     });
@@ -59,7 +52,7 @@ async fn main() {
     let handle2 = tokio::spawn(async move {
         println!("2. Testing trade_fee_rate endpoint (lbk_usdt)...");
         match account2.trade_fee_rate(Some("lbk_usdt")).await {
-            Ok(response) => println!("✓ Trade Fee Rate (LBK/USDT) Response: {}\n", response),
+            Ok(response) => println!("✓ Trade Fee Rate (LBK/USDT): {:?}\n", response.first()),
             Err(e) => println!("✗ Trade Fee Rate (LBK/USDT) Error: {}\n", e),
         }
     });
@@ -69,7 +62,7 @@ async fn main() {
     let handle3 = tokio::spawn(async move {
         println!("3. Testing api_restrictions endpoint...");
         match account3.api_restrictions().await {
-            Ok(response) => println!("✓ API Restrictions Response: {}\n", response),
+            Ok(response) => println!("✓ API Restrictions: {:?}\n", response),
             Err(e) => println!("✗ API Restrictions Error: {}\n", e),
         }
     });
@@ -79,14 +72,7 @@ async fn main() {
     let handle4 = tokio::spawn(async move {
         println!("4. Testing account_info endpoint...");
         match account4.account_info().await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ Account Info Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ Account Info: {} balances, first held: {:?}\n", response.balances.len(), response.holdings().next()),
             Err(e) => println!("✗ Account Info Error: {}\n", e),
         }
     });
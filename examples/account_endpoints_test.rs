@@ -38,42 +38,28 @@ fn main() {
     // Test trade fee rate (all pairs)
     println!("1. Testing trade_fee_rate endpoint (all pairs)...");
     match account.trade_fee_rate(None) {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ Trade Fee Rate Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ Trade Fee Rate: {} pairs, first: {:?}\n", response.len(), response.first()),
         Err(e) => println!("✗ Trade Fee Rate Error: {}\n", e),
     }
 
     // Test trade fee rate (specific pair)
     println!("2. Testing trade_fee_rate endpoint (lbk_usdt)...");
     match account.trade_fee_rate(Some("lbk_usdt")) {
-        Ok(response) => println!("✓ Trade Fee Rate (LBK/USDT) Response: {}\n", response),
+        Ok(response) => println!("✓ Trade Fee Rate (LBK/USDT): {:?}\n", response.first()),
         Err(e) => println!("✗ Trade Fee Rate (LBK/USDT) Error: {}\n", e),
     }
 
     // Test API restrictions
     println!("3. Testing api_restrictions endpoint...");
     match account.api_restrictions() {
-        Ok(response) => println!("✓ API Restrictions Response: {}\n", response),
+        Ok(response) => println!("✓ API Restrictions: {:?}\n", response),
         Err(e) => println!("✗ API Restrictions Error: {}\n", e),
     }
 
     // Test account info
     println!("4. Testing account_info endpoint...");
     match account.account_info() {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ Account Info Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ Account Info: {} balances, first held: {:?}\n", response.balances.len(), response.holdings().next()),
         Err(e) => println!("✗ Account Info Error: {}\n", e),
     }
 
@@ -41,14 +41,7 @@ fn main() {
     // Test account info
     println!("1. Testing account_info endpoint...");
     match spot.account_info() {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ Account Info Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ Account Info: {} balances, first held: {:?}\n", response.balances.len(), response.holdings().next()),
         Err(e) => println!("✗ Account Info Error: {}\n", e),
     }
 
@@ -75,14 +75,7 @@ async fn main() {
     let handle2 = tokio::spawn(async move {
         println!("2. Testing user_info endpoint...");
         match wallet2.user_info().await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ User Info Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ User Info: {} entries, first: {:?}\n", response.len(), response.first()),
             Err(e) => println!("✗ User Info Error: {}\n", e),
         }
     });
@@ -92,14 +85,7 @@ async fn main() {
     let handle3 = tokio::spawn(async move {
         println!("3. Testing deposit_history endpoint (USDT, last 30 days)...");
         match wallet3.deposit_history(None, Some("usdt"), Some(start_time), Some(end_time)).await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ Deposit History Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ Deposit History: {} entries, first: {:?}\n", response.len(), response.first()),
             Err(e) => println!("✗ Deposit History Error: {}\n", e),
         }
     });
@@ -109,14 +95,7 @@ async fn main() {
     let handle4 = tokio::spawn(async move {
         println!("4. Testing withdraw_history endpoint (last 30 days)...");
         match wallet4.withdraw_history(None, None, None, Some(start_time), Some(end_time)).await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ Withdrawal History Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ Withdrawal History: {} entries, first: {:?}\n", response.len(), response.first()),
             Err(e) => println!("✗ Withdrawal History Error: {}\n", e),
         }
     });
@@ -126,7 +105,7 @@ async fn main() {
     let handle5 = tokio::spawn(async move {
         println!("5. Testing deposit_address endpoint (USDT)...");
         match wallet5.deposit_address("usdt", None).await {
-            Ok(response) => println!("✓ Deposit Address Response: {}\n", response),
+            Ok(response) => println!("✓ Deposit Address Response: {:?}\n", response),
             Err(e) => println!("✗ Deposit Address Error: {}\n", e),
         }
     });
@@ -136,7 +115,7 @@ async fn main() {
     let handle6 = tokio::spawn(async move {
         println!("6. Testing asset_detail endpoint (USDT)...");
         match wallet6.asset_detail(Some("usdt")).await {
-            Ok(response) => println!("✓ Asset Detail Response: {}\n", response),
+            Ok(response) => println!("✓ Asset Detail Response: {:?}\n", response),
             Err(e) => println!("✗ Asset Detail Error: {}\n", e),
         }
     });
@@ -146,14 +125,7 @@ async fn main() {
     let handle7 = tokio::spawn(async move {
         println!("7. Testing asset_detail endpoint (all coins)...");
         match wallet7.asset_detail(None).await {
-            Ok(response) => {
-                let display = if response.len() > 500 {
-                    format!("{}... (truncated)", &response[..500])
-                } else {
-                    response.clone()
-                };
-                println!("✓ All Asset Details Response: {}\n", display);
-            },
+            Ok(response) => println!("✓ All Asset Details: {} entries, first: {:?}\n", response.len(), response.iter().next()),
             Err(e) => println!("✗ All Asset Details Error: {}\n", e),
         }
     });
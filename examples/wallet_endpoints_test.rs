@@ -58,14 +58,7 @@ fn main() {
     // Test user info
     println!("2. Testing user_info endpoint...");
     match wallet.user_info() {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ User Info Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ User Info: {} entries, first: {:?}\n", response.len(), response.first()),
         Err(e) => println!("✗ User Info Error: {}\n", e),
     }
 
@@ -78,56 +71,35 @@ fn main() {
     let start_time = end_time - (30 * 24 * 60 * 60 * 1000); // 30 days ago
 
     match wallet.deposit_history(None, Some("usdt"), Some(start_time), Some(end_time)) {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ Deposit History Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ Deposit History: {} entries, first: {:?}\n", response.len(), response.first()),
         Err(e) => println!("✗ Deposit History Error: {}\n", e),
     }
 
     // Test withdrawal history
     println!("4. Testing withdraw_history endpoint (last 30 days)...");
     match wallet.withdraw_history(None, None, None, Some(start_time), Some(end_time)) {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ Withdrawal History Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ Withdrawal History: {} entries, first: {:?}\n", response.len(), response.first()),
         Err(e) => println!("✗ Withdrawal History Error: {}\n", e),
     }
 
     // Test deposit address
     println!("5. Testing deposit_address endpoint (USDT)...");
     match wallet.deposit_address("usdt", None) {
-        Ok(response) => println!("✓ Deposit Address Response: {}\n", response),
+        Ok(response) => println!("✓ Deposit Address Response: {:?}\n", response),
         Err(e) => println!("✗ Deposit Address Error: {}\n", e),
     }
 
     // Test asset detail
     println!("6. Testing asset_detail endpoint (USDT)...");
     match wallet.asset_detail(Some("usdt")) {
-        Ok(response) => println!("✓ Asset Detail Response: {}\n", response),
+        Ok(response) => println!("✓ Asset Detail Response: {:?}\n", response),
         Err(e) => println!("✗ Asset Detail Error: {}\n", e),
     }
 
     // Test asset detail for all coins
     println!("7. Testing asset_detail endpoint (all coins)...");
     match wallet.asset_detail(None) {
-        Ok(response) => {
-            let display = if response.len() > 500 {
-                format!("{}... (truncated)", &response[..500])
-            } else {
-                response.clone()
-            };
-            println!("✓ All Asset Details Response: {}\n", display);
-        },
+        Ok(response) => println!("✓ All Asset Details: {} entries, first: {:?}\n", response.len(), response.iter().next()),
         Err(e) => println!("✗ All Asset Details Error: {}\n", e),
     }
 
@@ -1,8 +1,7 @@
 use crate::client::AsyncClient;
-use crate::api::{API, Account as AccountAPI};
+use crate::account::requests;
 use crate::errors::Result;
-use crate::util::build_signed_request;
-use std::collections::BTreeMap;
+use crate::model::{AccountInfo, ApiPermissions, TradeFee};
 
 /// Account API client for asynchronous operations
 #[derive(Clone)]
@@ -15,43 +14,18 @@ impl AsyncAccount {
     /// 
     /// # Parameters
     /// * `category` - Optional trading pair filter (e.g., "lbk_usdt")
-    pub async fn trade_fee_rate(&self, category: Option<&str>) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-
-        if let Some(cat) = category {
-            parameters.insert("category".to_string(), cat.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Account(AccountAPI::TradeFeeRate), Some(signed_request)).await
+    pub async fn trade_fee_rate(&self, category: Option<&str>) -> Result<Vec<TradeFee>> {
+        self.client.execute(requests::trade_fee_rate(category)).await
     }
 
     /// Query API Key permissions (trading, reading, withdrawal, IP restrictions)
-    pub async fn api_restrictions(&self) -> Result<String> {
-        let parameters: BTreeMap<String, String> = BTreeMap::new();
-        
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Account(AccountAPI::ApiRestrictions), Some(signed_request)).await
+    pub async fn api_restrictions(&self) -> Result<ApiPermissions> {
+        self.client.execute(requests::api_restrictions()).await
     }
 
     /// Get account information including balances and permissions
     /// (Alias for Spot::account_info for convenience)
-    pub async fn account_info(&self) -> Result<String> {
-        let parameters: BTreeMap<String, String> = BTreeMap::new();
-        
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Account(AccountAPI::AccountInfo), Some(signed_request)).await
+    pub async fn account_info(&self) -> Result<AccountInfo> {
+        self.client.execute(requests::account_info()).await
     }
 }
@@ -0,0 +1,364 @@
+use crate::account::{Account, AsyncAccount};
+use crate::errors::Result;
+use crate::events::EventHub;
+use crate::model::{AccountInfo, AssetBalance, AssetUpdate, AssetUpdatePush, BalanceChange};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc as async_mpsc;
+
+/// Differences below this are float noise, not drift
+const DEFAULT_TOLERANCE: f64 = 1e-8;
+
+/// A local balance that disagreed with the REST account endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceDrift {
+    pub asset: String,
+    /// What the book held before reconciling
+    pub local: AssetBalance,
+    /// What `account_info` reported, now stored in the book
+    pub remote: AssetBalance,
+}
+
+impl BalanceDrift {
+    /// `remote.free - local.free`
+    pub fn free_diff(&self) -> f64 {
+        self.remote.free - self.local.free
+    }
+
+    /// `remote.frozen - local.frozen`
+    pub fn frozen_diff(&self) -> f64 {
+        self.remote.frozen - self.local.frozen
+    }
+}
+
+/// A change to the book
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalanceEvent {
+    /// An `assetUpdate` push moved a balance
+    Updated {
+        previous: AssetBalance,
+        balance: AssetBalance,
+        change: BalanceChange,
+    },
+    /// Reconciling found and corrected a balance
+    Drift(BalanceDrift),
+}
+
+#[derive(Default)]
+struct Inner {
+    balances: BTreeMap<String, AssetBalance>,
+    /// Time of the latest push applied per asset (milliseconds)
+    pushed_at: HashMap<String, i64>,
+    reconciled_at: Option<Instant>,
+}
+
+/// Local balances kept current by `assetUpdate` pushes
+///
+/// Seed it with `reconcile`, feed every WebSocket message to `apply_push`,
+/// and call `reconcile` again whenever `reconcile_due` says so. Pushes carry
+/// the absolute free and frozen amounts after the change, so an order being
+/// placed, filled or cancelled, or a deposit or withdrawal, each simply
+/// replaces the asset's balance; pushes older than the last one applied are
+/// ignored. Clones share the same state.
+#[derive(Clone)]
+pub struct BalanceBook {
+    inner: Arc<Mutex<Inner>>,
+    events: EventHub<BalanceEvent>,
+    reconcile_every: Duration,
+    tolerance: f64,
+}
+
+impl BalanceBook {
+    /// Empty book asking to be reconciled every `reconcile_every`
+    pub fn new(reconcile_every: Duration) -> Self {
+        BalanceBook {
+            inner: Arc::new(Mutex::new(Inner::default())),
+            events: EventHub::new(),
+            reconcile_every,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Ignore differences up to `tolerance` when reconciling
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Register a callback invoked for every event
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&BalanceEvent) + Send + Sync + 'static,
+    {
+        self.events.on_event(callback);
+    }
+
+    /// Subscribe to events through a blocking channel
+    pub fn subscribe(&self) -> mpsc::Receiver<BalanceEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to events through an async channel
+    pub fn subscribe_async(&self) -> async_mpsc::UnboundedReceiver<BalanceEvent> {
+        self.events.subscribe_async()
+    }
+
+    /// Balance of `asset`, `None` if the book has never seen it
+    pub fn balance(&self, asset: &str) -> Option<AssetBalance> {
+        self.lock().balances.get(&asset.to_lowercase()).cloned()
+    }
+
+    /// Free amount of `asset`, zero if unknown
+    pub fn available(&self, asset: &str) -> f64 {
+        self.balance(asset).map_or(0.0, |b| b.free)
+    }
+
+    /// All balances, sorted by asset
+    pub fn balances(&self) -> Vec<AssetBalance> {
+        self.lock().balances.values().cloned().collect()
+    }
+
+    /// Whether the book was never reconciled or `reconcile_every` has passed
+    pub fn reconcile_due(&self) -> bool {
+        self.lock()
+            .reconciled_at
+            .is_none_or(|at| at.elapsed() >= self.reconcile_every)
+    }
+
+    /// Apply an `assetUpdate`, returning `false` if it is older than the
+    /// last push applied to the same asset
+    pub fn apply_asset_update(&self, update: &AssetUpdate) -> Result<bool> {
+        let asset = update.asset_code.to_lowercase();
+        let mut inner = self.lock();
+        if inner.pushed_at.get(&asset).is_some_and(|&time| update.time < time) {
+            return Ok(false);
+        }
+
+        let previous = inner.balances.get(&asset).cloned().unwrap_or_else(|| zero(&asset));
+        let free = amount(&update.free)?.unwrap_or(previous.free);
+        let frozen = match (amount(&update.freeze)?, amount(&update.asset)?) {
+            (Some(freeze), _) => freeze,
+            (None, Some(total)) => total - free,
+            (None, None) => previous.frozen,
+        };
+        let balance = AssetBalance {
+            asset: asset.clone(),
+            free,
+            frozen,
+        };
+        inner.balances.insert(asset.clone(), balance.clone());
+        inner.pushed_at.insert(asset, update.time);
+        drop(inner);
+        self.events.emit([BalanceEvent::Updated {
+            previous,
+            balance,
+            change: update.change(),
+        }]);
+        Ok(true)
+    }
+
+    /// Apply a raw WebSocket message, returning `true` if it was an
+    /// `assetUpdate` that changed the book
+    pub fn apply_push(&self, message: &str) -> Result<bool> {
+        let value: serde_json::Value = serde_json::from_str(message)?;
+        if value.get("type").and_then(|t| t.as_str()) != Some("assetUpdate") {
+            return Ok(false);
+        }
+        let push: AssetUpdatePush = serde_json::from_value(value)?;
+        self.apply_asset_update(&push.data)
+    }
+
+    /// Replace the book with `account_info`, returning the balances that drifted
+    ///
+    /// Assets pushed after `requested_at` are newer than the REST response
+    /// and are left alone. Push times come from LBank's clock, so
+    /// `requested_at` must be server time in milliseconds too.
+    pub fn apply_account_info(&self, info: &AccountInfo, requested_at: u64) -> Vec<BalanceDrift> {
+        let mut inner = self.lock();
+        let seeding = inner.reconciled_at.is_none();
+        // Assets missing from the response hold nothing
+        let mut remote: BTreeMap<String, AssetBalance> = inner
+            .balances
+            .keys()
+            .map(|asset| (asset.clone(), zero(asset)))
+            .collect();
+        for balance in &info.balances {
+            let asset = balance.asset.to_lowercase();
+            remote.insert(asset.clone(), AssetBalance { asset, ..balance.clone() });
+        }
+
+        let mut drifts = Vec::new();
+        for (asset, balance) in remote {
+            if inner.pushed_at.get(&asset).is_some_and(|&time| time >= requested_at as i64) {
+                continue;
+            }
+            let local = inner.balances.get(&asset).cloned().unwrap_or_else(|| zero(&asset));
+            let drifted = (local.free - balance.free).abs() > self.tolerance
+                || (local.frozen - balance.frozen).abs() > self.tolerance;
+            if drifted && !seeding {
+                drifts.push(BalanceDrift {
+                    asset: asset.clone(),
+                    local,
+                    remote: balance.clone(),
+                });
+            }
+            inner.balances.insert(asset, balance);
+        }
+        inner.reconciled_at = Some(Instant::now());
+        drop(inner);
+        self.events.emit(drifts.iter().cloned().map(BalanceEvent::Drift));
+        drifts
+    }
+
+    /// Fetch `account_info` and reconcile the book with it
+    ///
+    /// The first call seeds the book and reports no drift. Pushes are
+    /// compared against the request time on the client's `TimeSync` when it
+    /// has one, so keep time sync enabled if the local clock may drift.
+    pub fn reconcile(&self, account: &Account) -> Result<Vec<BalanceDrift>> {
+        let requested_at = account.client.server_time();
+        let info = account.account_info()?;
+        Ok(self.apply_account_info(&info, requested_at))
+    }
+
+    /// Fetch `account_info` and reconcile the book with it (async)
+    pub async fn reconcile_async(&self, account: &AsyncAccount) -> Result<Vec<BalanceDrift>> {
+        let requested_at = account.client.server_time();
+        let info = account.account_info().await?;
+        Ok(self.apply_account_info(&info, requested_at))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn zero(asset: &str) -> AssetBalance {
+    AssetBalance {
+        asset: asset.to_string(),
+        free: 0.0,
+        frozen: 0.0,
+    }
+}
+
+/// Parse an optional push amount, `None` when the field was left out
+fn amount(value: &str) -> Result<Option<f64>> {
+    match value.trim() {
+        "" => Ok(None),
+        value => Ok(Some(value.parse()?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(asset: &str, free: &str, freeze: &str, time: i64, change: &str) -> String {
+        format!(
+            r#"{{"data":{{"assetCode":"{}","free":"{}","freeze":"{}","time":{},"type":"{}"}},"type":"assetUpdate","SERVER":"V2"}}"#,
+            asset, free, freeze, time, change
+        )
+    }
+
+    fn account(balances: &[(&str, f64, f64)]) -> AccountInfo {
+        let balances: Vec<serde_json::Value> = balances
+            .iter()
+            .map(|(asset, free, locked)| serde_json::json!({"asset": asset, "free": free.to_string(), "locked": locked.to_string()}))
+            .collect();
+        serde_json::from_value(serde_json::json!({ "balances": balances })).unwrap()
+    }
+
+    #[test]
+    fn test_pushes_and_drift() {
+        let book = BalanceBook::new(Duration::from_secs(60));
+        let events = book.subscribe();
+        // Callbacks run unlocked, so they can read the book
+        let (reader, (tx, available)) = (book.clone(), mpsc::channel());
+        book.on_event(move |_| tx.send(reader.available("usdt")).unwrap());
+        assert!(book.reconcile_due());
+        assert!(book.apply_account_info(&account(&[("usdt", 100.0, 0.0)]), 0).is_empty());
+        assert!(!book.reconcile_due());
+
+        assert!(book.apply_push(&push("usdt", "60", "40", 10, "ORDER_CREATE")).unwrap());
+        assert_eq!(book.available("USDT"), 60.0);
+        assert_eq!(available.try_recv().unwrap(), 60.0);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            BalanceEvent::Updated { change: BalanceChange::OrderCreate, ref previous, .. } if previous.free == 100.0
+        ));
+        // Out of order
+        assert!(!book.apply_push(&push("usdt", "100", "0", 5, "ORDER_CANCEL")).unwrap());
+        assert!(!book.apply_push(r#"{"type":"kbar"}"#).unwrap());
+
+        // The REST snapshot predates the push to usdt, so only eth drifts
+        let drifts = book.apply_account_info(&account(&[("usdt", 100.0, 0.0), ("eth", 1.0, 0.0)]), 8);
+        let assets: Vec<&str> = drifts.iter().map(|d| d.asset.as_str()).collect();
+        assert_eq!(assets, vec!["eth"]);
+        assert_eq!((book.available("eth"), book.available("usdt")), (1.0, 60.0));
+        assert!(matches!(events.try_recv().unwrap(), BalanceEvent::Drift(_)));
+
+        let drifts = book.apply_account_info(&account(&[("usdt", 70.0, 30.0), ("eth", 1.0, 0.0)]), 20);
+        assert_eq!(drifts.len(), 1);
+        assert_eq!((drifts[0].free_diff(), drifts[0].frozen_diff()), (10.0, -10.0));
+        assert_eq!(book.balance("usdt").unwrap().total(), 100.0);
+        assert!(matches!(events.try_recv().unwrap(), BalanceEvent::Drift(_)));
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::TimeSync;
+    use crate::mock::MockServer;
+    use crate::util::get_timestamp;
+
+    #[test]
+    fn test_reconcile_against_account_info() {
+        let server = MockServer::start_for_tests();
+        server.set_balance("usdt", 100.0);
+        let account = Account { client: server.test_client() };
+        let book = BalanceBook::new(Duration::from_secs(60));
+        assert!(book.reconcile(&account).unwrap().is_empty());
+        assert_eq!(book.available("usdt"), 100.0);
+
+        server.deposit("usdt", 5.0);
+        let push = serde_json::json!({
+            "data": {"assetCode": "usdt", "asset": "105", "free": "105", "freeze": "0", "time": get_timestamp() - 1, "type": "DEPOSIT"},
+            "type": "assetUpdate",
+        });
+        assert!(book.apply_push(&push.to_string()).unwrap());
+        assert!(book.reconcile(&account).unwrap().is_empty());
+
+        // A change the WebSocket never reported
+        server.set_balance("usdt", 90.0);
+        let drifts = book.reconcile(&account).unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].free_diff(), -15.0);
+        assert_eq!(book.available("usdt"), 90.0);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_on_server_clock() {
+        let server = MockServer::start_for_tests();
+        server.set_clock_offset(60_000);
+        server.set_balance("usdt", 100.0);
+        let mut client = server.test_async_client();
+        client.set_time_sync(TimeSync::default());
+        client.sync_time().await.unwrap();
+        let account = AsyncAccount { client };
+        let book = BalanceBook::new(Duration::from_secs(60));
+        book.reconcile_async(&account).await.unwrap();
+
+        // Stamped before the request by the server clock, but after it by the local one
+        let push = serde_json::json!({
+            "data": {"assetCode": "usdt", "free": "105", "freeze": "0", "time": get_timestamp() + 30_000, "type": "DEPOSIT"},
+            "type": "assetUpdate",
+        });
+        assert!(book.apply_push(&push.to_string()).unwrap());
+        server.set_balance("usdt", 90.0);
+        let drifts = book.reconcile_async(&account).await.unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(book.available("usdt"), 90.0);
+    }
+}
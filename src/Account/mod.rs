@@ -1,5 +1,12 @@
 mod account; 
 mod async_account; 
+mod balance_book;
+mod portfolio;
+mod trade_fees;
+pub mod requests;
 
 pub use account::{Account}; 
-pub use async_account::AsyncAccount;  
\ No newline at end of file
+pub use async_account::AsyncAccount;  
+pub use balance_book::{BalanceBook, BalanceDrift, BalanceEvent};
+pub use portfolio::{Holding, Portfolio};
+pub use trade_fees::{break_even_price, total_fees, FeeSchedule, FillFee, OrderFees};
@@ -0,0 +1,183 @@
+use crate::account::{Account, AsyncAccount};
+use crate::errors::Result;
+use crate::market::{AsyncMarket, Market};
+use crate::model::{AssetBalance, SymbolPrice};
+use crate::util::parse_response;
+use std::collections::HashMap;
+
+/// One asset's balance valued in the portfolio's quote currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub balance: AssetBalance,
+    /// Price of one unit in the quote currency, `None` if no pair links the two
+    pub price: Option<f64>,
+    /// `balance.total()` times `price`
+    pub value: Option<f64>,
+    /// Share of the portfolio's total value, in percent
+    pub allocation: Option<f64>,
+}
+
+/// All non-empty balances valued in one quote currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio {
+    pub quote: String,
+    /// Largest holdings first, unpriced ones last
+    pub holdings: Vec<Holding>,
+    /// Sum of the priced holdings
+    pub total_value: f64,
+}
+
+impl Portfolio {
+    /// Value `balances` in `quote` using prices from `Market::price`
+    ///
+    /// An asset is priced through its `asset_quote` pair, or the inverse of a
+    /// `quote_asset` pair when only that one is listed. Assets with neither
+    /// are kept with no value and left out of the total.
+    pub fn new(balances: &[AssetBalance], quote: &str, prices: &[SymbolPrice]) -> Self {
+        let quote = quote.to_lowercase();
+        let prices: HashMap<String, f64> = prices
+            .iter()
+            .filter_map(|p| Some((p.symbol.to_lowercase(), p.price.parse().ok()?)))
+            .collect();
+
+        let mut holdings: Vec<Holding> = balances
+            .iter()
+            .filter(|balance| !balance.is_empty())
+            .map(|balance| {
+                let price = price_in(&prices, &balance.asset.to_lowercase(), &quote);
+                Holding {
+                    balance: balance.clone(),
+                    price,
+                    value: price.map(|price| price * balance.total()),
+                    allocation: None,
+                }
+            })
+            .collect();
+
+        let total_value: f64 = holdings.iter().filter_map(|h| h.value).sum();
+        for holding in &mut holdings {
+            holding.allocation = holding
+                .value
+                .filter(|_| total_value > 0.0)
+                .map(|value| value / total_value * 100.0);
+        }
+        holdings.sort_by(|a, b| {
+            let (a, b) = (a.value.unwrap_or(f64::NEG_INFINITY), b.value.unwrap_or(f64::NEG_INFINITY));
+            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Portfolio {
+            quote,
+            holdings,
+            total_value,
+        }
+    }
+
+    /// Value the account's balances in `quote`
+    pub fn fetch(account: &Account, market: &Market, quote: &str) -> Result<Self> {
+        let info = account.account_info()?;
+        let prices: Vec<SymbolPrice> = parse_response(&market.price(None)?)?;
+        Ok(Self::new(&info.balances, quote, &prices))
+    }
+
+    /// Value the account's balances in `quote` (async)
+    pub async fn fetch_async(account: &AsyncAccount, market: &AsyncMarket, quote: &str) -> Result<Self> {
+        let info = account.account_info().await?;
+        let prices: Vec<SymbolPrice> = parse_response(&market.price(None).await?)?;
+        Ok(Self::new(&info.balances, quote, &prices))
+    }
+
+    /// Holding of `asset`, matched case-insensitively
+    pub fn holding(&self, asset: &str) -> Option<&Holding> {
+        self.holdings.iter().find(|h| h.balance.asset.eq_ignore_ascii_case(asset))
+    }
+
+    /// Holdings that couldn't be priced in the quote currency
+    pub fn unpriced(&self) -> impl Iterator<Item = &Holding> {
+        self.holdings.iter().filter(|h| h.price.is_none())
+    }
+}
+
+fn price_in(prices: &HashMap<String, f64>, asset: &str, quote: &str) -> Option<f64> {
+    if asset == quote {
+        return Some(1.0);
+    }
+    if let Some(&price) = prices.get(&format!("{}_{}", asset, quote)) {
+        return Some(price);
+    }
+    prices
+        .get(&format!("{}_{}", quote, asset))
+        .filter(|&&price| price > 0.0)
+        .map(|price| 1.0 / price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(asset: &str, free: f64, frozen: f64) -> AssetBalance {
+        AssetBalance {
+            asset: asset.into(),
+            free,
+            frozen,
+        }
+    }
+
+    fn price(symbol: &str, price: &str) -> SymbolPrice {
+        SymbolPrice {
+            symbol: symbol.into(),
+            price: price.into(),
+        }
+    }
+
+    #[test]
+    fn test_portfolio_valuation() {
+        let balances = [
+            balance("usdt", 100.0, 0.0),
+            balance("eth", 0.1, 0.1),
+            balance("lbk", 0.0, 0.0),
+            balance("xyz", 5.0, 0.0),
+            balance("gbp", 0.0, 250.0),
+        ];
+        let prices = [price("eth_usdt", "2000"), price("usdt_gbp", "0.5")];
+        let portfolio = Portfolio::new(&balances, "USDT", &prices);
+
+        assert_eq!(portfolio.quote, "usdt");
+        assert!(portfolio.holding("lbk").is_none());
+        assert_eq!(portfolio.total_value, 1000.0);
+        let assets: Vec<&str> = portfolio.holdings.iter().map(|h| h.balance.asset.as_str()).collect();
+        assert_eq!(assets, vec!["gbp", "eth", "usdt", "xyz"]);
+
+        let eth = portfolio.holding("ETH").unwrap();
+        assert_eq!((eth.value, eth.allocation), (Some(400.0), Some(40.0)));
+        assert_eq!(portfolio.holding("gbp").unwrap().value, Some(500.0));
+        let unpriced: Vec<&str> = portfolio.unpriced().map(|h| h.balance.asset.as_str()).collect();
+        assert_eq!(unpriced, vec!["xyz"]);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[test]
+    fn test_fetch_values_account() {
+        let server = MockServer::start_for_tests();
+        server.add_pair("eth_usdt", 2000.0);
+        server.set_balance("usdt", 600.0);
+        server.set_balance("eth", 0.2);
+        server.set_balance("doge", 10.0);
+        let client = server.test_client();
+        let (account, market) = (Account { client: client.clone() }, Market { client });
+
+        let info = account.account_info().unwrap();
+        assert_eq!(info.balance("ETH").unwrap().total(), 0.2);
+
+        let portfolio = Portfolio::fetch(&account, &market, "usdt").unwrap();
+        assert_eq!(portfolio.total_value, 1000.0);
+        assert_eq!(portfolio.holdings[0].balance.asset, "usdt");
+        assert_eq!(portfolio.holding("eth").unwrap().allocation, Some(40.0));
+        assert_eq!(portfolio.unpriced().count(), 1);
+    }
+}
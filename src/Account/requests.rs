@@ -0,0 +1,17 @@
+//! Requests behind [`Account`](super::Account) and [`AsyncAccount`](super::AsyncAccount)
+
+use crate::api::{API, Account};
+use crate::model::{AccountInfo, ApiPermissions, TradeFee};
+use crate::request::Request;
+
+pub fn trade_fee_rate(category: Option<&str>) -> Request<Vec<TradeFee>> {
+    Request::signed(API::Account(Account::TradeFeeRate)).param_opt("category", category).parse()
+}
+
+pub fn api_restrictions() -> Request<ApiPermissions> {
+    Request::signed(API::Account(Account::ApiRestrictions)).parse()
+}
+
+pub fn account_info() -> Request<AccountInfo> {
+    Request::signed(API::Account(Account::AccountInfo)).parse()
+}
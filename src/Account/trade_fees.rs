@@ -0,0 +1,305 @@
+use crate::account::{Account, AsyncAccount};
+use crate::errors::Result;
+use crate::model::{TradeFee, TransactionRecord};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Fee and net amounts of an intended order
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderFees {
+    pub symbol: String,
+    pub buy: bool,
+    /// Whether the maker rate was applied
+    pub maker: bool,
+    pub price: f64,
+    pub amount: f64,
+    pub rate: f64,
+    /// Fee charged in `fee_asset`: the base asset on buys, the quote asset on sells
+    pub fee: f64,
+    pub fee_asset: String,
+    /// What leaves the account: `price * amount` quote on buys, `amount` base on sells
+    pub cost: f64,
+    /// What arrives, net of the fee, in the same asset as `fee`
+    pub proceeds: f64,
+}
+
+impl OrderFees {
+    /// Fees for an order of `type_` (as passed to `Spot::create_order`)
+    ///
+    /// `buy_maker`/`sell_maker` orders pay the maker rate; everything else is
+    /// assumed to take liquidity and pays the taker rate.
+    pub fn new(fee: &TradeFee, type_: &str, price: f64, amount: f64) -> Result<Self> {
+        let (base, quote) = split_symbol(&fee.symbol)?;
+        let buy = type_.starts_with("buy");
+        let maker = type_.ends_with("_maker");
+        let rate = fee.rate(maker);
+        let gross = price * amount;
+        let (cost, received, fee_asset) = if buy { (gross, amount, base) } else { (amount, gross, quote) };
+        Ok(OrderFees {
+            symbol: fee.symbol.clone(),
+            buy,
+            maker,
+            price,
+            amount,
+            rate,
+            fee: received * rate,
+            fee_asset: fee_asset.to_string(),
+            cost,
+            proceeds: received * (1.0 - rate),
+        })
+    }
+}
+
+/// Exit price at which a round trip entered at `price` neither gains nor loses
+///
+/// For `buy_first` the position is bought at `price` and sold back, so the
+/// exit has to be higher; otherwise it is sold at `price` and bought back
+/// lower. Both legs pay the maker or taker rate as given.
+pub fn break_even_price(fee: &TradeFee, price: f64, buy_first: bool, entry_maker: bool, exit_maker: bool) -> f64 {
+    let kept = (1.0 - fee.rate(entry_maker)) * (1.0 - fee.rate(exit_maker));
+    if buy_first {
+        price / kept
+    } else {
+        price * kept
+    }
+}
+
+/// The fee paid on one fill
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillFee {
+    pub trade_id: String,
+    pub order_id: String,
+    pub fee: f64,
+    /// Base asset on buys, quote asset on sells
+    pub fee_asset: String,
+    /// `fee` as a fraction of what the fill received
+    pub rate: f64,
+    /// Whether the fill carried no `commission` and `fee` was worked out
+    /// from the cached rate instead
+    pub estimated: bool,
+}
+
+impl FillFee {
+    /// Fee of `record`, falling back to `schedule` when it has no `commission`
+    pub fn new(record: &TransactionRecord, schedule: Option<&TradeFee>) -> Result<Self> {
+        let (base, quote) = split_symbol(&record.symbol)?;
+        let qty: f64 = record.qty.parse()?;
+        let received = if record.is_buyer {
+            qty
+        } else {
+            match record.quote_qty.as_deref() {
+                Some(quote_qty) => quote_qty.parse()?,
+                None => qty * record.price.parse::<f64>()?,
+            }
+        };
+
+        let commission = record.commission.as_deref().filter(|c| !c.trim().is_empty());
+        let (fee, estimated) = match (commission, schedule) {
+            (Some(commission), _) => (commission.parse()?, false),
+            (None, Some(schedule)) => (received * schedule.rate(record.is_maker), true),
+            (None, None) => return Err(format!("fill {} has no commission and no fee rate", record.id).into()),
+        };
+        Ok(FillFee {
+            trade_id: record.id.clone(),
+            order_id: record.order_id.clone(),
+            fee,
+            fee_asset: if record.is_buyer { base } else { quote }.to_string(),
+            rate: if received > 0.0 { fee / received } else { 0.0 },
+            estimated,
+        })
+    }
+}
+
+/// Sum of `fills` per fee asset
+pub fn total_fees(fills: &[FillFee]) -> BTreeMap<String, f64> {
+    let mut totals = BTreeMap::new();
+    for fill in fills {
+        *totals.entry(fill.fee_asset.clone()).or_insert(0.0) += fill.fee;
+    }
+    totals
+}
+
+fn split_symbol(symbol: &str) -> Result<(&str, &str)> {
+    symbol
+        .split_once('_')
+        .ok_or_else(|| format!("cannot split symbol {} into base and quote", symbol).into())
+}
+
+/// Per-symbol cache of `trade_fee_rate`
+///
+/// Rates older than the TTL are fetched again on the next `get`. Clones share
+/// the same cache.
+#[derive(Clone)]
+pub struct FeeSchedule {
+    fees: Arc<Mutex<HashMap<String, (TradeFee, Instant)>>>,
+    ttl: Duration,
+}
+
+impl FeeSchedule {
+    pub fn new(ttl: Duration) -> Self {
+        FeeSchedule {
+            fees: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// Cache `fee`, e.g. a rate negotiated outside the API
+    pub fn insert(&self, fee: TradeFee) {
+        self.lock().insert(fee.symbol.to_lowercase(), (fee, Instant::now()));
+    }
+
+    /// Rate of `symbol` if cached and fresh
+    pub fn cached(&self, symbol: &str) -> Option<TradeFee> {
+        self.lock()
+            .get(&symbol.to_lowercase())
+            .filter(|(_, fetched)| fetched.elapsed() < self.ttl)
+            .map(|(fee, _)| fee.clone())
+    }
+
+    /// Rate of `symbol`, fetched when not cached
+    pub fn get(&self, account: &Account, symbol: &str) -> Result<TradeFee> {
+        if let Some(fee) = self.cached(symbol) {
+            return Ok(fee);
+        }
+        let fees = account.trade_fee_rate(Some(symbol))?;
+        self.store(fees, symbol)
+    }
+
+    /// Rate of `symbol`, fetched when not cached (async)
+    pub async fn get_async(&self, account: &AsyncAccount, symbol: &str) -> Result<TradeFee> {
+        if let Some(fee) = self.cached(symbol) {
+            return Ok(fee);
+        }
+        let fees = account.trade_fee_rate(Some(symbol)).await?;
+        self.store(fees, symbol)
+    }
+
+    /// Fetch and cache the rates of every pair, returning how many were loaded
+    pub fn refresh(&self, account: &Account) -> Result<usize> {
+        let fees = account.trade_fee_rate(None)?;
+        Ok(self.extend(fees))
+    }
+
+    /// Fetch and cache the rates of every pair, returning how many were loaded (async)
+    pub async fn refresh_async(&self, account: &AsyncAccount) -> Result<usize> {
+        let fees = account.trade_fee_rate(None).await?;
+        Ok(self.extend(fees))
+    }
+
+    /// Fees of `fills`, estimating from cached rates where a fill has no `commission`
+    pub fn fill_fees(&self, fills: &[TransactionRecord]) -> Result<Vec<FillFee>> {
+        fills
+            .iter()
+            .map(|fill| FillFee::new(fill, self.cached(&fill.symbol).as_ref()))
+            .collect()
+    }
+
+    fn store(&self, fees: Vec<TradeFee>, symbol: &str) -> Result<TradeFee> {
+        self.extend(fees);
+        self.cached(symbol)
+            .ok_or_else(|| format!("no trade fee rate for {}", symbol).into())
+    }
+
+    fn extend(&self, fees: Vec<TradeFee>) -> usize {
+        let mut cache = self.lock();
+        let count = fees.len();
+        for fee in fees {
+            cache.insert(fee.symbol.to_lowercase(), (fee, Instant::now()));
+        }
+        count
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (TradeFee, Instant)>> {
+        self.fees.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee() -> TradeFee {
+        TradeFee {
+            symbol: "eth_usdt".into(),
+            maker_commission: 0.001,
+            taker_commission: 0.002,
+        }
+    }
+
+    fn fill(is_buyer: bool, commission: Option<&str>) -> TransactionRecord {
+        TransactionRecord {
+            symbol: "eth_usdt".into(),
+            id: "1".into(),
+            order_id: "o1".into(),
+            price: "2000".into(),
+            qty: "0.5".into(),
+            quote_qty: Some("1000".into()),
+            commission: commission.map(Into::into),
+            time: 0,
+            is_buyer,
+            is_maker: false,
+        }
+    }
+
+    #[test]
+    fn test_order_fees_and_break_even() {
+        let buy = OrderFees::new(&fee(), "buy_maker", 2000.0, 1.0).unwrap();
+        assert!(buy.maker);
+        assert_eq!((buy.cost, buy.fee, buy.fee_asset.as_str(), buy.proceeds), (2000.0, 0.001, "eth", 0.999));
+
+        let sell = OrderFees::new(&fee(), "sell", 2000.0, 1.0).unwrap();
+        assert_eq!((sell.cost, sell.fee, sell.fee_asset.as_str(), sell.proceeds), (1.0, 4.0, "usdt", 1996.0));
+
+        let exit = break_even_price(&fee(), 2000.0, true, true, false);
+        let round_trip = OrderFees::new(&fee(), "sell", exit, buy.proceeds).unwrap();
+        assert!((round_trip.proceeds - 2000.0).abs() < 1e-9);
+        assert!(break_even_price(&fee(), 2000.0, false, false, false) < 2000.0);
+    }
+
+    #[test]
+    fn test_fill_fees() {
+        let sold = FillFee::new(&fill(false, Some("2")), None).unwrap();
+        assert_eq!((sold.fee, sold.fee_asset.as_str(), sold.rate, sold.estimated), (2.0, "usdt", 0.002, false));
+
+        let schedule = FeeSchedule::new(Duration::from_secs(60));
+        assert!(schedule.fill_fees(&[fill(true, None)]).is_err());
+        schedule.insert(fee());
+        let fills = schedule.fill_fees(&[fill(true, None), fill(false, Some("2"))]).unwrap();
+        assert!(fills[0].estimated);
+        assert_eq!(total_fees(&fills), BTreeMap::from([("eth".to_string(), 0.001), ("usdt".to_string(), 2.0)]));
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+    use crate::spot::Spot;
+    use crate::util::parse_response;
+
+    #[test]
+    fn test_schedule_matches_fills() {
+        let server = MockServer::start_for_tests();
+        server.add_pair("eth_usdt", 2000.0);
+        server.set_trade_fee("eth_usdt", 0.001, 0.002);
+        server.set_balance("usdt", 5000.0);
+        let spot = Spot { client: server.test_client() };
+        let account = Account { client: spot.client.clone() };
+
+        let schedule = FeeSchedule::new(Duration::from_secs(60));
+        let fee = schedule.get(&account, "eth_usdt").unwrap();
+        assert_eq!((fee.maker_commission, fee.taker_commission), (0.001, 0.002));
+        assert!(schedule.cached("ETH_USDT").is_some());
+        assert!(schedule.get(&account, "btc_usdt").is_err());
+
+        let intended = OrderFees::new(&fee, "buy", 2000.0, 1.0).unwrap();
+        spot.place_order("eth_usdt", "buy", Some("2000"), Some("1"), None).unwrap();
+        let text = spot.transaction_history("eth_usdt", None, None, None, None).unwrap();
+        let records: Vec<TransactionRecord> = parse_response(&text).unwrap();
+        let fills = schedule.fill_fees(&records).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!(!fills[0].estimated);
+        assert_eq!((fills[0].fee, fills[0].fee_asset.as_str()), (intended.fee, "eth"));
+    }
+}
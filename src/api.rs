@@ -1,9 +1,14 @@
 use crate::{
     client::{Client, AsyncClient},
     config::Config,
-    common::{Common, AsyncCommon}
+    common::{Common, AsyncCommon},
+    market::{Market as MarketClient, AsyncMarket},
+    wallet::{Wallet as WalletClient, AsyncWallet},
+    spot::{Spot as SpotClient, AsyncSpot},
+    account::{Account as AccountClient, AsyncAccount},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum API {
     General(General),
     Market(Market),
@@ -13,6 +18,7 @@ pub enum API {
     WebSocket(WebSocket),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum General {
     CurrencyPairs,
     Accuracy,
@@ -21,6 +27,7 @@ pub enum General {
     Timestamp,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Market {
     SystemPing,
     Depth,
@@ -32,6 +39,7 @@ pub enum Market {
     Kline,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Wallet {
     SystemStatus,
     UserInfo,
@@ -43,6 +51,7 @@ pub enum Wallet {
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Spot {
     OrderTest,
     CreateOrder,
@@ -55,12 +64,14 @@ pub enum Spot {
     TransactionHistory,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Account {
     TradeFeeRate,
     ApiRestrictions,
     AccountInfo,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum WebSocket {
     // Public WebSocket endpoints
     KlineSubscribe,
@@ -196,7 +207,8 @@ impl LBank for Common {
         config: &Config,
     ) -> Common {
         Common {
-            client: Client::new_with_config(api_key, secret_key, config),
+            client: Client::try_new_with_config(api_key, secret_key, config)
+                .expect("invalid HTTP client configuration"),
         }
     }
 
@@ -224,7 +236,8 @@ impl AsyncLBank for AsyncCommon {
         config: &Config,
     ) -> AsyncCommon {
         AsyncCommon {
-            client: AsyncClient::new_with_config(api_key, secret_key, config),
+            client: AsyncClient::try_new_with_config(api_key, secret_key, config)
+                .expect("invalid HTTP client configuration"),
         }
     }
 
@@ -233,5 +246,31 @@ impl AsyncLBank for AsyncCommon {
     }
 }
 
+/// `LBank` / `AsyncLBank` for the remaining domain clients, which only wrap a client
+macro_rules! impl_lbank {
+    ($trait_:ident, $client:ident, $($domain:ident),+) => {$(
+        impl $trait_ for $domain {
+            fn new(api_key: Option<String>, secret_key: Option<String>) -> $domain {
+                Self::new_with_config(api_key, secret_key, &Config::default())
+            }
 
+            fn new_with_config(
+                api_key: Option<String>,
+                secret_key: Option<String>,
+                config: &Config,
+            ) -> $domain {
+                $domain {
+                    client: $client::try_new_with_config(api_key, secret_key, config)
+                        .expect("invalid HTTP client configuration"),
+                }
+            }
 
+            fn set_verbose(&mut self, verbose: bool) {
+                self.client.set_verbose(verbose);
+            }
+        }
+    )+};
+}
+
+impl_lbank!(LBank, Client, MarketClient, WalletClient, SpotClient, AccountClient);
+impl_lbank!(AsyncLBank, AsyncClient, AsyncMarket, AsyncWallet, AsyncSpot, AsyncAccount);
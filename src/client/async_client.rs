@@ -1,40 +1,86 @@
 use serde::de::DeserializeOwned;
-use crate::{api::API, config::Config, errors::Result};
+use std::sync::Arc;
+use std::time::Instant;
+use super::cassette::{Cassette, CassetteMode};
+use super::host_pool::{can_fail_over, HostPool, HostStatus};
+use super::rate_limit::RateLimiter;
+use super::time_sync::TimeSync;
+use super::transport::{async_transport, AsyncHttpTransport, HttpRequest, Method};
+use crate::{api::API, config::Config, errors::Result, request::Request, util::{client_order_id, get_timestamp}};
 
 /// Async HTTP client for asynchronous operations
 #[derive(Clone)]
 pub struct AsyncClient {
     pub api_key: String,
     pub secret_key: String,
+    /// Primary REST host (`Config::rest_api_endpoint`)
     pub host: String,
-    pub http_client: reqwest::Client,
+    /// `host` followed by `Config::fallback_hosts`; requests go to its active host
+    pub hosts: Arc<HostPool>,
+    /// HTTP stack requests go through; reqwest unless replaced with `set_transport`
+    pub transport: Arc<dyn AsyncHttpTransport>,
     pub verbose: bool,
+    pub custom_id_prefix: String,
+    /// Record/replay hook for tests; see [`Cassette`]
+    pub cassette: Option<Arc<Cassette>>,
+    /// Limiter every request waits on; shared by clones of this client
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Server clock offset applied to signed requests
+    pub time_sync: Option<Arc<TimeSync>>,
 }
 
 impl AsyncClient {
     /// Create a new async client
     pub fn new(api_key: Option<String>, secret_key: Option<String>) -> Self {
         let config = Config::default();
-        Self::new_with_config(api_key, secret_key, &config)
+        Self::try_new_with_config(api_key, secret_key, &config).expect("invalid HTTP client configuration")
     }
 
     /// Create a new async client with custom config
+    ///
+    /// # Panics
+    /// If the HTTP client can't be built from `config` (e.g. an invalid proxy
+    /// URL); use `try_new_with_config` to get an error instead.
+    #[deprecated(note = "use `try_new_with_config`, which returns an error instead of panicking")]
     pub fn new_with_config(
         api_key: Option<String>,
         secret_key: Option<String>,
         config: &Config
     ) -> Self {
-        let async_client = reqwest::Client::builder()
-            .pool_idle_timeout(None)
-            .build()
-            .unwrap();
+        Self::try_new_with_config(api_key, secret_key, config).expect("invalid HTTP client configuration")
+    }
 
+    /// Create a new async client with custom config, failing on invalid options
+    pub fn try_new_with_config(
+        api_key: Option<String>,
+        secret_key: Option<String>,
+        config: &Config,
+    ) -> Result<Self> {
+        Ok(Self::new_with_transport(api_key, secret_key, config, async_transport(config)?))
+    }
+
+    /// Create a new async client sending requests through `transport`
+    pub fn new_with_transport(
+        api_key: Option<String>,
+        secret_key: Option<String>,
+        config: &Config,
+        transport: impl AsyncHttpTransport + 'static,
+    ) -> Self {
         AsyncClient {
             api_key: api_key.unwrap_or_default(),
             secret_key: secret_key.unwrap_or_default(),
             host: config.rest_api_endpoint.clone(),
-            http_client: async_client,
+            hosts: Arc::new(HostPool::new(
+                std::iter::once(config.rest_api_endpoint.clone())
+                    .chain(config.fallback_hosts.iter().cloned())
+                    .collect(),
+            )),
+            transport: Arc::new(transport),
             verbose: false,
+            custom_id_prefix: config.custom_id_prefix.clone(),
+            cassette: None,
+            rate_limiter: None,
+            time_sync: None,
         }
     }
 
@@ -42,48 +88,161 @@ impl AsyncClient {
         self.verbose = verbose;
     }
 
-    /// Async GET request
-    pub async fn get(&self, endpoint: API, params: Option<String>) -> Result<String> {
-        let mut url = format!("{}{}", self.host,  String::from(endpoint));
-        if let Some(params) = params {
-            if !params.is_empty() {
-                url.push_str(&format!("?{}", params));
+    /// Replace the HTTP stack, e.g. with middleware or a test double
+    pub fn set_transport(&mut self, transport: impl AsyncHttpTransport + 'static) {
+        self.transport = Arc::new(transport);
+    }
+
+    /// Record responses into, or replay them from, a cassette
+    pub fn set_cassette(&mut self, cassette: Cassette) {
+        self.cassette = Some(Arc::new(cassette));
+    }
+
+    /// Delay requests to stay under LBank's rate limits
+    pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+    }
+
+    /// Stamp signed requests with server time instead of the local clock
+    pub fn set_time_sync(&mut self, time_sync: TimeSync) {
+        self.time_sync = Some(Arc::new(time_sync));
+    }
+
+    /// Measure the offset to LBank's clock now, returning it in milliseconds
+    ///
+    /// Does nothing but return 0 when no [`TimeSync`] is set.
+    pub async fn sync_time(&self) -> Result<i64> {
+        let Some(time_sync) = &self.time_sync else {
+            return Ok(0);
+        };
+        let sent = get_timestamp();
+        let server_time = self.execute(crate::common::requests::time().parse::<u64>()).await?;
+        Ok(time_sync.update(server_time, sent, get_timestamp()))
+    }
+
+    /// Current time in milliseconds on LBank's clock
+    ///
+    /// Estimated from the [`TimeSync`] offset when one is set, the local clock otherwise.
+    pub fn server_time(&self) -> u64 {
+        self.time_sync.as_ref().map_or_else(get_timestamp, |time_sync| time_sync.now())
+    }
+
+    /// Generate a fresh client order ID using the configured prefix
+    pub fn new_custom_id(&self) -> String {
+        client_order_id(&self.custom_id_prefix)
+    }
+
+    /// Send a request through the cassette and transport
+    ///
+    /// GET parameters go in the query string, POST and DELETE parameters in a
+    /// form-encoded body.
+    async fn send(&self, method: Method, endpoint: API, params: Option<String>) -> Result<String> {
+        let path = String::from(endpoint);
+        if let Some(cassette) = &self.cassette {
+            if let Some(reply) = cassette.intercept(method.as_str(), &path, params.as_deref()) {
+                return reply;
             }
         }
 
-        if self.verbose {
-            println!("Request URL: {}", url);
+        let query = match method {
+            Method::Get => params.as_deref().filter(|query| !query.is_empty()).map(|query| format!("?{}", query)),
+            Method::Post | Method::Delete => None,
+        };
+        let body = match method {
+            Method::Get => None,
+            Method::Post | Method::Delete => params.clone(),
+        };
+
+        let text = self.send_to_hosts(method, &path, query.as_deref().unwrap_or_default(), body).await?;
+        if let Some(cassette) = &self.cassette {
+            cassette.capture(method.as_str(), &path, params.as_deref(), &text)?;
         }
-
-        let response = self.http_client.get(&url).send().await?;
-        let text = response.text().await?;
-
         Ok(text)
     }
 
-    /// Async POST request
-    pub async fn post(&self, endpoint: API, params: Option<String>) -> Result<String> {
-        let url = format!("{}{}", self.host, String::from(endpoint));
+    /// Wait for the rate limiter, unless a replaying cassette answers requests
+    async fn throttle(&self) {
+        let replaying = self.cassette.as_ref().is_some_and(|cassette| cassette.mode() == CassetteMode::Replay);
+        if let Some(rate_limiter) = self.rate_limiter.as_ref().filter(|_| !replaying) {
+            rate_limiter.acquire_async().await;
+        }
+    }
 
-        if self.verbose {
-            println!("Request URL: {}", url);
-            if let Some(ref p) = params {
-                println!("Request Body: {}", p);
+    /// Send to the active host, failing over to the others (see [`HostPool`])
+    ///
+    /// Only GETs are resent after a 5xx; POSTs and DELETEs are resent only
+    /// when the connection failed and LBank can't have seen them.
+    async fn send_to_hosts(&self, method: Method, path: &str, query: &str, body: Option<String>) -> Result<String> {
+        let mut result = Err("No REST host configured".into());
+        for index in self.hosts.route() {
+            let url = format!("{}{}{}", self.hosts.hosts()[index], path, query);
+            if self.verbose {
+                println!("{} Request URL: {}", method.as_str(), url);
+                if let Some(body) = &body {
+                    println!("Request Body: {}", body);
+                }
+            }
+            let started = Instant::now();
+            result = self.transport.send(HttpRequest { method, url, body: body.clone() }).await;
+            match &result {
+                Ok(_) => {
+                    self.hosts.succeeded(index, started.elapsed());
+                    break;
+                }
+                Err(e) if e.is_host_unavailable() => {
+                    self.hosts.failed(index);
+                    if !can_fail_over(method, e) {
+                        break;
+                    }
+                }
+                Err(_) => break,
             }
         }
+        result
+    }
 
-        let mut request = self.http_client.post(&url);
+    /// Ping every host with `system_ping`, make the fastest healthy one active
+    /// and return the health of each
+    pub async fn check_hosts(&self) -> Vec<HostStatus> {
+        let ping = crate::market::requests::system_ping();
+        let mut latencies = Vec::with_capacity(self.hosts.hosts().len());
+        for host in self.hosts.hosts() {
+            let url = format!("{}{}", host, String::from(ping.endpoint));
+            let started = Instant::now();
+            let answered = self.transport.send(HttpRequest { method: ping.method, url, body: None }).await;
+            latencies.push(answered.ok().map(|_| started.elapsed()));
+        }
+        self.hosts.checked(&latencies);
+        self.hosts.status()
+    }
 
-        if let Some(body) = params {
-            request = request
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .body(body);
+    /// Sign (if required) and send a request, decoding its response
+    pub async fn execute<T>(&self, mut request: Request<T>) -> Result<T> {
+        let time_sync = self.time_sync.as_ref().filter(|_| request.signed);
+        if time_sync.is_some_and(|time_sync| time_sync.is_stale()) {
+            Box::pin(self.sync_time()).await?;
         }
+        // Stamp and sign only once the limiter lets the request go, so a long
+        // wait can't push the timestamp out of LBank's window
+        self.throttle().await;
+        if let Some(time_sync) = time_sync {
+            request.params.entry("timestamp".into()).or_insert_with(|| time_sync.now().to_string());
+        }
+        let params = request.encode(&self.api_key, &self.secret_key)?;
+        let text = self.send(request.method, request.endpoint, params).await?;
+        request.decode(&text)
+    }
 
-        let response = request.send().await?;
-        let text = response.text().await?;
+    /// Async GET request
+    pub async fn get(&self, endpoint: API, params: Option<String>) -> Result<String> {
+        self.throttle().await;
+        self.send(Method::Get, endpoint, params).await
+    }
 
-        Ok(text)
+    /// Async POST request
+    pub async fn post(&self, endpoint: API, params: Option<String>) -> Result<String> {
+        self.throttle().await;
+        self.send(Method::Post, endpoint, params).await
     }
 
     /// Async GET request with JSON parsing
@@ -112,25 +271,8 @@ impl AsyncClient {
 
     /// Async DELETE request with signature
     pub async fn delete_signed<T: DeserializeOwned>(&self, endpoint: API, params: Option<String>) -> Result<T> {
-        let url = format!("{}{}", self.host, String::from(endpoint));
-
-        if self.verbose {
-            println!("DELETE Request URL: {}", url);
-            if let Some(ref p) = params {
-                println!("Request Params: {}", p);
-            }
-        }
-
-        let mut request = self.http_client.delete(&url);
-
-        if let Some(body) = params {
-            request = request
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .body(body);
-        }
-
-        let response = request.send().await?;
-        let text = response.text().await?;
+        self.throttle().await;
+        let text = self.send(Method::Delete, endpoint, params).await?;
         Ok(serde_json::from_str(&text)?)
     }
 }
@@ -168,13 +310,15 @@ mod tests {
     fn test_async_client_with_custom_config() {
         let mut config = Config::default();
         config.rest_api_endpoint = "https://custom.api.com".to_string();
+        config.custom_id_prefix = "bot-".to_string();
 
-        let client = AsyncClient::new_with_config(
+        let client = AsyncClient::try_new_with_config(
             Some("key".into()),
             Some("secret".into()),
             &config
-        );
+        ).unwrap();
 
         assert_eq!(client.host, "https://custom.api.com");
+        assert!(client.new_custom_id().starts_with("bot-"));
     }
 }
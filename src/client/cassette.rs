@@ -0,0 +1,266 @@
+use crate::errors::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Parameters replaced by [`REDACTED`] before an interaction is stored
+///
+/// `timestamp`, `echostr` and the generated client IDs (`custom_id`,
+/// `withdrawOrderId`) change on every call, so they are redacted too to keep
+/// replays matching. More can be added with [`Cassette::redact`].
+pub const REDACTED_PARAMS: [&str; 6] = ["api_key", "sign", "echostr", "timestamp", "custom_id", "withdrawOrderId"];
+
+/// Placeholder stored instead of a redacted parameter value
+pub const REDACTED: &str = "REDACTED";
+
+/// What a client does with a [`Cassette`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and append every response to the cassette file
+    Record,
+    /// Answer requests from the cassette without touching the network
+    Replay,
+    /// Send requests as usual; the cassette is ignored
+    Passthrough,
+}
+
+/// A recorded request and the raw response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub path: String,
+    /// Query string or form body parameters, with [`REDACTED_PARAMS`] redacted
+    pub params: BTreeMap<String, String>,
+    pub response: String,
+}
+
+impl Interaction {
+    fn matches(&self, method: &str, path: &str, params: &BTreeMap<String, String>) -> bool {
+        self.method == method && self.path == path && &self.params == params
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    /// Interactions already answered in replay mode
+    played: Vec<bool>,
+}
+
+/// Fixture file of HTTP interactions for deterministic tests
+///
+/// Attach one to a client with `set_cassette`. In [`CassetteMode::Record`]
+/// every `get`/`post`/`delete_signed` response is appended to the file as it
+/// arrives. In [`CassetteMode::Replay`] each request is answered by the first
+/// unplayed interaction with the same method, path and (redacted) parameters,
+/// and a request without a recording fails instead of reaching the network.
+pub struct Cassette {
+    mode: CassetteMode,
+    path: Option<PathBuf>,
+    tape: Mutex<Tape>,
+    redacted: Vec<String>,
+}
+
+impl Cassette {
+    /// Record into `path`, replacing any existing recording
+    pub fn record(path: impl AsRef<Path>) -> Cassette {
+        Cassette {
+            mode: CassetteMode::Record,
+            path: Some(path.as_ref().to_path_buf()),
+            tape: Mutex::new(Tape::default()),
+            redacted: default_redacted(),
+        }
+    }
+
+    /// Replay the recording at `path`
+    pub fn replay(path: impl AsRef<Path>) -> Result<Cassette> {
+        let file: CassetteFile = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        let mut cassette = Cassette::from_interactions(file.interactions);
+        cassette.path = Some(path.as_ref().to_path_buf());
+        Ok(cassette)
+    }
+
+    /// Replay in-memory interactions
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Cassette {
+        Cassette {
+            mode: CassetteMode::Replay,
+            path: None,
+            tape: Mutex::new(Tape {
+                played: vec![false; interactions.len()],
+                interactions,
+            }),
+            redacted: default_redacted(),
+        }
+    }
+
+    /// A cassette that lets every request through
+    pub fn passthrough() -> Cassette {
+        Cassette {
+            mode: CassetteMode::Passthrough,
+            path: None,
+            tape: Mutex::new(Tape::default()),
+            redacted: default_redacted(),
+        }
+    }
+
+    /// Also redact `params`, e.g. parameters a test generates randomly
+    pub fn redact(mut self, params: &[&str]) -> Cassette {
+        self.redacted.extend(params.iter().map(|param| param.to_string()));
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Interactions recorded or loaded so far
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.tape().interactions.clone()
+    }
+
+    /// Whether every loaded interaction has been replayed
+    pub fn is_exhausted(&self) -> bool {
+        self.tape().played.iter().all(|played| *played)
+    }
+
+    fn tape(&self) -> MutexGuard<'_, Tape> {
+        self.tape.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// In replay mode, the recorded answer to a request
+    ///
+    /// Returns `None` when the request should go to the network.
+    pub(crate) fn intercept(&self, method: &str, path: &str, params: Option<&str>) -> Option<Result<String>> {
+        if self.mode != CassetteMode::Replay {
+            return None;
+        }
+        let params = self.redact_params(params);
+        let mut tape = self.tape();
+        let Tape { interactions, played } = &mut *tape;
+        let reply = interactions
+            .iter()
+            .zip(played.iter_mut())
+            .find(|(interaction, played)| !**played && interaction.matches(method, path, &params))
+            .map(|(interaction, played)| {
+                *played = true;
+                interaction.response.clone()
+            })
+            .ok_or_else(|| format!("No recorded response for {} {} {:?}", method, path, params).into());
+        Some(reply)
+    }
+
+    /// In record mode, append a response and save the cassette file
+    pub(crate) fn capture(&self, method: &str, path: &str, params: Option<&str>, response: &str) -> Result<()> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+        let mut tape = self.tape();
+        tape.interactions.push(Interaction {
+            method: method.to_string(),
+            path: path.to_string(),
+            params: self.redact_params(params),
+            response: response.to_string(),
+        });
+        if let Some(file) = &self.path {
+            let contents = serde_json::to_string_pretty(&CassetteFile {
+                interactions: tape.interactions.clone(),
+            })?;
+            std::fs::write(file, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Split `a=1&b=2` and redact the cassette's redacted parameters
+    fn redact_params(&self, params: Option<&str>) -> BTreeMap<String, String> {
+        params
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                let value = if self.redacted.iter().any(|param| param == key) { REDACTED } else { value };
+                (key.to_string(), value.to_string())
+            })
+            .collect()
+    }
+}
+
+fn default_redacted() -> Vec<String> {
+    REDACTED_PARAMS.iter().map(|param| param.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Wallet, API};
+    use crate::client::Client;
+
+    #[test]
+    fn test_record_then_replay() {
+        let path = std::env::temp_dir().join(format!("lbank-cassette-{}.json", std::process::id()));
+        let recorder = Cassette::record(&path);
+        recorder
+            .capture("POST", "/v2/supplement/user_info.do", Some("api_key=key&echostr=abc&sign=xyz&timestamp=1"), "{\"result\":true}")
+            .unwrap();
+        assert_eq!(recorder.interactions()[0].params["api_key"], REDACTED);
+
+        let mut client = Client::new(Some("other_key".into()), Some("secret".into()));
+        client.set_cassette(Cassette::replay(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let params = "api_key=other_key&echostr=def&sign=uvw&timestamp=2";
+        let response = client.post(API::Wallet(Wallet::UserInfo), Some(params.into())).unwrap();
+        assert_eq!(response, "{\"result\":true}");
+
+        // Each interaction answers once, and unknown requests never reach the network
+        assert!(client.cassette.as_ref().unwrap().is_exhausted());
+        assert!(client.post(API::Wallet(Wallet::UserInfo), Some(params.into())).is_err());
+    }
+
+    #[test]
+    fn test_extra_redacted_params() {
+        let cassette = Cassette::from_interactions(Vec::new()).redact(&["nonce"]);
+        let params = cassette.redact_params(Some("nonce=1&custom_id=bot-1&symbol=lbk_usdt"));
+        assert_eq!((params["nonce"].as_str(), params["custom_id"].as_str()), (REDACTED, REDACTED));
+        assert_eq!(params["symbol"], "lbk_usdt");
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+    use crate::spot::Spot;
+
+    fn spot(server: &MockServer, cassette: Cassette) -> Spot {
+        let mut client = server.test_client();
+        client.set_cassette(cassette);
+        Spot { client }
+    }
+
+    #[test]
+    fn test_place_order_replays() {
+        let server = MockServer::start_for_tests();
+        server.add_pair("lbk_usdt", 0.01);
+        server.set_balance("usdt", 100.0);
+        let path = std::env::temp_dir().join(format!("lbank-cassette-order-{}.json", std::process::id()));
+
+        let recorded = spot(&server, Cassette::record(&path))
+            .place_order("lbk_usdt", "buy", Some("0.008"), Some("1000"), None)
+            .unwrap();
+
+        // A fresh custom_id and timestamp still match the recording
+        let replay = spot(&server, Cassette::replay(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let replayed = replay.place_order("lbk_usdt", "buy", Some("0.008"), Some("1000"), None).unwrap();
+        assert_eq!(replayed, recorded);
+        assert_eq!(server.balance("usdt"), (92.0, 8.0), "the replay never reached the server");
+        assert!(replay.place_order("lbk_usdt", "buy", Some("0.008"), Some("999"), None).is_err());
+    }
+}
@@ -1,40 +1,86 @@
 use serde::de::DeserializeOwned;
-use crate::{api::API, config::Config, errors::Result};
+use std::sync::Arc;
+use std::time::Instant;
+use super::cassette::{Cassette, CassetteMode};
+use super::host_pool::{can_fail_over, HostPool, HostStatus};
+use super::rate_limit::RateLimiter;
+use super::time_sync::TimeSync;
+use super::transport::{blocking_transport, HttpTransport, HttpRequest, Method};
+use crate::{api::API, config::Config, errors::Result, request::Request, util::{client_order_id, get_timestamp}};
 
 /// Blocking HTTP client for synchronous operations
 #[derive(Clone)]
 pub struct Client {
     pub api_key: String,
     pub secret_key: String,
+    /// Primary REST host (`Config::rest_api_endpoint`)
     pub host: String,
-    pub http_client: reqwest::blocking::Client,
+    /// `host` followed by `Config::fallback_hosts`; requests go to its active host
+    pub hosts: Arc<HostPool>,
+    /// HTTP stack requests go through; reqwest unless replaced with `set_transport`
+    pub transport: Arc<dyn HttpTransport>,
     pub verbose: bool,
+    pub custom_id_prefix: String,
+    /// Record/replay hook for tests; see [`Cassette`]
+    pub cassette: Option<Arc<Cassette>>,
+    /// Limiter every request waits on; shared by clones of this client
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// Server clock offset applied to signed requests
+    pub time_sync: Option<Arc<TimeSync>>,
 }
 
 impl Client {
     /// Create a new blocking client
     pub fn new(api_key: Option<String>, secret_key: Option<String>) -> Self {
         let config = Config::default();
-        Self::new_with_config(api_key, secret_key, &config)
+        Self::try_new_with_config(api_key, secret_key, &config).expect("invalid HTTP client configuration")
     }
 
     /// Create a new blocking client with custom config
+    ///
+    /// # Panics
+    /// If the HTTP client can't be built from `config` (e.g. an invalid proxy
+    /// URL); use `try_new_with_config` to get an error instead.
+    #[deprecated(note = "use `try_new_with_config`, which returns an error instead of panicking")]
     pub fn new_with_config(
         api_key: Option<String>,
         secret_key: Option<String>,
         config: &Config
     ) -> Self {
-        let blocking_client = reqwest::blocking::Client::builder()
-            .pool_idle_timeout(None)
-            .build()
-            .unwrap();
+        Self::try_new_with_config(api_key, secret_key, config).expect("invalid HTTP client configuration")
+    }
+
+    /// Create a new blocking client with custom config, failing on invalid options
+    pub fn try_new_with_config(
+        api_key: Option<String>,
+        secret_key: Option<String>,
+        config: &Config,
+    ) -> Result<Self> {
+        Ok(Self::new_with_transport(api_key, secret_key, config, blocking_transport(config)?))
+    }
 
+    /// Create a new blocking client sending requests through `transport`
+    pub fn new_with_transport(
+        api_key: Option<String>,
+        secret_key: Option<String>,
+        config: &Config,
+        transport: impl HttpTransport + 'static,
+    ) -> Self {
         Client {
             api_key: api_key.unwrap_or_default(),
             secret_key: secret_key.unwrap_or_default(),
             host: config.rest_api_endpoint.clone(),
-            http_client: blocking_client,
+            hosts: Arc::new(HostPool::new(
+                std::iter::once(config.rest_api_endpoint.clone())
+                    .chain(config.fallback_hosts.iter().cloned())
+                    .collect(),
+            )),
+            transport: Arc::new(transport),
             verbose: false,
+            custom_id_prefix: config.custom_id_prefix.clone(),
+            cassette: None,
+            rate_limiter: None,
+            time_sync: None,
         }
     }
 
@@ -42,48 +88,161 @@ impl Client {
         self.verbose = verbose;
     }
 
-    /// Blocking GET request
-    pub fn get(&self, endpoint: API, params: Option<String>) -> Result<String> {
-        let mut url = format!("{}{}", self.host, String::from(endpoint));
-        if let Some(params) = params {
-            if !params.is_empty() {
-                url.push_str(&format!("?{}", params));
+    /// Replace the HTTP stack, e.g. with middleware or a test double
+    pub fn set_transport(&mut self, transport: impl HttpTransport + 'static) {
+        self.transport = Arc::new(transport);
+    }
+
+    /// Record responses into, or replay them from, a cassette
+    pub fn set_cassette(&mut self, cassette: Cassette) {
+        self.cassette = Some(Arc::new(cassette));
+    }
+
+    /// Delay requests to stay under LBank's rate limits
+    pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+    }
+
+    /// Stamp signed requests with server time instead of the local clock
+    pub fn set_time_sync(&mut self, time_sync: TimeSync) {
+        self.time_sync = Some(Arc::new(time_sync));
+    }
+
+    /// Measure the offset to LBank's clock now, returning it in milliseconds
+    ///
+    /// Does nothing but return 0 when no [`TimeSync`] is set.
+    pub fn sync_time(&self) -> Result<i64> {
+        let Some(time_sync) = &self.time_sync else {
+            return Ok(0);
+        };
+        let sent = get_timestamp();
+        let server_time = self.execute(crate::common::requests::time().parse::<u64>())?;
+        Ok(time_sync.update(server_time, sent, get_timestamp()))
+    }
+
+    /// Current time in milliseconds on LBank's clock
+    ///
+    /// Estimated from the [`TimeSync`] offset when one is set, the local clock otherwise.
+    pub fn server_time(&self) -> u64 {
+        self.time_sync.as_ref().map_or_else(get_timestamp, |time_sync| time_sync.now())
+    }
+
+    /// Generate a fresh client order ID using the configured prefix
+    pub fn new_custom_id(&self) -> String {
+        client_order_id(&self.custom_id_prefix)
+    }
+
+    /// Send a request through the cassette and transport
+    ///
+    /// GET parameters go in the query string, POST and DELETE parameters in a
+    /// form-encoded body.
+    fn send(&self, method: Method, endpoint: API, params: Option<String>) -> Result<String> {
+        let path = String::from(endpoint);
+        if let Some(cassette) = &self.cassette {
+            if let Some(reply) = cassette.intercept(method.as_str(), &path, params.as_deref()) {
+                return reply;
             }
         }
 
-        if self.verbose {
-            println!("Request URL: {}", url);
+        let query = match method {
+            Method::Get => params.as_deref().filter(|query| !query.is_empty()).map(|query| format!("?{}", query)),
+            Method::Post | Method::Delete => None,
+        };
+        let body = match method {
+            Method::Get => None,
+            Method::Post | Method::Delete => params.clone(),
+        };
+
+        let text = self.send_to_hosts(method, &path, query.as_deref().unwrap_or_default(), body)?;
+        if let Some(cassette) = &self.cassette {
+            cassette.capture(method.as_str(), &path, params.as_deref(), &text)?;
         }
-
-        let response = self.http_client.get(&url).send()?;
-        let text = response.text()?;
-
         Ok(text)
     }
 
-    /// Blocking POST request
-    pub fn post(&self, endpoint: API, params: Option<String>) -> Result<String> {
-        let url = format!("{}{}", self.host, String::from(endpoint));
+    /// Wait for the rate limiter, unless a replaying cassette answers requests
+    fn throttle(&self) {
+        let replaying = self.cassette.as_ref().is_some_and(|cassette| cassette.mode() == CassetteMode::Replay);
+        if let Some(rate_limiter) = self.rate_limiter.as_ref().filter(|_| !replaying) {
+            rate_limiter.acquire();
+        }
+    }
 
-        if self.verbose {
-            println!("Request URL: {}", url);
-            if let Some(ref p) = params {
-                println!("Request Body: {}", p);
+    /// Send to the active host, failing over to the others (see [`HostPool`])
+    ///
+    /// Only GETs are resent after a 5xx; POSTs and DELETEs are resent only
+    /// when the connection failed and LBank can't have seen them.
+    fn send_to_hosts(&self, method: Method, path: &str, query: &str, body: Option<String>) -> Result<String> {
+        let mut result = Err("No REST host configured".into());
+        for index in self.hosts.route() {
+            let url = format!("{}{}{}", self.hosts.hosts()[index], path, query);
+            if self.verbose {
+                println!("{} Request URL: {}", method.as_str(), url);
+                if let Some(body) = &body {
+                    println!("Request Body: {}", body);
+                }
+            }
+            let started = Instant::now();
+            result = self.transport.send(HttpRequest { method, url, body: body.clone() });
+            match &result {
+                Ok(_) => {
+                    self.hosts.succeeded(index, started.elapsed());
+                    break;
+                }
+                Err(e) if e.is_host_unavailable() => {
+                    self.hosts.failed(index);
+                    if !can_fail_over(method, e) {
+                        break;
+                    }
+                }
+                Err(_) => break,
             }
         }
+        result
+    }
 
-        let mut request = self.http_client.post(&url);
+    /// Ping every host with `system_ping`, make the fastest healthy one active
+    /// and return the health of each
+    pub fn check_hosts(&self) -> Vec<HostStatus> {
+        let ping = crate::market::requests::system_ping();
+        let mut latencies = Vec::with_capacity(self.hosts.hosts().len());
+        for host in self.hosts.hosts() {
+            let url = format!("{}{}", host, String::from(ping.endpoint));
+            let started = Instant::now();
+            let answered = self.transport.send(HttpRequest { method: ping.method, url, body: None });
+            latencies.push(answered.ok().map(|_| started.elapsed()));
+        }
+        self.hosts.checked(&latencies);
+        self.hosts.status()
+    }
 
-        if let Some(body) = params {
-            request = request
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .body(body);
+    /// Sign (if required) and send a request, decoding its response
+    pub fn execute<T>(&self, mut request: Request<T>) -> Result<T> {
+        let time_sync = self.time_sync.as_ref().filter(|_| request.signed);
+        if time_sync.is_some_and(|time_sync| time_sync.is_stale()) {
+            self.sync_time()?;
         }
+        // Stamp and sign only once the limiter lets the request go, so a long
+        // wait can't push the timestamp out of LBank's window
+        self.throttle();
+        if let Some(time_sync) = time_sync {
+            request.params.entry("timestamp".into()).or_insert_with(|| time_sync.now().to_string());
+        }
+        let params = request.encode(&self.api_key, &self.secret_key)?;
+        let text = self.send(request.method, request.endpoint, params)?;
+        request.decode(&text)
+    }
 
-        let response = request.send()?;
-        let text = response.text()?;
+    /// Blocking GET request
+    pub fn get(&self, endpoint: API, params: Option<String>) -> Result<String> {
+        self.throttle();
+        self.send(Method::Get, endpoint, params)
+    }
 
-        Ok(text)
+    /// Blocking POST request
+    pub fn post(&self, endpoint: API, params: Option<String>) -> Result<String> {
+        self.throttle();
+        self.send(Method::Post, endpoint, params)
     }
 
     /// Blocking GET request with JSON parsing
@@ -112,25 +271,8 @@ impl Client {
 
     /// Blocking DELETE request with signature
     pub fn delete_signed<T: DeserializeOwned>(&self, endpoint: API, params: Option<String>) -> Result<T> {
-        let url = format!("{}{}", self.host, String::from(endpoint));
-
-        if self.verbose {
-            println!("DELETE Request URL: {}", url);
-            if let Some(ref p) = params {
-                println!("Request Params: {}", p);
-            }
-        }
-
-        let mut request = self.http_client.delete(&url);
-
-        if let Some(body) = params {
-            request = request
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .body(body);
-        }
-
-        let response = request.send()?;
-        let text = response.text()?;
+        self.throttle();
+        let text = self.send(Method::Delete, endpoint, params)?;
         Ok(serde_json::from_str(&text)?)
     }
 }
@@ -168,13 +310,128 @@ mod tests {
     fn test_client_with_custom_config() {
         let mut config = Config::default();
         config.rest_api_endpoint = "https://custom.api.com".to_string();
+        config.custom_id_prefix = "bot-".to_string();
 
-        let client = Client::new_with_config(
+        let client = Client::try_new_with_config(
             Some("key".into()),
             Some("secret".into()),
             &config
-        );
+        ).unwrap();
 
         assert_eq!(client.host, "https://custom.api.com");
+        assert!(client.new_custom_id().starts_with("bot-"));
+    }
+
+    #[test]
+    fn test_custom_transport() {
+        use crate::client::HttpTransport;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Fake(Mutex<Vec<HttpRequest>>);
+
+        impl HttpTransport for Arc<Fake> {
+            fn send(&self, request: HttpRequest) -> Result<String> {
+                self.0.lock().unwrap().push(request);
+                Ok("{\"result\":true}".into())
+            }
+        }
+
+        let fake = Arc::new(Fake::default());
+        let client = Client::new_with_transport(None, None, &Config::default(), fake.clone());
+        client.get(API::Market(crate::api::Market::Depth), Some("symbol=lbk_usdt".into())).unwrap();
+        client.post(API::Wallet(crate::api::Wallet::UserInfo), Some("api_key=k".into())).unwrap();
+
+        let requests = fake.0.lock().unwrap();
+        assert_eq!(requests[0].method, Method::Get);
+        assert!(requests[0].url.ends_with("?symbol=lbk_usdt"));
+        assert_eq!(requests[0].body, None);
+        assert_eq!(requests[1].method, Method::Post);
+        assert_eq!(requests[1].body.as_deref(), Some("api_key=k"));
+    }
+
+    #[test]
+    fn test_signed_after_rate_limit_wait() {
+        use crate::client::HttpTransport;
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        /// Age of each request's `timestamp` when it reached the transport (ms)
+        #[derive(Default)]
+        struct Ages(Mutex<Vec<u64>>);
+
+        impl HttpTransport for Arc<Ages> {
+            fn send(&self, request: HttpRequest) -> Result<String> {
+                let body = request.body.unwrap_or_default();
+                let timestamp: u64 = body
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("timestamp="))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                self.0.lock().unwrap().push(get_timestamp() - timestamp);
+                Ok("{\"result\":true}".into())
+            }
+        }
+
+        let ages = Arc::new(Ages::default());
+        let secret = "0123456789abcdef0123456789abcdef";
+        let config = Config::default();
+        let mut client = Client::new_with_transport(Some("key".into()), Some(secret.into()), &config, ages.clone());
+        client.set_rate_limiter(RateLimiter::new(1, Duration::from_millis(300)));
+        for _ in 0..2 {
+            client.execute(Request::signed(API::Spot(crate::api::Spot::AccountInfo))).unwrap();
+        }
+
+        // The second request waited for the limiter before it was stamped
+        let ages = ages.0.lock().unwrap();
+        assert!(ages.iter().all(|&age| age < 200), "stale timestamps: {:?}", ages);
+    }
+
+    /// Serve `status` to every connection, counting requests
+    fn http_host(status: &'static str) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let _ = stream.read(&mut [0; 4096]);
+                let body = "{\"result\":true}";
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (url, hits)
+    }
+
+    #[test]
+    fn test_post_not_resent_after_server_error() {
+        use std::sync::atomic::Ordering;
+
+        let (failing, failing_hits) = http_host("503 Service Unavailable");
+        let (healthy, healthy_hits) = http_host("200 OK");
+        let config = Config {
+            rest_api_endpoint: failing,
+            fallback_hosts: vec![healthy],
+            ..Config::default()
+        };
+        let client = Client::try_new_with_config(None, None, &config).unwrap();
+
+        let err = client.post(API::Spot(crate::api::Spot::CreateOrder), Some("symbol=lbk_usdt".into())).unwrap_err();
+        assert!(err.is_ambiguous());
+        assert_eq!((failing_hits.load(Ordering::SeqCst), healthy_hits.load(Ordering::SeqCst)), (1, 0));
+
+        // GETs are safe to resend
+        client.get(API::Market(crate::api::Market::Depth), None).unwrap();
+        assert_eq!(healthy_hits.load(Ordering::SeqCst), 1);
     }
 }
@@ -0,0 +1,174 @@
+use super::transport::Method;
+use crate::errors::Error;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Health of one REST host as last observed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostStatus {
+    pub host: String,
+    /// False after a connection error or 5xx, until the host answers again
+    pub healthy: bool,
+    /// Round trip of the last successful request or health check
+    pub latency: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Health {
+    failed: bool,
+    latency: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    health: Vec<Health>,
+    active: usize,
+}
+
+/// Whether a request that failed with `error` may be resent to another host
+///
+/// A failed connection never reached LBank, so any request may go elsewhere.
+/// A 5xx from a gateway doesn't prove LBank didn't act on the request, so
+/// POSTs and DELETEs (orders, withdrawals) are only resent after connection
+/// errors; other failures go back to the caller as ambiguous errors.
+pub(crate) fn can_fail_over(method: Method, error: &Error) -> bool {
+    match method {
+        Method::Get => error.is_host_unavailable(),
+        Method::Post | Method::Delete => error.is_connect(),
+    }
+}
+
+/// Prioritized REST hosts with sticky routing and failover
+///
+/// Requests go to the active host. When it fails (see [`can_fail_over`]),
+/// the remaining healthy hosts are tried fastest first (unmeasured ones in
+/// priority order), then the failed ones, and whichever answers becomes the
+/// active host. Shared by every clone of a client.
+#[derive(Debug)]
+pub struct HostPool {
+    hosts: Vec<String>,
+    state: Mutex<PoolState>,
+}
+
+impl HostPool {
+    /// Pool over `hosts` in priority order; the first one starts out active
+    pub fn new(hosts: Vec<String>) -> Self {
+        let mut unique: Vec<String> = Vec::with_capacity(hosts.len());
+        for host in hosts {
+            if !unique.contains(&host) {
+                unique.push(host);
+            }
+        }
+        HostPool {
+            state: Mutex::new(PoolState {
+                health: vec![Health::default(); unique.len()],
+                active: 0,
+            }),
+            hosts: unique,
+        }
+    }
+
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    /// Host requests currently go to
+    pub fn active(&self) -> &str {
+        &self.hosts[self.state().active]
+    }
+
+    pub fn status(&self) -> Vec<HostStatus> {
+        let state = self.state();
+        self.hosts
+            .iter()
+            .zip(&state.health)
+            .map(|(host, health)| HostStatus {
+                host: host.clone(),
+                healthy: !health.failed,
+                latency: health.latency,
+            })
+            .collect()
+    }
+
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Indices of the hosts to try for a request, in order
+    pub(crate) fn route(&self) -> Vec<usize> {
+        let state = self.state();
+        let mut others: Vec<usize> = (0..self.hosts.len()).filter(|&i| i != state.active).collect();
+        // Stable sort keeps priority order among equals
+        others.sort_by_key(|&i| {
+            let health = state.health[i];
+            (health.failed, health.latency.is_none(), health.latency)
+        });
+        std::iter::once(state.active).chain(others).collect()
+    }
+
+    /// A request to `index` succeeded; it becomes the active host
+    pub(crate) fn succeeded(&self, index: usize, latency: Duration) {
+        let mut state = self.state();
+        state.health[index] = Health {
+            failed: false,
+            latency: Some(latency),
+        };
+        state.active = index;
+    }
+
+    /// A request to `index` could not be served
+    pub(crate) fn failed(&self, index: usize) {
+        self.state().health[index].failed = true;
+    }
+
+    /// Record a health check of every host and make the fastest healthy one active
+    ///
+    /// `latencies[i]` is `None` if host `i` failed the check. The active host
+    /// is kept when every host failed.
+    pub(crate) fn checked(&self, latencies: &[Option<Duration>]) {
+        let mut state = self.state();
+        for (health, latency) in state.health.iter_mut().zip(latencies) {
+            *health = Health {
+                failed: latency.is_none(),
+                latency: *latency,
+            };
+        }
+        if let Some(fastest) = (0..latencies.len()).filter(|&i| latencies[i].is_some()).min_by_key(|&i| latencies[i]) {
+            state.active = fastest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> HostPool {
+        HostPool::new(vec!["https://a".into(), "https://b".into(), "https://c".into(), "https://a".into()])
+    }
+
+    #[test]
+    fn test_failover_is_sticky() {
+        let pool = pool();
+        assert_eq!(pool.hosts().len(), 3);
+        assert_eq!(pool.route(), vec![0, 1, 2]);
+
+        pool.failed(0);
+        pool.succeeded(2, Duration::from_millis(40));
+        assert_eq!(pool.active(), "https://c");
+        // Measured healthy hosts first, then unmeasured, then failed
+        assert_eq!(pool.route(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_health_check_picks_fastest() {
+        let pool = pool();
+        pool.checked(&[Some(Duration::from_millis(80)), None, Some(Duration::from_millis(20))]);
+        assert_eq!(pool.active(), "https://c");
+        assert_eq!(pool.route(), vec![2, 0, 1]);
+        assert!(!pool.status()[1].healthy);
+
+        pool.checked(&[None, None, None]);
+        assert_eq!(pool.active(), "https://c");
+    }
+}
@@ -0,0 +1,379 @@
+use super::rate_limit::RateLimiter;
+use super::self_check::SelfCheckReport;
+use super::time_sync::TimeSync;
+use super::{AsyncClient, Client};
+use crate::account::{Account, AsyncAccount};
+use crate::common::{AsyncCommon, Common};
+use crate::config::{Config, TlsBackend};
+use crate::errors::{ErrorKind, Result};
+use crate::market::{AsyncMarket, Market};
+use crate::model::Permission;
+use crate::spot::{AsyncSpot, Spot};
+use crate::wallet::{AsyncWallet, Wallet};
+use crate::util::get_timestamp;
+use crate::websocket::{AsyncWebSocket, WebSocket};
+use std::time::Duration;
+
+/// Builder for [`LBankClient`] and [`AsyncLBankClient`]
+///
+/// Rate limiting defaults to [`RateLimiter::default`]; time sync is off
+/// unless enabled with `time_sync`.
+pub struct LBankClientBuilder {
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    config: Config,
+    rate_limiter: Option<RateLimiter>,
+    time_sync: Option<Duration>,
+    verbose: bool,
+}
+
+impl LBankClientBuilder {
+    /// API key and secret (hex HMAC secret or RSA private key)
+    pub fn credentials(mut self, api_key: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self.secret_key = Some(secret_key.into());
+        self
+    }
+
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Replace the default rate limiter
+    pub fn rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Send requests without client-side rate limiting
+    pub fn no_rate_limit(mut self) -> Self {
+        self.rate_limiter = None;
+        self
+    }
+
+    /// Stamp signed requests with server time, refreshing the offset every `resync_interval`
+    pub fn time_sync(mut self, resync_interval: Duration) -> Self {
+        self.time_sync = Some(resync_interval);
+        self
+    }
+
+    /// Time allowed to establish a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Time allowed for a whole request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// HTTP(S) or, with the `socks` feature, SOCKS5 proxy URL
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.config.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.config.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn tls_backend(mut self, tls_backend: TlsBackend) -> Self {
+        self.config.tls_backend = tls_backend;
+        self
+    }
+
+    /// Hosts tried in order when the main REST host can't be reached
+    pub fn fallback_hosts<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.fallback_hosts = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Build a blocking client, failing on invalid connection options
+    pub fn build(self) -> Result<LBankClient> {
+        let mut client = Client::try_new_with_config(self.api_key, self.secret_key, &self.config)?;
+        client.set_verbose(self.verbose);
+        if let Some(rate_limiter) = self.rate_limiter {
+            client.set_rate_limiter(rate_limiter);
+        }
+        if let Some(interval) = self.time_sync {
+            client.set_time_sync(TimeSync::new(interval));
+        }
+        Ok(LBankClient {
+            client,
+            config: self.config,
+        })
+    }
+
+    /// Build an async client, failing on invalid connection options
+    pub fn build_async(self) -> Result<AsyncLBankClient> {
+        let mut client = AsyncClient::try_new_with_config(self.api_key, self.secret_key, &self.config)?;
+        client.set_verbose(self.verbose);
+        if let Some(rate_limiter) = self.rate_limiter {
+            client.set_rate_limiter(rate_limiter);
+        }
+        if let Some(interval) = self.time_sync {
+            client.set_time_sync(TimeSync::new(interval));
+        }
+        Ok(AsyncLBankClient {
+            client,
+            config: self.config,
+        })
+    }
+}
+
+impl Default for LBankClientBuilder {
+    fn default() -> Self {
+        LBankClientBuilder {
+            api_key: None,
+            secret_key: None,
+            config: Config::default(),
+            rate_limiter: Some(RateLimiter::default()),
+            time_sync: None,
+            verbose: false,
+        }
+    }
+}
+
+/// Blocking LBank client with a view per API domain
+///
+/// Every view is a cheap clone of the same [`Client`], so they share its
+/// connection pool, rate limiter and time sync.
+#[derive(Clone)]
+pub struct LBankClient {
+    client: Client,
+    config: Config,
+}
+
+impl LBankClient {
+    pub fn builder() -> LBankClientBuilder {
+        LBankClientBuilder::default()
+    }
+
+    /// The underlying HTTP client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Trading pairs, accuracy, asset configs and server time
+    pub fn general(&self) -> Common {
+        Common { client: self.client.clone() }
+    }
+
+    pub fn market(&self) -> Market {
+        Market { client: self.client.clone() }
+    }
+
+    pub fn spot(&self) -> Spot {
+        Spot { client: self.client.clone() }
+    }
+
+    pub fn wallet(&self) -> Wallet {
+        Wallet { client: self.client.clone() }
+    }
+
+    pub fn account(&self) -> Account {
+        Account { client: self.client.clone() }
+    }
+
+    /// WebSocket URL and subscribeKey management
+    pub fn ws(&self) -> WebSocket {
+        WebSocket {
+            client: self.client.clone(),
+            endpoint: self.config.ws_endpoint.clone(),
+        }
+    }
+
+    /// Check the client is fit to trade before relying on it
+    ///
+    /// Measures clock skew against `timestamp.do` (refreshing time sync when
+    /// enabled), then signs an `api_restrictions` request, so bad credentials
+    /// surface as that request's LBank error. See [`SelfCheckReport::new`]
+    /// for what fails the check; warnings, such as the 30-day expiry of keys
+    /// not bound to an IP, are returned in the report.
+    pub fn self_check(&self, required: &[Permission]) -> Result<SelfCheckReport> {
+        if self.client.api_key.is_empty() {
+            return Err(ErrorKind::SelfCheckFailed("no API credentials".into()).into());
+        }
+        let sent = get_timestamp();
+        let server_time = self.client.execute(crate::common::requests::time().parse::<u64>())?;
+        let received = get_timestamp();
+        let skew = match &self.client.time_sync {
+            Some(time_sync) => time_sync.update(server_time, sent, received),
+            None => server_time as i64 - ((sent + received) / 2) as i64,
+        };
+        let permissions = self.account().api_restrictions()?;
+        SelfCheckReport::new(permissions, skew, self.client.time_sync.is_some(), required, get_timestamp() as i64)
+    }
+}
+
+/// Async LBank client with a view per API domain
+///
+/// See [`LBankClient`].
+#[derive(Clone)]
+pub struct AsyncLBankClient {
+    client: AsyncClient,
+    config: Config,
+}
+
+impl AsyncLBankClient {
+    pub fn builder() -> LBankClientBuilder {
+        LBankClientBuilder::default()
+    }
+
+    /// The underlying HTTP client
+    pub fn client(&self) -> &AsyncClient {
+        &self.client
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Trading pairs, accuracy, asset configs and server time
+    pub fn general(&self) -> AsyncCommon {
+        AsyncCommon { client: self.client.clone() }
+    }
+
+    pub fn market(&self) -> AsyncMarket {
+        AsyncMarket { client: self.client.clone() }
+    }
+
+    pub fn spot(&self) -> AsyncSpot {
+        AsyncSpot { client: self.client.clone() }
+    }
+
+    pub fn wallet(&self) -> AsyncWallet {
+        AsyncWallet { client: self.client.clone() }
+    }
+
+    pub fn account(&self) -> AsyncAccount {
+        AsyncAccount { client: self.client.clone() }
+    }
+
+    /// WebSocket URL and subscribeKey management
+    pub fn ws(&self) -> AsyncWebSocket {
+        AsyncWebSocket {
+            client: self.client.clone(),
+            endpoint: self.config.ws_endpoint.clone(),
+        }
+    }
+
+    /// Check the client is fit to trade before relying on it
+    ///
+    /// See [`LBankClient::self_check`].
+    pub async fn self_check(&self, required: &[Permission]) -> Result<SelfCheckReport> {
+        if self.client.api_key.is_empty() {
+            return Err(ErrorKind::SelfCheckFailed("no API credentials".into()).into());
+        }
+        let sent = get_timestamp();
+        let server_time = self.client.execute(crate::common::requests::time().parse::<u64>()).await?;
+        let received = get_timestamp();
+        let skew = match &self.client.time_sync {
+            Some(time_sync) => time_sync.update(server_time, sent, received),
+            None => server_time as i64 - ((sent + received) / 2) as i64,
+        };
+        let permissions = self.account().api_restrictions().await?;
+        SelfCheckReport::new(permissions, skew, self.client.time_sync.is_some(), required, get_timestamp() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::DEFAULT_RESYNC_INTERVAL;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_views_share_client_state() {
+        let lbank = LBankClient::builder()
+            .credentials("key", "secret")
+            .time_sync(DEFAULT_RESYNC_INTERVAL)
+            .build()
+            .unwrap();
+
+        let spot = lbank.spot();
+        let wallet = lbank.wallet();
+        assert_eq!(spot.client.api_key, "key");
+        assert!(Arc::ptr_eq(&spot.client.transport, &wallet.client.transport));
+        assert!(Arc::ptr_eq(
+            spot.client.rate_limiter.as_ref().unwrap(),
+            wallet.client.rate_limiter.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(spot.client.time_sync.as_ref().unwrap(), lbank.client().time_sync.as_ref().unwrap()));
+        assert_eq!(lbank.ws().url(), crate::config::SPOT_WS_MAINNET);
+
+        let unlimited = LBankClient::builder().no_rate_limit().build_async().unwrap();
+        assert!(unlimited.market().client.rate_limiter.is_none());
+    }
+
+    #[test]
+    fn test_invalid_options_fail_build() {
+        assert!(LBankClient::builder().proxy("::not a url::").build().is_err());
+
+        let lbank = LBankClient::builder()
+            .fallback_hosts(crate::config::SPOT_FALLBACK_HOSTS)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(lbank.client().hosts.hosts().len(), 3);
+        assert_eq!(lbank.config().timeout, Some(Duration::from_secs(5)));
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::SelfCheckWarning;
+    use crate::mock::{MockServer, INVALID_SIGNATURE, TEST_KEY, TEST_SECRET};
+    use crate::model::ApiPermissions;
+
+    #[test]
+    fn test_self_check() {
+        let server = MockServer::start_for_tests();
+        let build = |secret: &str, time_sync: bool| {
+            let builder = LBankClient::builder().credentials(TEST_KEY, secret).config(server.config());
+            let builder = if time_sync { builder.time_sync(Duration::from_secs(60)) } else { builder };
+            builder.build().unwrap()
+        };
+        let created = get_timestamp() as i64 - 1000;
+        server.set_api_permissions(ApiPermissions {
+            ip_restrict: false,
+            create_time: created,
+            enable_withdrawals: false,
+            enable_internal_transfer: false,
+            enable_reading: true,
+            enable_spot_trading: true,
+        });
+
+        let report = build(TEST_SECRET, false).self_check(&[Permission::Reading, Permission::Trading]).unwrap();
+        assert!(report.clock_skew_ms.abs() < 1000);
+        assert!(matches!(report.warnings[..], [SelfCheckWarning::KeyExpires { soon: false, .. }]));
+
+        let err = build(TEST_SECRET, false).self_check(&[Permission::Withdrawals]).unwrap_err();
+        assert!(err.to_string().contains("withdrawals"));
+        let err = build("fedcba9876543210fedcba9876543210", false).self_check(&[]).unwrap_err();
+        assert_eq!(err.lbank_code(), Some(INVALID_SIGNATURE as i16));
+
+        server.set_clock_offset(60_000);
+        assert!(build(TEST_SECRET, false).self_check(&[]).is_err());
+        let report = build(TEST_SECRET, true).self_check(&[]).unwrap();
+        assert!(matches!(report.warnings[0], SelfCheckWarning::ClockSkewCorrected { skew_ms } if skew_ms > 59_000));
+    }
+}
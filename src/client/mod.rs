@@ -1,5 +1,19 @@
 mod client;
 mod async_client;
+mod cassette;
+mod transport;
+mod rate_limit;
+mod time_sync;
+mod lbank_client;
+mod host_pool;
+mod self_check;
 
 pub use client::Client;
 pub use async_client::AsyncClient;
+pub use cassette::{Cassette, CassetteMode, Interaction, REDACTED, REDACTED_PARAMS};
+pub use transport::{AsyncHttpTransport, HttpRequest, HttpTransport, Method};
+pub use rate_limit::{RateLimiter, DEFAULT_REQUESTS, DEFAULT_WINDOW};
+pub use time_sync::{TimeSync, DEFAULT_RESYNC_INTERVAL};
+pub use lbank_client::{AsyncLBankClient, LBankClient, LBankClientBuilder};
+pub use host_pool::{HostPool, HostStatus};
+pub use self_check::{SelfCheckReport, SelfCheckWarning, KEY_EXPIRY_WARNING, MAX_CLOCK_SKEW};
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Requests LBank allows per [`DEFAULT_WINDOW`] on non-order endpoints
+pub const DEFAULT_REQUESTS: usize = 200;
+
+/// Window the default limit applies to
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Sliding-window limiter shared by every view of a client
+///
+/// Allows at most `requests` requests to start within any `window`; callers
+/// over the limit are delayed until a slot frees up.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests: usize,
+    window: Duration,
+    /// Start times of the last `requests` requests, oldest first
+    slots: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests: usize, window: Duration) -> Self {
+        RateLimiter {
+            requests: requests.max(1),
+            window,
+            slots: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Reserve the next free slot and return how long to wait for it
+    pub fn reserve(&self) -> Duration {
+        let mut slots = self.slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let at = if slots.len() < self.requests {
+            now
+        } else {
+            slots.pop_front().map_or(now, |oldest| (oldest + self.window).max(now))
+        };
+        slots.push_back(at);
+        at - now
+    }
+
+    /// Block until the next request may be sent
+    pub fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Wait until the next request may be sent
+    pub async fn acquire_async(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::new(DEFAULT_REQUESTS, DEFAULT_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_spaces_requests_over_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        assert!(limiter.reserve().is_zero());
+        assert!(limiter.reserve().is_zero());
+
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+        // The fourth request waits for the second slot, not the third
+        assert!(limiter.reserve() <= Duration::from_secs(60));
+    }
+}
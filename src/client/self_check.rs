@@ -0,0 +1,115 @@
+use crate::errors::{ErrorKind, Result};
+use crate::model::{ApiPermissions, Permission};
+use std::time::Duration;
+
+/// Largest clock difference tolerated when signed requests aren't time-synced
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(5);
+
+/// Warn about unbound keys this long before they expire
+pub const KEY_EXPIRY_WARNING: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Something worth knowing that doesn't stop the client from working
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfCheckWarning {
+    /// The key isn't bound to an IP address, so it expires 30 days after creation
+    KeyExpires { expires_at: i64, soon: bool },
+    /// The local clock is off by `skew_ms`, which time sync corrects for
+    ClockSkewCorrected { skew_ms: i64 },
+}
+
+/// Outcome of a passed `self_check`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfCheckReport {
+    pub permissions: ApiPermissions,
+    /// Server time minus local time, in milliseconds
+    pub clock_skew_ms: i64,
+    pub warnings: Vec<SelfCheckWarning>,
+}
+
+impl SelfCheckReport {
+    /// Judge the results of the self-check requests
+    ///
+    /// Fails when the clock is off by more than [`MAX_CLOCK_SKEW`] without
+    /// time sync to correct it, when the key lacks any of `required`, or
+    /// when it has already expired. `now` is in milliseconds.
+    pub fn new(
+        permissions: ApiPermissions,
+        clock_skew_ms: i64,
+        time_synced: bool,
+        required: &[Permission],
+        now: i64,
+    ) -> Result<Self> {
+        let mut warnings = Vec::new();
+        if clock_skew_ms.unsigned_abs() > MAX_CLOCK_SKEW.as_millis() as u64 {
+            if !time_synced {
+                return Err(failed(format!(
+                    "local clock is {} ms off server time; enable time_sync or fix the clock",
+                    clock_skew_ms
+                )));
+            }
+            warnings.push(SelfCheckWarning::ClockSkewCorrected { skew_ms: clock_skew_ms });
+        }
+
+        let missing = permissions.missing(required);
+        if !missing.is_empty() {
+            let missing: Vec<String> = missing.iter().map(ToString::to_string).collect();
+            return Err(failed(format!("API key lacks {}", missing.join(", "))));
+        }
+
+        if let Some(expires_at) = permissions.expires_at() {
+            if expires_at <= now {
+                return Err(failed(format!("API key expired at {}; bind it to an IP to keep it", expires_at)));
+            }
+            let soon = expires_at - now <= KEY_EXPIRY_WARNING.as_millis() as i64;
+            warnings.push(SelfCheckWarning::KeyExpires { expires_at, soon });
+        }
+
+        Ok(SelfCheckReport {
+            permissions,
+            clock_skew_ms,
+            warnings,
+        })
+    }
+}
+
+fn failed(reason: String) -> crate::errors::Error {
+    ErrorKind::SelfCheckFailed(reason).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UNBOUND_KEY_LIFETIME_MS;
+
+    fn permissions(ip_restrict: bool, create_time: i64) -> ApiPermissions {
+        ApiPermissions {
+            ip_restrict,
+            create_time,
+            enable_withdrawals: false,
+            enable_internal_transfer: false,
+            enable_reading: true,
+            enable_spot_trading: true,
+        }
+    }
+
+    #[test]
+    fn test_self_check_verdicts() {
+        let now = 100 * UNBOUND_KEY_LIFETIME_MS;
+        let bound = SelfCheckReport::new(permissions(true, 1), 10, false, &[Permission::Trading], now).unwrap();
+        assert!(bound.warnings.is_empty());
+
+        let err = SelfCheckReport::new(permissions(true, 1), 6_000, false, &[], now).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::SelfCheckFailed(_)));
+        let synced = SelfCheckReport::new(permissions(true, 1), 6_000, true, &[], now).unwrap();
+        assert_eq!(synced.warnings, vec![SelfCheckWarning::ClockSkewCorrected { skew_ms: 6_000 }]);
+
+        let err = SelfCheckReport::new(permissions(true, 1), 0, false, &[Permission::Withdrawals, Permission::Reading], now);
+        assert_eq!(err.unwrap_err().to_string(), "Self-check failed: API key lacks withdrawals");
+
+        let created = now - UNBOUND_KEY_LIFETIME_MS + 1000;
+        let unbound = SelfCheckReport::new(permissions(false, created), 0, false, &[], now).unwrap();
+        let expires_at = created + UNBOUND_KEY_LIFETIME_MS;
+        assert_eq!(unbound.warnings, vec![SelfCheckWarning::KeyExpires { expires_at, soon: true }]);
+        assert!(SelfCheckReport::new(permissions(false, created - 1000), 0, false, &[], now).is_err());
+    }
+}
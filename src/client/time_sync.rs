@@ -0,0 +1,80 @@
+use crate::util::get_timestamp;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often the offset is refreshed by default
+pub const DEFAULT_RESYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Offset between the local clock and LBank's, applied to signed `timestamp`s
+///
+/// LBank rejects signed requests whose timestamp drifts too far from server
+/// time. Clients holding a `TimeSync` fetch `timestamp.do`
+/// before the first signed request and again once the offset is older than
+/// the resync interval.
+#[derive(Debug)]
+pub struct TimeSync {
+    offset_ms: AtomicI64,
+    synced_at: Mutex<Option<Instant>>,
+    resync_interval: Duration,
+}
+
+impl TimeSync {
+    pub fn new(resync_interval: Duration) -> Self {
+        TimeSync {
+            offset_ms: AtomicI64::new(0),
+            synced_at: Mutex::new(None),
+            resync_interval,
+        }
+    }
+
+    /// Server time minus local time, in milliseconds
+    pub fn offset(&self) -> i64 {
+        self.offset_ms.load(Ordering::SeqCst)
+    }
+
+    /// Current server time in milliseconds, as estimated from the offset
+    pub fn now(&self) -> u64 {
+        get_timestamp().saturating_add_signed(self.offset())
+    }
+
+    /// Whether the offset was never measured or is older than the resync interval
+    pub fn is_stale(&self) -> bool {
+        self.synced_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_none_or(|at| at.elapsed() >= self.resync_interval)
+    }
+
+    /// Record a server time read between local times `sent` and `received` (ms)
+    ///
+    /// The server is assumed to have answered halfway through the round trip.
+    pub fn update(&self, server_time: u64, sent: u64, received: u64) -> i64 {
+        let local = (sent + received) / 2;
+        let offset = server_time as i64 - local as i64;
+        self.offset_ms.store(offset, Ordering::SeqCst);
+        *self.synced_at.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Instant::now());
+        offset
+    }
+}
+
+impl Default for TimeSync {
+    fn default() -> Self {
+        TimeSync::new(DEFAULT_RESYNC_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_uses_round_trip_midpoint() {
+        let sync = TimeSync::default();
+        assert!(sync.is_stale());
+
+        assert_eq!(sync.update(10_500, 9_000, 10_000), 1_000);
+        assert!(!sync.is_stale());
+        assert!(sync.now() >= get_timestamp() + 999);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::config::{Config, TlsBackend};
+use crate::errors::Result;
+use futures::future::BoxFuture;
+
+/// HTTP method of an LBank REST call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+/// A fully built request: parameters are already in the URL (GET) or the
+/// form-encoded `body` (POST / DELETE)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub body: Option<String>,
+}
+
+/// Blocking HTTP stack used by [`Client`](super::Client)
+///
+/// Implement it to inject middleware, instrumentation or a test double.
+/// Returns the raw response body; error codes inside it are handled by
+/// callers. 5xx responses should be errors so the client can fail over GETs.
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> Result<String>;
+}
+
+/// Async HTTP stack used by [`AsyncClient`](super::AsyncClient)
+pub trait AsyncHttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<String>>;
+}
+
+impl HttpTransport for reqwest::blocking::Client {
+    fn send(&self, request: HttpRequest) -> Result<String> {
+        let mut builder = match request.method {
+            Method::Get => self.get(&request.url),
+            Method::Post => self.post(&request.url),
+            Method::Delete => self.delete(&request.url),
+        };
+        if let Some(body) = request.body {
+            builder = builder
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .body(body);
+        }
+        let mut response = builder.send()?;
+        if response.status().is_server_error() {
+            response = response.error_for_status()?;
+        }
+        Ok(response.text()?)
+    }
+}
+
+impl AsyncHttpTransport for reqwest::Client {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<String>> {
+        Box::pin(async move {
+            let mut builder = match request.method {
+                Method::Get => self.get(&request.url),
+                Method::Post => self.post(&request.url),
+                Method::Delete => self.delete(&request.url),
+            };
+            if let Some(body) = request.body {
+                builder = builder
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(body);
+            }
+            let mut response = builder.send().await?;
+            if response.status().is_server_error() {
+                response = response.error_for_status()?;
+            }
+            Ok(response.text().await?)
+        })
+    }
+}
+
+/// Apply `Config`'s connection options to a blocking or async reqwest builder
+macro_rules! configure {
+    ($builder:expr, $config:expr) => {{
+        let config: &Config = $config;
+        let mut builder = $builder.pool_idle_timeout(None);
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent.as_str());
+        }
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?;
+            builder = builder.proxy(proxy);
+        }
+        builder = match config.tls_backend {
+            TlsBackend::Default => builder,
+            TlsBackend::NativeTls => builder.use_native_tls(),
+            #[cfg(feature = "rustls-tls")]
+            TlsBackend::Rustls => builder.use_rustls_tls(),
+            #[cfg(not(feature = "rustls-tls"))]
+            TlsBackend::Rustls => return Err("TlsBackend::Rustls requires the `rustls-tls` feature".into()),
+        };
+        Ok(builder.build()?)
+    }};
+}
+
+/// Blocking reqwest client configured from `config`
+pub(crate) fn blocking_transport(config: &Config) -> Result<reqwest::blocking::Client> {
+    configure!(reqwest::blocking::Client::builder(), config)
+}
+
+/// Async reqwest client configured from `config`
+pub(crate) fn async_transport(config: &Config) -> Result<reqwest::Client> {
+    configure!(reqwest::Client::builder(), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_options_are_errors() {
+        let config = Config {
+            proxy: Some("not a proxy".into()),
+            ..Config::default()
+        };
+        assert!(blocking_transport(&config).is_err());
+        assert!(async_transport(&config).is_err());
+
+        let config = Config {
+            proxy: Some("http://127.0.0.1:8080".into()),
+            user_agent: Some("lbank-rs-test".into()),
+            ..Config::default()
+        };
+        assert!(blocking_transport(&config).is_ok());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::client::Client;
-use crate::api::{API, General};
+use crate::common::requests;
 use crate::errors::Result;
 
 /// Common API client for synchronous operations
@@ -11,26 +11,26 @@ pub struct Common {
 impl Common {
     /// Get list of available trading pairs
     pub fn currency_pairs(&self) -> Result<String> {
-        self.client.get(API::General(General::CurrencyPairs), None)
+        self.client.execute(requests::currency_pairs())
     }
 
     /// Get basic information (price/quantity accuracy, min transaction quantity) for all trading pairs
     pub fn accuracy(&self) -> Result<String> {
-        self.client.get(API::General(General::Accuracy), None)
+        self.client.execute(requests::accuracy())
     }
 
     /// Get withdrawal configurations for assets (deprecated)
     pub fn withdraw_configs(&self) -> Result<String> {
-        self.client.get(API::General(General::WithdrawConfigs), None)
+        self.client.execute(requests::withdraw_configs())
     }
 
     /// Get coin information including deposit/withdrawal configuration for multiple chains
     pub fn asset_configs(&self) -> Result<String> {
-        self.client.get(API::General(General::AssetConfigs), None)
+        self.client.execute(requests::asset_configs())
     }
 
     /// Get current server timestamp (used for signature generation)
     pub fn time(&self) -> Result<String> {
-        self.client.get(API::General(General::Timestamp), None)
+        self.client.execute(requests::time())
     }
 }
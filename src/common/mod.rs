@@ -1,5 +1,6 @@
 mod common;
 mod async_common;
+pub mod requests;
 
 pub use common::Common;
 pub use async_common::AsyncCommon;
@@ -0,0 +1,24 @@
+//! Requests behind [`Common`](super::Common) and [`AsyncCommon`](super::AsyncCommon)
+
+use crate::api::{API, General};
+use crate::request::Request;
+
+pub fn currency_pairs() -> Request {
+    Request::get(API::General(General::CurrencyPairs))
+}
+
+pub fn accuracy() -> Request {
+    Request::get(API::General(General::Accuracy))
+}
+
+pub fn withdraw_configs() -> Request {
+    Request::get(API::General(General::WithdrawConfigs))
+}
+
+pub fn asset_configs() -> Request {
+    Request::get(API::General(General::AssetConfigs))
+}
+
+pub fn time() -> Request {
+    Request::get(API::General(General::Timestamp))
+}
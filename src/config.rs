@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 
 // API endpoints
 pub const SPOT_MAINNET: &str = "https://www.lbkex.net";
@@ -5,6 +7,20 @@ pub const SPOT_WS_MAINNET: &str = "wss://www.lbkex.net/ws/V2/";
 pub const FUTURES_MAINNET: &str = "https://fapi.lbkex.net";
 pub const FUTURES_WS_MAINNET: &str = "wss://fapi.lbkex.net/ws";
 
+/// Alternate spot REST hosts listed in LBank's API docs
+pub const SPOT_FALLBACK_HOSTS: [&str; 2] = ["https://api.lbkex.com", "https://api.lbank.info"];
+
+/// TLS implementation used for REST requests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TlsBackend {
+    /// reqwest's default (native TLS)
+    #[default]
+    Default,
+    NativeTls,
+    /// rustls; requires the `rustls-tls` feature
+    Rustls,
+}
+
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -16,6 +32,22 @@ pub struct Config {
 
     pub recv_window: u64,
 
+    /// Prefix for automatically generated client order IDs (`custom_id`)
+    pub custom_id_prefix: String,
+
+    /// Time allowed to establish a connection
+    pub connect_timeout: Option<Duration>,
+    /// Time allowed for a whole request, from connecting to reading the body
+    pub timeout: Option<Duration>,
+    /// Proxy for all REST requests, e.g. "http://127.0.0.1:8080" or
+    /// "socks5://127.0.0.1:1080" (SOCKS requires the `socks` feature)
+    pub proxy: Option<String>,
+    /// `User-Agent` header sent with every request
+    pub user_agent: Option<String>,
+    pub tls_backend: TlsBackend,
+    /// REST hosts tried in order when `rest_api_endpoint` can't be reached
+    /// (see [`SPOT_FALLBACK_HOSTS`])
+    pub fallback_hosts: Vec<String>,
 }
 
 
@@ -29,6 +61,15 @@ impl Default for Config {
             futures_ws_endpoint: FUTURES_WS_MAINNET.into(),
 
             recv_window: 5000,
+
+            custom_id_prefix: String::new(),
+
+            connect_timeout: Some(Duration::from_secs(10)),
+            timeout: Some(Duration::from_secs(30)),
+            proxy: None,
+            user_agent: None,
+            tls_backend: TlsBackend::Default,
+            fallback_hosts: Vec::new(),
         }
     }
 }
@@ -46,6 +87,10 @@ mod tests {
         assert_eq!(config.futures_rest_api_endpoint, FUTURES_MAINNET);
         assert_eq!(config.futures_ws_endpoint, FUTURES_WS_MAINNET);
         assert_eq!(config.recv_window, 5000);
+        assert!(config.custom_id_prefix.is_empty());
+        assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+        assert!(config.proxy.is_none());
+        assert!(config.fallback_hosts.is_empty());
     }
 
     #[test]
@@ -1,6 +1,19 @@
 use serde::Deserialize;
 use error_chain::error_chain;
 
+/// LBank error code: request too frequent
+pub const REQUEST_TOO_FREQUENT: i16 = 10004;
+/// LBank error code: order has been filled
+pub const ORDER_FILLED: i16 = 10025;
+/// LBank error code: order has been cancelled
+pub const ORDER_CANCELLED: i16 = 10026;
+/// LBank error code: the order number does not exist
+pub const ORDER_NOT_FOUND: i16 = 10032;
+/// LBank error code: customID duplicated
+pub const CUSTOM_ID_DUPLICATED: i16 = 10036;
+/// LBank error code: order has been cancelled or completed
+pub const ORDER_CLOSED: i16 = 10037;
+
 #[derive(Debug, Deserialize)]
 pub struct LBankContentError {
     pub code: i16,
@@ -18,6 +31,21 @@ error_chain! {
             description("invalid Vec for Kline")
             display("{} at {} is missing", name, index)
         }
+
+        WithdrawalRejected(reason: String) {
+            description("withdrawal rejected by guard")
+            display("Withdrawal rejected: {}", reason)
+        }
+
+        ConfirmationRequired(token: String) {
+            description("withdrawal needs confirmation")
+            display("Withdrawal needs confirmation; resend with token {}", token)
+        }
+
+        SelfCheckFailed(reason: String) {
+            description("startup self-check failed")
+            display("Self-check failed: {}", reason)
+        }
     }
 
     foreign_links {
@@ -31,3 +59,41 @@ error_chain! {
         TimestampError(std::time::SystemTimeError);
     }
 }
+
+impl Error {
+    /// LBank error code carried by this error, if it came from an API response
+    pub fn lbank_code(&self) -> Option<i16> {
+        match self.kind() {
+            ErrorKind::LBankError(response) => Some(response.code),
+            _ => None,
+        }
+    }
+
+    /// Whether the request may have reached the exchange without us seeing the answer
+    ///
+    /// Timeouts, dropped connections and unparseable gateway pages all leave the
+    /// outcome of a write request unknown; LBank error envelopes do not.
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::ReqError(_) | ErrorKind::IoError(_) | ErrorKind::Json(_)
+        )
+    }
+
+    /// Whether the request failed before a connection to the host was established
+    pub fn is_connect(&self) -> bool {
+        match self.kind() {
+            ErrorKind::ReqError(e) => e.is_connect(),
+            ErrorKind::IoError(e) => e.kind() == std::io::ErrorKind::ConnectionRefused,
+            _ => false,
+        }
+    }
+
+    /// Whether the host couldn't be reached or answered with a 5xx status
+    pub fn is_host_unavailable(&self) -> bool {
+        match self.kind() {
+            ErrorKind::ReqError(e) => e.status().is_some_and(|status| status.is_server_error()) || self.is_connect(),
+            _ => self.is_connect(),
+        }
+    }
+}
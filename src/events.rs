@@ -0,0 +1,123 @@
+use std::sync::{mpsc, Arc, Mutex};
+use tokio::sync::mpsc as async_mpsc;
+
+type Callback<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+struct Subscribers<E> {
+    callbacks: Vec<Callback<E>>,
+    senders: Vec<mpsc::Sender<E>>,
+    async_senders: Vec<async_mpsc::UnboundedSender<E>>,
+}
+
+/// Fan-out of events to callbacks and channels
+///
+/// Shared by the trackers that notify subscribers of state changes. Events
+/// are delivered by `emit`, which callers invoke after releasing their own
+/// state lock, so a callback may call back into the tracker that emitted the
+/// event. Events emitted from several threads at once may interleave. Clones
+/// share the same subscribers; dropped receivers are unsubscribed on the next
+/// event.
+pub struct EventHub<E> {
+    subscribers: Arc<Mutex<Subscribers<E>>>,
+}
+
+impl<E> Clone for EventHub<E> {
+    fn clone(&self) -> Self {
+        EventHub {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<E> Default for EventHub<E> {
+    fn default() -> Self {
+        EventHub {
+            subscribers: Arc::new(Mutex::new(Subscribers {
+                callbacks: Vec::new(),
+                senders: Vec::new(),
+                async_senders: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl<E: Clone> EventHub<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback invoked for every event
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        self.lock().callbacks.push(Arc::new(callback));
+    }
+
+    /// Subscribe to events through a blocking channel
+    pub fn subscribe(&self) -> mpsc::Receiver<E> {
+        let (tx, rx) = mpsc::channel();
+        self.lock().senders.push(tx);
+        rx
+    }
+
+    /// Subscribe to events through an async channel
+    pub fn subscribe_async(&self) -> async_mpsc::UnboundedReceiver<E> {
+        let (tx, rx) = async_mpsc::unbounded_channel();
+        self.lock().async_senders.push(tx);
+        rx
+    }
+
+    /// Deliver `events` in order to every subscriber
+    ///
+    /// Callbacks run on the calling thread without any lock held.
+    pub fn emit<I>(&self, events: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for event in events {
+            let callbacks = {
+                let mut subscribers = self.lock();
+                subscribers.senders.retain(|tx| tx.send(event.clone()).is_ok());
+                subscribers.async_senders.retain(|tx| tx.send(event.clone()).is_ok());
+                subscribers.callbacks.clone()
+            };
+            for callback in &callbacks {
+                callback(&event);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Subscribers<E>> {
+        self.subscribers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_callbacks_can_reenter() {
+        let hub: EventHub<u32> = EventHub::new();
+        let seen = Arc::new(AtomicUsize::new(0));
+        let (inner_hub, inner_seen) = (hub.clone(), seen.clone());
+        hub.on_event(move |&n| {
+            inner_seen.fetch_add(1, Ordering::SeqCst);
+            // Subscribing and emitting from a callback must not deadlock
+            if n == 1 {
+                drop(inner_hub.subscribe());
+                inner_hub.emit([2]);
+            }
+        });
+        let events = hub.subscribe();
+        let dropped = hub.subscribe();
+        drop(dropped);
+
+        hub.emit([1]);
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+        assert_eq!(events.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(hub.lock().senders.len(), 1);
+    }
+}
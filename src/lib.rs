@@ -7,5 +7,11 @@ pub mod config;
 pub mod common;
 pub mod errors;
 pub mod account;
+pub mod websocket;
 pub mod model;
-pub mod util; 
\ No newline at end of file
+pub mod util;
+pub mod pagination;
+pub mod events;
+pub mod request;
+#[cfg(feature = "mock")]
+pub mod mock; 
\ No newline at end of file
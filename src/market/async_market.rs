@@ -1,6 +1,10 @@
 use crate::client::AsyncClient;
-use crate::api::{API, Market as MarketAPI};
+use crate::market::requests;
 use crate::errors::Result;
+use crate::market::backfill::{backfill_series, backfill_stream, Backfill, KlineSeries, MAX_KLINE_BARS};
+use crate::model::Candle;
+use crate::util::parse_response;
+use futures::stream::BoxStream;
 
 /// Market API client for asynchronous operations
 #[derive(Clone)]
@@ -11,7 +15,7 @@ pub struct AsyncMarket {
 impl AsyncMarket {
     /// Test server connectivity / health check
     pub async fn system_ping(&self) -> Result<String> {
-        self.client.post(API::Market(MarketAPI::SystemPing), None).await
+        self.client.execute(requests::system_ping()).await
     }
 
     /// Get order book depth information (asks/bids)
@@ -20,8 +24,7 @@ impl AsyncMarket {
     /// * `symbol` - Trading pair (e.g., "eth_btc")
     /// * `size` - Number of depth entries to return (1-200)
     pub async fn depth(&self, symbol: &str, size: u32) -> Result<String> {
-        let params = format!("symbol={}&size={}", symbol, size);
-        self.client.get(API::Market(MarketAPI::Depth), Some(params)).await
+        self.client.execute(requests::depth(symbol, size)).await
     }
 
     /// Get latest price for trading pairs
@@ -29,8 +32,7 @@ impl AsyncMarket {
     /// # Parameters
     /// * `symbol` - Optional trading pair. If None, returns all pairs
     pub async fn price(&self, symbol: Option<&str>) -> Result<String> {
-        let params = symbol.map(|s| format!("symbol={}", s));
-        self.client.get(API::Market(MarketAPI::Price), params).await
+        self.client.execute(requests::price(symbol)).await
     }
 
     /// Get current best bid/ask prices and quantities
@@ -38,8 +40,7 @@ impl AsyncMarket {
     /// # Parameters
     /// * `symbol` - Trading pair (e.g., "lbk_usdt")
     pub async fn book_ticker(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::BookTicker), Some(params)).await
+        self.client.execute(requests::book_ticker(symbol)).await
     }
 
     /// Get 24-hour ticker data (excludes ETF pairs)
@@ -47,8 +48,7 @@ impl AsyncMarket {
     /// # Parameters
     /// * `symbol` - Trading pair or "all" for all pairs
     pub async fn ticker_24hr(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::Ticker24hr), Some(params)).await
+        self.client.execute(requests::ticker_24hr(symbol)).await
     }
 
     /// Get 24-hour ticker data for Leveraged Tokens (ETF) trading pairs
@@ -56,8 +56,7 @@ impl AsyncMarket {
     /// # Parameters
     /// * `symbol` - ETF trading pair or "all" for all ETF pairs
     pub async fn etf_ticker_24hr(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::EtfTicker24hr), Some(params)).await
+        self.client.execute(requests::etf_ticker_24hr(symbol)).await
     }
 
     /// Get list of recent trades
@@ -67,11 +66,7 @@ impl AsyncMarket {
     /// * `size` - Number of trades to return
     /// * `time` - Optional timestamp filter (returns data after this timestamp)
     pub async fn trades(&self, symbol: &str, size: u32, time: Option<u64>) -> Result<String> {
-        let mut params = format!("symbol={}&size={}", symbol, size);
-        if let Some(t) = time {
-            params.push_str(&format!("&time={}", t));
-        }
-        self.client.get(API::Market(MarketAPI::Trades), Some(params)).await
+        self.client.execute(requests::trades(symbol, size, time)).await
     }
 
     /// Get K-line/candlestick data
@@ -85,10 +80,51 @@ impl AsyncMarket {
     ///   - "day1", "week1", "month1"
     /// * `time` - Timestamp in seconds
     pub async fn kline(&self, symbol: &str, size: u32, kline_type: &str, time: u64) -> Result<String> {
-        let params = format!(
-            "symbol={}&size={}&type={}&time={}",
-            symbol, size, kline_type, time
-        );
-        self.client.get(API::Market(MarketAPI::Kline), Some(params)).await
+        self.client.execute(requests::kline(symbol, size, kline_type, time)).await
+    }
+
+    /// Get every K-line bar opening between `start_time` and `end_time`
+    ///
+    /// Requests 2000 bars at a time and returns them in order, without
+    /// overlapping bars, along with any gaps in the exchange's data.
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair
+    /// * `kline_type` - K-line interval type (see `kline`)
+    /// * `start_time` - Start timestamp in seconds
+    /// * `end_time` - End timestamp in seconds (exclusive)
+    pub async fn kline_history(
+        &self,
+        symbol: &str,
+        kline_type: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<KlineSeries> {
+        backfill_series(Backfill::new(kline_type, start_time, end_time), |time| async move {
+            let text = self.kline(symbol, MAX_KLINE_BARS, kline_type, time).await?;
+            parse_response::<Vec<Candle>>(&text)
+        })
+        .await
+    }
+
+    /// Stream every K-line bar opening between `start_time` and `end_time`
+    ///
+    /// Same as `kline_history`, fetching each chunk as the stream reaches it.
+    pub fn kline_history_stream(
+        &self,
+        symbol: &str,
+        kline_type: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> BoxStream<'_, Result<Candle>> {
+        let backfill = Backfill::new(kline_type, start_time, end_time);
+        let (symbol, kline_type) = (symbol.to_string(), kline_type.to_string());
+        backfill_stream(backfill, move |time| {
+            let (symbol, kline_type) = (symbol.clone(), kline_type.clone());
+            async move {
+                let text = self.kline(&symbol, MAX_KLINE_BARS, &kline_type, time).await?;
+                parse_response::<Vec<Candle>>(&text)
+            }
+        })
     }
 }
\ No newline at end of file
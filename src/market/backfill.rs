@@ -0,0 +1,257 @@
+use crate::errors::Result;
+use crate::model::Candle;
+use crate::pagination::Throttle;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::collections::VecDeque;
+use std::future::Future;
+
+/// Most bars `kline` returns per request
+pub const MAX_KLINE_BARS: u32 = 2000;
+
+/// Bar length in seconds for a `kline` type
+///
+/// Returns `None` for "month1", whose bars vary in length, and for unknown types.
+pub fn kline_interval(kline_type: &str) -> Option<u64> {
+    let minutes = match kline_type {
+        "minute1" => 1,
+        "minute5" => 5,
+        "minute15" => 15,
+        "minute30" => 30,
+        "hour1" => 60,
+        "hour4" => 4 * 60,
+        "hour8" => 8 * 60,
+        "hour12" => 12 * 60,
+        "day1" => 24 * 60,
+        "week1" => 7 * 24 * 60,
+        _ => return None,
+    };
+    Some(minutes * 60)
+}
+
+/// Bars missing between two consecutive candles of a backfill
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KlineGap {
+    /// Open time of the last bar before the gap (seconds)
+    pub after: u64,
+    /// Open time of the first bar after the gap (seconds)
+    pub before: u64,
+    /// Number of bars missing
+    pub missing: u64,
+}
+
+/// Ordered, de-duplicated candles with the gaps found between them
+#[derive(Debug, Clone, Default)]
+pub struct KlineSeries {
+    pub candles: Vec<Candle>,
+    pub gaps: Vec<KlineGap>,
+}
+
+/// Chunking state shared by the sync and async backfills
+pub(crate) struct Backfill {
+    start: u64,
+    end: u64,
+    interval: Option<u64>,
+    next: Option<u64>,
+    last: Option<u64>,
+    gaps: Vec<KlineGap>,
+    buffer: VecDeque<Candle>,
+    throttle: Throttle,
+}
+
+impl Backfill {
+    /// Backfill bars opening in `start..end` (seconds)
+    pub(crate) fn new(kline_type: &str, start: u64, end: u64) -> Self {
+        Backfill {
+            start,
+            end,
+            interval: kline_interval(kline_type),
+            next: (start < end).then_some(start),
+            last: None,
+            gaps: Vec::new(),
+            buffer: VecDeque::new(),
+            throttle: Throttle::default(),
+        }
+    }
+
+    /// Buffer the new bars of a chunk and move past it
+    ///
+    /// Bars outside the range or at/before the last bar already yielded
+    /// (chunk overlap) are dropped. An empty chunk ends the backfill.
+    fn accept(&mut self, mut chunk: Vec<Candle>) {
+        chunk.sort_by_key(|c| c.time);
+        chunk.dedup_by_key(|c| c.time);
+
+        let (start, end, last) = (self.start, self.end, self.last);
+        chunk.retain(|c| c.time >= start && c.time < end && last.is_none_or(|last| c.time > last));
+
+        for candle in chunk {
+            if let (Some(previous), Some(interval)) = (self.last, self.interval) {
+                if candle.time - previous > interval {
+                    self.gaps.push(KlineGap {
+                        after: previous,
+                        before: candle.time,
+                        missing: (candle.time - previous) / interval - 1,
+                    });
+                }
+            }
+            self.last = Some(candle.time);
+            self.buffer.push_back(candle);
+        }
+
+        self.next = match self.last {
+            Some(last) if self.next.is_some_and(|next| last >= next) => {
+                Some(last + self.interval.unwrap_or(1)).filter(|next| *next < end)
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Iterator over every candle of a `kline` backfill
+///
+/// Requests [`MAX_KLINE_BARS`] bars at a time, each chunk starting after the
+/// last bar received, and yields candles in order without duplicates.
+/// Iteration stops after the first error.
+pub struct KlineBackfill<'a> {
+    backfill: Backfill,
+    fetch: Box<dyn FnMut(u64) -> Result<Vec<Candle>> + 'a>,
+}
+
+impl<'a> KlineBackfill<'a> {
+    pub(crate) fn new(backfill: Backfill, fetch: impl FnMut(u64) -> Result<Vec<Candle>> + 'a) -> Self {
+        KlineBackfill {
+            backfill,
+            fetch: Box::new(fetch),
+        }
+    }
+
+    /// Gaps found between the candles yielded so far
+    pub fn gaps(&self) -> &[KlineGap] {
+        &self.backfill.gaps
+    }
+
+    /// Run the backfill to completion
+    pub fn into_series(mut self) -> Result<KlineSeries> {
+        let candles = self.by_ref().collect::<Result<Vec<_>>>()?;
+        Ok(KlineSeries {
+            candles,
+            gaps: self.backfill.gaps,
+        })
+    }
+}
+
+impl Iterator for KlineBackfill<'_> {
+    type Item = Result<Candle>;
+
+    fn next(&mut self) -> Option<Result<Candle>> {
+        loop {
+            if let Some(candle) = self.backfill.buffer.pop_front() {
+                return Some(Ok(candle));
+            }
+            let time = self.backfill.next?;
+            let fetch = &mut self.fetch;
+            match self.backfill.throttle.call(|| fetch(time)) {
+                Ok(chunk) => self.backfill.accept(chunk),
+                Err(e) => {
+                    self.backfill.next = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Stream every candle of a `kline` backfill
+///
+/// Async counterpart of [`KlineBackfill`].
+pub(crate) fn backfill_stream<'a, F, Fut>(backfill: Backfill, fetch: F) -> BoxStream<'a, Result<Candle>>
+where
+    F: FnMut(u64) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<Vec<Candle>>> + Send + 'a,
+{
+    stream::unfold((backfill, fetch), |(mut backfill, mut fetch)| async move {
+        loop {
+            if let Some(candle) = backfill.buffer.pop_front() {
+                return Some((Ok(candle), (backfill, fetch)));
+            }
+            let time = backfill.next?;
+            match backfill.throttle.call_async(|| fetch(time)).await {
+                Ok(chunk) => backfill.accept(chunk),
+                Err(e) => {
+                    backfill.next = None;
+                    return Some((Err(e), (backfill, fetch)));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Run an async backfill to completion
+pub(crate) async fn backfill_series<F, Fut>(mut backfill: Backfill, mut fetch: F) -> Result<KlineSeries>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<Vec<Candle>>>,
+{
+    let mut candles = Vec::new();
+    while let Some(time) = backfill.next {
+        let chunk = backfill.throttle.call_async(|| fetch(time)).await?;
+        backfill.accept(chunk);
+        candles.extend(backfill.buffer.drain(..));
+    }
+    Ok(KlineSeries {
+        candles,
+        gaps: backfill.gaps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(time: u64) -> Candle {
+        Candle {
+            time,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_chunks_overlap_and_gaps() {
+        // Second chunk overlaps the first and skips the bar at 240
+        let chunks = vec![vec![bar(60), bar(0), bar(120)], vec![bar(120), bar(180), bar(300)], vec![]];
+        let mut requested = Vec::new();
+        let series = KlineBackfill::new(Backfill::new("minute1", 0, 1000), |time| {
+            requested.push(time);
+            Ok(chunks[requested.len() - 1].clone())
+        })
+        .into_series()
+        .unwrap();
+
+        let times: Vec<u64> = series.candles.iter().map(|c| c.time).collect();
+        assert_eq!(times, vec![0, 60, 120, 180, 300]);
+        assert_eq!(requested, vec![0, 180, 360]);
+        assert_eq!(series.gaps, vec![KlineGap { after: 180, before: 300, missing: 1 }]);
+    }
+
+    #[test]
+    fn test_stops_at_end_or_stale_chunk() {
+        let mut backfill = Backfill::new("minute1", 0, 120);
+        backfill.accept(vec![bar(0), bar(60), bar(120), bar(180)]);
+        assert_eq!(backfill.buffer.len(), 2);
+        assert_eq!(backfill.next, None);
+
+        // A server that ignores `time` returns the same bars again
+        let mut backfill = Backfill::new("minute1", 0, 1000);
+        backfill.accept(vec![bar(0), bar(60)]);
+        backfill.accept(vec![bar(0), bar(60)]);
+        assert_eq!(backfill.next, None);
+
+        assert_eq!(kline_interval("hour4"), Some(14_400));
+        assert_eq!(kline_interval("month1"), None);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::client::Client;
-use crate::api::{API, Market as MarketAPI};
+use crate::market::requests;
 use crate::errors::Result;
+use crate::market::backfill::{Backfill, KlineBackfill, KlineSeries, MAX_KLINE_BARS};
+use crate::model::Candle;
+use crate::util::parse_response;
 
 /// Market API client for synchronous operations
 #[derive(Clone)]
@@ -11,7 +14,7 @@ pub struct Market {
 impl Market {
     /// Test server connectivity / health check
     pub fn system_ping(&self) -> Result<String> {
-        self.client.post(API::Market(MarketAPI::SystemPing), None)
+        self.client.execute(requests::system_ping())
     }
 
     /// Get order book depth information (asks/bids)
@@ -20,8 +23,7 @@ impl Market {
     /// * `symbol` - Trading pair (e.g., "eth_btc")
     /// * `size` - Number of depth entries to return (1-200)
     pub fn depth(&self, symbol: &str, size: u32) -> Result<String> {
-        let params = format!("symbol={}&size={}", symbol, size);
-        self.client.get(API::Market(MarketAPI::Depth), Some(params))
+        self.client.execute(requests::depth(symbol, size))
     }
 
     /// Get latest price for trading pairs
@@ -29,8 +31,7 @@ impl Market {
     /// # Parameters
     /// * `symbol` - Optional trading pair. If None, returns all pairs
     pub fn price(&self, symbol: Option<&str>) -> Result<String> {
-        let params = symbol.map(|s| format!("symbol={}", s));
-        self.client.get(API::Market(MarketAPI::Price), params)
+        self.client.execute(requests::price(symbol))
     }
 
     /// Get current best bid/ask prices and quantities
@@ -38,8 +39,7 @@ impl Market {
     /// # Parameters
     /// * `symbol` - Trading pair (e.g., "lbk_usdt")
     pub fn book_ticker(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::BookTicker), Some(params))
+        self.client.execute(requests::book_ticker(symbol))
     }
 
     /// Get 24-hour ticker data (excludes ETF pairs)
@@ -47,8 +47,7 @@ impl Market {
     /// # Parameters
     /// * `symbol` - Trading pair or "all" for all pairs
     pub fn ticker_24hr(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::Ticker24hr), Some(params))
+        self.client.execute(requests::ticker_24hr(symbol))
     }
 
     /// Get 24-hour ticker data for Leveraged Tokens (ETF) trading pairs
@@ -56,8 +55,7 @@ impl Market {
     /// # Parameters
     /// * `symbol` - ETF trading pair or "all" for all ETF pairs
     pub fn etf_ticker_24hr(&self, symbol: &str) -> Result<String> {
-        let params = format!("symbol={}", symbol);
-        self.client.get(API::Market(MarketAPI::EtfTicker24hr), Some(params))
+        self.client.execute(requests::etf_ticker_24hr(symbol))
     }
 
     /// Get list of recent trades
@@ -67,11 +65,7 @@ impl Market {
     /// * `size` - Number of trades to return
     /// * `time` - Optional timestamp filter (returns data after this timestamp)
     pub fn trades(&self, symbol: &str, size: u32, time: Option<u64>) -> Result<String> {
-        let mut params = format!("symbol={}&size={}", symbol, size);
-        if let Some(t) = time {
-            params.push_str(&format!("&time={}", t));
-        }
-        self.client.get(API::Market(MarketAPI::Trades), Some(params))
+        self.client.execute(requests::trades(symbol, size, time))
     }
 
     /// Get K-line/candlestick data
@@ -85,10 +79,36 @@ impl Market {
     ///   - "day1", "week1", "month1"
     /// * `time` - Timestamp in seconds
     pub fn kline(&self, symbol: &str, size: u32, kline_type: &str, time: u64) -> Result<String> {
-        let params = format!(
-            "symbol={}&size={}&type={}&time={}",
-            symbol, size, kline_type, time
-        );
-        self.client.get(API::Market(MarketAPI::Kline), Some(params))
+        self.client.execute(requests::kline(symbol, size, kline_type, time))
+    }
+
+    /// Get every K-line bar opening between `start_time` and `end_time`
+    ///
+    /// Requests 2000 bars at a time and returns them in order, without
+    /// overlapping bars, along with any gaps in the exchange's data.
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair
+    /// * `kline_type` - K-line interval type (see `kline`)
+    /// * `start_time` - Start timestamp in seconds
+    /// * `end_time` - End timestamp in seconds (exclusive)
+    pub fn kline_history(&self, symbol: &str, kline_type: &str, start_time: u64, end_time: u64) -> Result<KlineSeries> {
+        self.kline_history_iter(symbol, kline_type, start_time, end_time).into_series()
+    }
+
+    /// Iterate over every K-line bar opening between `start_time` and `end_time`
+    ///
+    /// Same as `kline_history`, fetching each chunk as the iterator reaches it.
+    pub fn kline_history_iter<'a>(
+        &'a self,
+        symbol: &'a str,
+        kline_type: &'a str,
+        start_time: u64,
+        end_time: u64,
+    ) -> KlineBackfill<'a> {
+        KlineBackfill::new(Backfill::new(kline_type, start_time, end_time), move |time| {
+            let text = self.kline(symbol, MAX_KLINE_BARS, kline_type, time)?;
+            parse_response::<Vec<Candle>>(&text)
+        })
     }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 mod market;
 mod async_market;
+mod backfill;
+pub mod requests;
 
 pub use market::Market;
-pub use async_market::AsyncMarket;
\ No newline at end of file
+pub use async_market::AsyncMarket;
+pub use backfill::{kline_interval, KlineBackfill, KlineGap, KlineSeries, MAX_KLINE_BARS};
@@ -0,0 +1,45 @@
+//! Requests behind [`Market`](super::Market) and [`AsyncMarket`](super::AsyncMarket)
+
+use crate::api::{API, Market};
+use crate::request::Request;
+
+pub fn system_ping() -> Request {
+    Request::post(API::Market(Market::SystemPing))
+}
+
+pub fn depth(symbol: &str, size: u32) -> Request {
+    Request::get(API::Market(Market::Depth))
+        .param("symbol", symbol)
+        .param("size", size)
+}
+
+pub fn price(symbol: Option<&str>) -> Request {
+    Request::get(API::Market(Market::Price)).param_opt("symbol", symbol)
+}
+
+pub fn book_ticker(symbol: &str) -> Request {
+    Request::get(API::Market(Market::BookTicker)).param("symbol", symbol)
+}
+
+pub fn ticker_24hr(symbol: &str) -> Request {
+    Request::get(API::Market(Market::Ticker24hr)).param("symbol", symbol)
+}
+
+pub fn etf_ticker_24hr(symbol: &str) -> Request {
+    Request::get(API::Market(Market::EtfTicker24hr)).param("symbol", symbol)
+}
+
+pub fn trades(symbol: &str, size: u32, time: Option<u64>) -> Request {
+    Request::get(API::Market(Market::Trades))
+        .param("symbol", symbol)
+        .param("size", size)
+        .param_opt("time", time)
+}
+
+pub fn kline(symbol: &str, size: u32, kline_type: &str, time: u64) -> Request {
+    Request::get(API::Market(Market::Kline))
+        .param("symbol", symbol)
+        .param("size", size)
+        .param("type", kline_type)
+        .param("time", time)
+}
@@ -0,0 +1,979 @@
+use crate::errors::{CUSTOM_ID_DUPLICATED, ORDER_CANCELLED, ORDER_FILLED, ORDER_NOT_FOUND};
+use crate::model::{ApiPermissions, Candle, DepositRecord, DepositStatus, OrderStatus, WithdrawRecord, WithdrawStatus};
+use crate::util::{get_timestamp, uuid_spot};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Handler result: JSON `data`, or an LBank error code and message
+pub(crate) type Reply = std::result::Result<Value, (i32, String)>;
+
+// LBank error codes answered by the mock
+pub(crate) const MISSING_PARAMETER: i32 = 10001;
+pub(crate) const INVALID_PARAMETER: i32 = 10003;
+pub(crate) const UNKNOWN_API_KEY: i32 = 10005;
+pub(crate) const INVALID_SIGNATURE: i32 = 10007;
+const INVALID_PAIR: i32 = 10008;
+const INVALID_AMOUNT: i32 = 10009;
+const INVALID_ORDER_TYPE: i32 = 10015;
+const INSUFFICIENT_BALANCE: i32 = 10016;
+const INVALID_PAGE_LENGTH: i32 = 10018;
+pub(crate) const INVALID_ECHOSTR: i32 = 10031;
+
+const EPSILON: f64 = 1e-12;
+
+/// Every REST route in `api::API`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Route {
+    CurrencyPairs,
+    Accuracy,
+    WithdrawConfigs,
+    AssetConfigs,
+    Timestamp,
+    SystemPing,
+    Depth,
+    Price,
+    BookTicker,
+    Ticker24hr,
+    EtfTicker24hr,
+    Trades,
+    Kline,
+    SystemStatus,
+    UserInfo,
+    Withdraw,
+    DepositHistory,
+    WithdrawHistory,
+    DepositAddress,
+    AssetDetail,
+    OrderTest,
+    CreateOrder,
+    CancelOrder,
+    CancelOrderBySymbol,
+    OrderInfo,
+    OpenOrders,
+    OrderHistory,
+    AccountInfo,
+    TransactionHistory,
+    TradeFeeRate,
+    ApiRestrictions,
+    GetSubscribeKey,
+    RefreshSubscribeKey,
+    DestroySubscribeKey,
+}
+
+impl Route {
+    /// Whether the route requires `api_key` / `sign`
+    pub(crate) fn is_signed(self) -> bool {
+        !matches!(
+            self,
+            Route::CurrencyPairs
+                | Route::Accuracy
+                | Route::WithdrawConfigs
+                | Route::AssetConfigs
+                | Route::Timestamp
+                | Route::SystemPing
+                | Route::Depth
+                | Route::Price
+                | Route::BookTicker
+                | Route::Ticker24hr
+                | Route::EtfTicker24hr
+                | Route::Trades
+                | Route::Kline
+                | Route::SystemStatus
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Limit,
+    Market,
+    Maker,
+    Ioc,
+    Fok,
+}
+
+fn parse_type(type_: &str) -> Option<(Side, Kind)> {
+    let (side, kind) = type_.split_once('_').unwrap_or((type_, ""));
+    let side = match side {
+        "buy" => Side::Buy,
+        "sell" => Side::Sell,
+        _ => return None,
+    };
+    let kind = match kind {
+        "" => Kind::Limit,
+        "market" => Kind::Market,
+        "maker" => Kind::Maker,
+        "ioc" => Kind::Ioc,
+        "fok" => Kind::Fok,
+        _ => return None,
+    };
+    Some((side, kind))
+}
+
+#[derive(Default)]
+pub(crate) struct Pair {
+    pub price: f64,
+    pub klines: Vec<Candle>,
+    /// Commission reported by `trade_fee_rate` and on fills; balances aren't charged it
+    pub maker_fee: f64,
+    pub taker_fee: f64,
+}
+
+struct Order {
+    id: String,
+    custom_id: Option<String>,
+    symbol: String,
+    type_: String,
+    side: Side,
+    kind: Kind,
+    price: f64,
+    amount: f64,
+    /// Quote budget of a market buy (sent as `price`)
+    budget: f64,
+    executed: f64,
+    quote: f64,
+    status: OrderStatus,
+    time: u64,
+    update_time: u64,
+}
+
+impl Order {
+    fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+
+    fn is_market_buy(&self) -> bool {
+        self.side == Side::Buy && self.kind == Kind::Market
+    }
+
+    /// Base amount still to fill when trading at `price`
+    fn remaining(&self, price: f64) -> f64 {
+        if self.is_market_buy() {
+            (self.budget - self.quote).max(0.0) / price
+        } else {
+            (self.amount - self.executed).max(0.0)
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        if self.is_market_buy() {
+            self.budget - self.quote <= EPSILON
+        } else {
+            self.amount - self.executed <= EPSILON
+        }
+    }
+
+    fn crosses(&self, price: f64) -> bool {
+        match (self.kind, self.side) {
+            (Kind::Market, _) => true,
+            (_, Side::Buy) => self.price >= price,
+            (_, Side::Sell) => self.price <= price,
+        }
+    }
+}
+
+struct Fill {
+    id: u64,
+    order_id: String,
+    symbol: String,
+    price: f64,
+    qty: f64,
+    is_buyer: bool,
+    is_maker: bool,
+    time: u64,
+}
+
+/// Price and total quantity of a book level
+type Level = (f64, f64);
+
+#[derive(Default, Clone, Copy)]
+pub(crate) struct Balance {
+    pub free: f64,
+    pub locked: f64,
+}
+
+/// In-memory state of the mock exchange, for a single account
+#[derive(Default)]
+pub(crate) struct Exchange {
+    pub pairs: BTreeMap<String, Pair>,
+    pub balances: BTreeMap<String, Balance>,
+    pub withdraw_fees: BTreeMap<String, f64>,
+    /// Reported by `system_status` as status 0
+    pub maintenance: bool,
+    pub deposit_suspended: BTreeSet<String>,
+    pub withdraw_suspended: BTreeSet<String>,
+    /// Reported by `api_restrictions`; a fully enabled key when `None`
+    pub api_permissions: Option<ApiPermissions>,
+    /// Added to the local clock by `timestamp.do`, in milliseconds
+    pub clock_offset_ms: i64,
+    orders: Vec<Order>,
+    fills: Vec<Fill>,
+    deposits: Vec<DepositRecord>,
+    withdrawals: Vec<WithdrawRecord>,
+    subscribe_keys: HashSet<String>,
+    next_id: u64,
+}
+
+impl Exchange {
+    pub(crate) fn handle(&mut self, route: Route, p: &BTreeMap<String, String>) -> Reply {
+        match route {
+            Route::CurrencyPairs => Ok(json!(self.pairs.keys().collect::<Vec<_>>())),
+            Route::Accuracy => Ok(self
+                .pairs
+                .keys()
+                .map(|symbol| {
+                    json!({"symbol": symbol, "quantityAccuracy": "8", "pricePrecision": "8", "minTranQua": "0"})
+                })
+                .collect()),
+            Route::WithdrawConfigs => Ok(self
+                .coins()
+                .iter()
+                .map(|coin| {
+                    let can_withdraw = !self.withdraw_suspended.contains(coin);
+                    json!({"assetCode": coin, "min": "0", "canWithDraw": can_withdraw, "fee": fmt(self.withdraw_fee(coin))})
+                })
+                .collect()),
+            Route::AssetConfigs => Ok(self
+                .coins()
+                .iter()
+                .map(|coin| {
+                    json!({
+                        "assetCode": coin, "chainName": coin,
+                        "canDeposit": !self.deposit_suspended.contains(coin),
+                        "canDraw": !self.withdraw_suspended.contains(coin),
+                        "canStationDraw": true, "hasMemo": false,
+                        "assetFee": {
+                            "type": 1, "feeCode": coin, "minAmt": "0", "feeAmt": fmt(self.withdraw_fee(coin)),
+                            "feeRate": "0", "stationFeeAmt": "0", "stationMinAmt": "1", "minDepositAmt": "0", "depositFee": "0",
+                        },
+                    })
+                })
+                .collect()),
+            Route::Timestamp => Ok(json!(get_timestamp().saturating_add_signed(self.clock_offset_ms))),
+            Route::SystemPing => Ok(json!({})),
+            Route::Depth => self.depth(p),
+            Route::Price => self.price(p),
+            Route::BookTicker => self.book_ticker(p),
+            Route::Ticker24hr | Route::EtfTicker24hr => self.ticker(p),
+            Route::Trades => self.trades(p),
+            Route::Kline => self.kline(p),
+            Route::SystemStatus => Ok(json!({"status": if self.maintenance { "0" } else { "1" }})),
+            Route::UserInfo => Ok(self.user_info()),
+            Route::Withdraw => self.withdraw(p),
+            Route::DepositHistory => {
+                let deposits: Vec<&DepositRecord> = self
+                    .deposits
+                    .iter()
+                    .filter(|d| matches_history(p, &d.coin, d.status.into(), d.insert_time))
+                    .collect();
+                Ok(json!({"depositOrders": deposits}))
+            }
+            Route::WithdrawHistory => {
+                let withdrawals: Vec<&WithdrawRecord> = self
+                    .withdrawals
+                    .iter()
+                    .filter(|w| matches_history(p, &w.coin, w.status.into(), w.apply_time))
+                    .filter(|w| p.get("withdrawOrderId").is_none_or(|id| w.withdraw_order_id.as_ref() == Some(id)))
+                    .collect();
+                Ok(json!({"totalPages": 1, "withdraws": withdrawals}))
+            }
+            Route::DepositAddress => {
+                let coin = required(p, "coin")?;
+                Ok(json!({"address": format!("mock-{}-address", coin), "memo": "", "coin": coin}))
+            }
+            Route::AssetDetail => Ok(self
+                .coins()
+                .into_iter()
+                .filter(|coin| p.get("coin").is_none_or(|c| c == coin))
+                .map(|coin| {
+                    let detail = json!({
+                        "minWithdrawAmount": "0", "depositStatus": true,
+                        "withdrawFee": fmt(self.withdraw_fee(&coin)), "withdrawStatus": true,
+                    });
+                    (coin, detail)
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into()),
+            Route::OrderTest => self.create_order(p, true),
+            Route::CreateOrder => self.create_order(p, false),
+            Route::CancelOrder => self.cancel_order(p),
+            Route::CancelOrderBySymbol => {
+                let symbol = required(p, "symbol")?;
+                let open: Vec<usize> = (0..self.orders.len())
+                    .filter(|&i| self.orders[i].symbol == symbol && self.orders[i].is_open())
+                    .collect();
+                Ok(open
+                    .into_iter()
+                    .map(|i| {
+                        self.close(i);
+                        order_json(&self.orders[i])
+                    })
+                    .collect())
+            }
+            Route::OrderInfo => Ok(order_json(&self.orders[self.find_order(p)?])),
+            Route::OpenOrders => self.order_page(p, 200, |o| o.is_open()),
+            Route::OrderHistory => {
+                let status = p.get("status").cloned();
+                self.order_page(p, 50, move |o| {
+                    status.as_ref().is_none_or(|s| s == &i8::from(o.status).to_string())
+                })
+            }
+            Route::AccountInfo => {
+                let balances: Vec<Value> = self
+                    .balances
+                    .iter()
+                    .map(|(asset, b)| json!({"asset": asset, "free": fmt(b.free), "locked": fmt(b.locked)}))
+                    .collect();
+                Ok(json!({
+                    "makerCommission": "0", "takerCommission": "0", "buyerCommission": "0", "sellerCommission": "0",
+                    "canTrade": true, "canWithdraw": true, "canDeposit": true, "balances": balances,
+                }))
+            }
+            Route::TransactionHistory => self.transaction_history(p),
+            Route::TradeFeeRate => Ok(self
+                .pairs
+                .iter()
+                .filter(|(symbol, _)| p.get("category").is_none_or(|c| c == *symbol))
+                .map(|(symbol, pair)| json!({"symbol": symbol, "makerCommission": fmt(pair.maker_fee * 100.0), "takerCommission": fmt(pair.taker_fee * 100.0)}))
+                .collect()),
+            Route::ApiRestrictions => Ok(match &self.api_permissions {
+                Some(permissions) => json!(permissions),
+                None => json!({
+                    "ipRestrict": false, "createTime": 0, "enableWithdrawals": true,
+                    "enableInternalTransfer": true, "enableReading": true, "enableSpotTrading": true,
+                }),
+            }),
+            Route::GetSubscribeKey => {
+                let key = uuid_spot();
+                self.subscribe_keys.insert(key.clone());
+                Ok(json!({"key": key}))
+            }
+            Route::RefreshSubscribeKey | Route::DestroySubscribeKey => {
+                let key = required(p, "subscribeKey")?;
+                let known = if route == Route::DestroySubscribeKey {
+                    self.subscribe_keys.remove(key)
+                } else {
+                    self.subscribe_keys.contains(key)
+                };
+                if !known {
+                    return Err((INVALID_PARAMETER, "invalid subscribeKey".into()));
+                }
+                Ok(json!({}))
+            }
+        }
+    }
+
+    /// Move the reference price, filling resting orders it crosses at their limit price
+    pub(crate) fn set_price(&mut self, symbol: &str, price: f64) {
+        if let Some(pair) = self.pairs.get_mut(symbol) {
+            pair.price = price;
+        }
+        for i in 0..self.orders.len() {
+            let order = &self.orders[i];
+            if order.symbol == symbol && order.is_open() && order.crosses(price) {
+                let (qty, at) = (order.remaining(order.price), order.price);
+                self.fill(i, qty, at, true);
+            }
+        }
+    }
+
+    pub(crate) fn deposit(&mut self, coin: &str, amount: f64) {
+        self.balances.entry(coin.to_string()).or_default().free += amount;
+        self.deposits.push(DepositRecord {
+            id: Some((self.deposits.len() + 1).to_string()),
+            insert_time: get_timestamp() as i64,
+            amount: fmt(amount),
+            address: Some(format!("mock-{}-address", coin)),
+            network_name: Some(coin.to_string()),
+            tx_id: Some(uuid_spot()),
+            coin: coin.to_string(),
+            status: DepositStatus::Success,
+        });
+    }
+
+    fn coins(&self) -> Vec<String> {
+        let mut coins: Vec<String> = self.balances.keys().cloned().collect();
+        for symbol in self.pairs.keys() {
+            coins.extend(symbol.split('_').map(str::to_string));
+        }
+        coins.sort();
+        coins.dedup();
+        coins
+    }
+
+    fn withdraw_fee(&self, coin: &str) -> f64 {
+        self.withdraw_fees.get(coin).copied().unwrap_or(0.0)
+    }
+
+    fn pair(&self, p: &BTreeMap<String, String>) -> std::result::Result<(&str, &Pair), (i32, String)> {
+        let symbol = required(p, "symbol")?;
+        let (symbol, pair) = self
+            .pairs
+            .get_key_value(symbol)
+            .ok_or_else(|| (INVALID_PAIR, "invalid trading pair".to_string()))?;
+        Ok((symbol, pair))
+    }
+
+    fn depth(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (symbol, _) = self.pair(p)?;
+        let size: usize = optional(p, "size")?.unwrap_or(60.0) as usize;
+        let (asks, bids) = self.book(symbol);
+        let level = |(price, qty): &(f64, f64)| json!([fmt(*price), fmt(*qty)]);
+        Ok(json!({
+            "asks": asks.iter().take(size).map(level).collect::<Vec<_>>(),
+            "bids": bids.iter().take(size).map(level).collect::<Vec<_>>(),
+            "timestamp": get_timestamp(),
+        }))
+    }
+
+    /// Resting orders aggregated by price: asks ascending, bids descending
+    fn book(&self, symbol: &str) -> (Vec<Level>, Vec<Level>) {
+        let side = |side: Side| {
+            let mut levels: Vec<Level> = Vec::new();
+            for order in self.orders.iter().filter(|o| o.symbol == symbol && o.is_open() && o.side == side) {
+                let qty = order.remaining(order.price);
+                match levels.iter_mut().find(|(price, _)| *price == order.price) {
+                    Some(level) => level.1 += qty,
+                    None => levels.push((order.price, qty)),
+                }
+            }
+            levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+            levels
+        };
+        let mut bids = side(Side::Buy);
+        bids.reverse();
+        (side(Side::Sell), bids)
+    }
+
+    fn price(&self, p: &BTreeMap<String, String>) -> Reply {
+        if p.contains_key("symbol") {
+            let (symbol, pair) = self.pair(p)?;
+            return Ok(json!([{"symbol": symbol, "price": fmt(pair.price)}]));
+        }
+        Ok(self
+            .pairs
+            .iter()
+            .map(|(symbol, pair)| json!({"symbol": symbol, "price": fmt(pair.price)}))
+            .collect())
+    }
+
+    fn book_ticker(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (symbol, pair) = self.pair(p)?;
+        let (asks, bids) = self.book(symbol);
+        let (ask, ask_qty) = asks.first().copied().unwrap_or((pair.price, 0.0));
+        let (bid, bid_qty) = bids.first().copied().unwrap_or((pair.price, 0.0));
+        Ok(json!({
+            "symbol": symbol, "askPrice": fmt(ask), "askQty": fmt(ask_qty),
+            "bidPrice": fmt(bid), "bidQty": fmt(bid_qty),
+        }))
+    }
+
+    fn ticker(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (symbol, pair) = self.pair(p)?;
+        let since = get_timestamp().saturating_sub(24 * 60 * 60 * 1000);
+        let trades: Vec<&Fill> = self.taker_fills(symbol).filter(|f| f.time >= since).collect();
+        let high = trades.iter().map(|f| f.price).fold(pair.price, f64::max);
+        let low = trades.iter().map(|f| f.price).fold(pair.price, f64::min);
+        Ok(json!([{
+            "symbol": symbol,
+            "timestamp": get_timestamp(),
+            "ticker": {
+                "high": fmt(high),
+                "low": fmt(low),
+                "vol": fmt(trades.iter().map(|f| f.qty).sum()),
+                "turnover": fmt(trades.iter().map(|f| f.qty * f.price).sum()),
+                "change": "0",
+                "latest": fmt(pair.price),
+            },
+        }]))
+    }
+
+    fn trades(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (symbol, _) = self.pair(p)?;
+        let size = optional(p, "size")?.unwrap_or(100.0) as usize;
+        let since = optional(p, "time")?.unwrap_or(0.0) as u64;
+        Ok(self
+            .taker_fills(symbol)
+            .rev()
+            .filter(|f| f.time >= since)
+            .take(size)
+            .map(|f| {
+                let side = if f.is_buyer { "buy" } else { "sell" };
+                json!({"date_ms": f.time, "amount": fmt(f.qty), "price": fmt(f.price), "type": side, "tid": f.id.to_string()})
+            })
+            .collect())
+    }
+
+    /// One entry per trade, as seen from the side that took liquidity
+    fn taker_fills<'a>(&'a self, symbol: &'a str) -> impl DoubleEndedIterator<Item = &'a Fill> + 'a {
+        self.fills.iter().filter(move |f| f.symbol == symbol && !f.is_maker)
+    }
+
+    fn kline(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (_, pair) = self.pair(p)?;
+        let size = optional(p, "size")?.unwrap_or(2000.0) as usize;
+        let time = optional(p, "time")?.unwrap_or(0.0) as u64;
+        Ok(pair
+            .klines
+            .iter()
+            .filter(|c| c.time >= time)
+            .take(size)
+            .map(|c| json!([c.time, c.open, c.high, c.low, c.close, c.volume]))
+            .collect())
+    }
+
+    fn user_info(&self) -> Value {
+        self.coins()
+            .iter()
+            .map(|coin| {
+                let balance = self.balances.get(coin).copied().unwrap_or_default();
+                json!({
+                    "coin": coin,
+                    "usableAmt": fmt(balance.free),
+                    "assetAmt": fmt(balance.free + balance.locked),
+                    "freezeAmt": fmt(balance.locked),
+                    "networkList": [{
+                        "name": coin, "network": coin, "isDefault": true,
+                        "withdrawEnable": !self.withdraw_suspended.contains(coin),
+                        "depositEnable": !self.deposit_suspended.contains(coin),
+                        "withdrawFee": fmt(self.withdraw_fee(coin)), "withdrawMin": "0", "minLimit": "1",
+                    }],
+                })
+            })
+            .collect()
+    }
+
+    fn withdraw(&mut self, p: &BTreeMap<String, String>) -> Reply {
+        let coin = required(p, "coin")?.to_string();
+        let address = required(p, "address")?.to_string();
+        let amount = number(p, "amount")?;
+        let fee = optional(p, "fee")?.unwrap_or(0.0);
+        let internal = p.get("type").is_some_and(|t| t == "1");
+        if amount <= 0.0 {
+            return Err((INVALID_AMOUNT, "amount must be positive".into()));
+        }
+        let balance = self.balances.entry(coin.clone()).or_default();
+        if balance.free + EPSILON < amount {
+            return Err((INSUFFICIENT_BALANCE, "insufficient balance".into()));
+        }
+        balance.free -= amount;
+
+        self.next_id += 1;
+        self.withdrawals.push(WithdrawRecord {
+            id: self.next_id as i64,
+            amount: fmt(amount),
+            coin: coin.clone(),
+            address: Some(address),
+            withdraw_order_id: p.get("withdrawOrderId").cloned(),
+            fee: Some(fmt(fee)),
+            network_name: p.get("networkName").cloned().or(Some(coin.clone())),
+            transfer_type: Some(if internal { "Intra-site Transfer" } else { "Digital Asset Withdrawal" }.into()),
+            tx_id: None,
+            fee_asset_code: Some(coin),
+            apply_time: get_timestamp() as i64,
+            status: WithdrawStatus::Applying,
+        });
+        Ok(json!({"fee": fee, "withdrawId": self.next_id}))
+    }
+
+    fn create_order(&mut self, p: &BTreeMap<String, String>, test_only: bool) -> Reply {
+        let (symbol, _) = self.pair(p)?;
+        let symbol = symbol.to_string();
+        let type_ = required(p, "type")?.to_string();
+        let (side, kind) = parse_type(&type_).ok_or_else(|| (INVALID_ORDER_TYPE, "invalid order type".to_string()))?;
+
+        // Market buys are sized by the quote amount sent as `price`
+        let (price, amount, budget) = match (side, kind) {
+            (Side::Buy, Kind::Market) => (0.0, 0.0, number(p, "price")?),
+            (Side::Sell, Kind::Market) => (0.0, number(p, "amount")?, 0.0),
+            _ => (number(p, "price")?, number(p, "amount")?, 0.0),
+        };
+        if price < 0.0 || amount < 0.0 || (price == 0.0 && kind != Kind::Market) || (amount == 0.0 && budget == 0.0) {
+            return Err((INVALID_AMOUNT, "price and amount must be positive".into()));
+        }
+
+        let custom_id = p.get("custom_id").cloned();
+        if custom_id.is_some() && self.orders.iter().any(|o| o.custom_id == custom_id) {
+            return Err((CUSTOM_ID_DUPLICATED as i32, "customID duplicated".into()));
+        }
+
+        let (base, quote) = symbol.split_once('_').unwrap_or((&symbol, ""));
+        let (asset, locked) = match side {
+            Side::Buy if kind == Kind::Market => (quote, budget),
+            Side::Buy => (quote, price * amount),
+            Side::Sell => (base, amount),
+        };
+        let balance = self.balances.get(asset).copied().unwrap_or_default();
+        if balance.free + EPSILON < locked {
+            return Err((INSUFFICIENT_BALANCE, "insufficient balance".into()));
+        }
+        if test_only {
+            return Ok(json!({"symbol": symbol}));
+        }
+
+        let asset = asset.to_string();
+        let balance = self.balances.entry(asset).or_default();
+        balance.free -= locked;
+        balance.locked += locked;
+
+        self.next_id += 1;
+        let now = get_timestamp();
+        self.orders.push(Order {
+            id: format!("mock-{}", self.next_id),
+            custom_id: custom_id.clone(),
+            symbol: symbol.clone(),
+            type_,
+            side,
+            kind,
+            price,
+            amount,
+            budget,
+            executed: 0.0,
+            quote: 0.0,
+            status: OrderStatus::New,
+            time: now,
+            update_time: now,
+        });
+        let index = self.orders.len() - 1;
+        self.match_order(index);
+
+        let order = &self.orders[index];
+        Ok(json!({"symbol": symbol, "order_id": order.id, "custom_id": custom_id}))
+    }
+
+    /// Match a new order against resting orders, then against the reference price
+    ///
+    /// The reference price has unlimited depth: any order crossing it fills in
+    /// full. Post-only orders that would cross are cancelled; IOC, FOK and
+    /// market remainders are cancelled; limit remainders rest.
+    fn match_order(&mut self, index: usize) {
+        let reference = self.pairs[&self.orders[index].symbol].price;
+        let order = &self.orders[index];
+        let best_resting = self.best_resting(index).map(|i| self.orders[i].price);
+        let would_cross = order.crosses(reference) || best_resting.is_some_and(|price| order.crosses(price));
+
+        match order.kind {
+            Kind::Maker if would_cross => return self.close(index),
+            Kind::Fok if !order.crosses(reference) => return self.close(index),
+            Kind::Maker => return,
+            _ => {}
+        }
+
+        while let Some(maker) = self.best_resting(index) {
+            let price = self.orders[maker].price;
+            let qty = self.orders[index].remaining(price).min(self.orders[maker].remaining(price));
+            self.fill(index, qty, price, false);
+            self.fill(maker, qty, price, true);
+        }
+
+        let order = &self.orders[index];
+        if !order.is_done() && order.crosses(reference) {
+            let qty = order.remaining(reference);
+            self.fill(index, qty, reference, false);
+        }
+
+        let order = &self.orders[index];
+        if !order.is_done() && order.kind != Kind::Limit {
+            self.close(index);
+        }
+    }
+
+    /// Best-priced opposite resting order the order at `index` crosses (time priority on ties)
+    fn best_resting(&self, index: usize) -> Option<usize> {
+        let taker = &self.orders[index];
+        if taker.is_done() {
+            return None;
+        }
+        (0..self.orders.len())
+            .filter(|&i| {
+                let o = &self.orders[i];
+                i != index && o.symbol == taker.symbol && o.side != taker.side && o.is_open() && taker.crosses(o.price)
+            })
+            .min_by(|&a, &b| {
+                let (a, b) = (self.orders[a].price, self.orders[b].price);
+                match taker.side {
+                    Side::Buy => a.total_cmp(&b),
+                    Side::Sell => b.total_cmp(&a),
+                }
+            })
+    }
+
+    fn fill(&mut self, index: usize, qty: f64, price: f64, is_maker: bool) {
+        if qty <= EPSILON {
+            return;
+        }
+        let now = get_timestamp();
+        let order = &mut self.orders[index];
+        order.executed += qty;
+        order.quote += qty * price;
+        order.update_time = now;
+        order.status = if order.is_done() {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+
+        let (side, market_buy, limit) = (order.side, order.is_market_buy(), order.price);
+        let order_id = order.id.clone();
+        let symbol = order.symbol.clone();
+        let (base, quote) = symbol.split_once('_').unwrap_or((&symbol, ""));
+        match side {
+            Side::Buy => {
+                let reserved = if market_buy { qty * price } else { qty * limit };
+                let quote_balance = self.balances.entry(quote.to_string()).or_default();
+                quote_balance.locked = (quote_balance.locked - reserved).max(0.0);
+                quote_balance.free += reserved - qty * price;
+                self.balances.entry(base.to_string()).or_default().free += qty;
+            }
+            Side::Sell => {
+                let base_balance = self.balances.entry(base.to_string()).or_default();
+                base_balance.locked = (base_balance.locked - qty).max(0.0);
+                self.balances.entry(quote.to_string()).or_default().free += qty * price;
+            }
+        }
+
+        self.next_id += 1;
+        self.fills.push(Fill {
+            id: self.next_id,
+            order_id,
+            symbol,
+            price,
+            qty,
+            is_buyer: side == Side::Buy,
+            is_maker,
+            time: now,
+        });
+    }
+
+    /// Cancel whatever is left of an order and release its locked balance
+    fn close(&mut self, index: usize) {
+        let order = &mut self.orders[index];
+        let unfilled = match order.side {
+            Side::Buy if order.is_market_buy() => order.budget - order.quote,
+            Side::Buy => (order.amount - order.executed) * order.price,
+            Side::Sell => order.amount - order.executed,
+        }
+        .max(0.0);
+        order.status = if order.executed > EPSILON {
+            OrderStatus::PartiallyFilledCancelled
+        } else {
+            OrderStatus::Cancelled
+        };
+        order.update_time = get_timestamp();
+
+        let (base, quote) = order.symbol.split_once('_').unwrap_or((&order.symbol, ""));
+        let asset = if order.side == Side::Buy { quote } else { base };
+        let balance = self.balances.entry(asset.to_string()).or_default();
+        balance.locked = (balance.locked - unfilled).max(0.0);
+        balance.free += unfilled;
+    }
+
+    fn find_order(&self, p: &BTreeMap<String, String>) -> std::result::Result<usize, (i32, String)> {
+        let symbol = required(p, "symbol")?;
+        let order_id = p.get("orderId");
+        let custom_id = p.get("origClientOrderId");
+        if order_id.is_none() && custom_id.is_none() {
+            return Err((MISSING_PARAMETER, "orderId or origClientOrderId is required".into()));
+        }
+        self.orders
+            .iter()
+            .position(|o| {
+                o.symbol == symbol
+                    && order_id.is_none_or(|id| &o.id == id)
+                    && custom_id.is_none_or(|id| o.custom_id.as_ref() == Some(id))
+            })
+            .ok_or_else(|| (ORDER_NOT_FOUND as i32, "order does not exist".into()))
+    }
+
+    fn cancel_order(&mut self, p: &BTreeMap<String, String>) -> Reply {
+        let index = self.find_order(p)?;
+        match self.orders[index].status {
+            OrderStatus::Filled => return Err((ORDER_FILLED as i32, "order has been filled".into())),
+            status if status.is_final() => return Err((ORDER_CANCELLED as i32, "order has been cancelled".into())),
+            _ => self.close(index),
+        }
+        let mut order = order_json(&self.orders[index]);
+        order["origClientOrderId"] = json!(self.orders[index].custom_id);
+        Ok(order)
+    }
+
+    fn order_page(&self, p: &BTreeMap<String, String>, max_length: u32, filter: impl Fn(&Order) -> bool) -> Reply {
+        let (symbol, _) = self.pair(p)?;
+        let page = number(p, "current_page")? as usize;
+        let length = number(p, "page_length")? as u32;
+        if length == 0 || length > max_length {
+            return Err((INVALID_PAGE_LENGTH, format!("page_length must be between 1 and {}", max_length)));
+        }
+        let length = length as usize;
+
+        let matching: Vec<&Order> = self.orders.iter().rev().filter(|o| o.symbol == symbol && filter(o)).collect();
+        let orders: Vec<Value> = matching
+            .iter()
+            .skip(page.saturating_sub(1) * length)
+            .take(length)
+            .map(|o| order_json(o))
+            .collect();
+        Ok(json!({"current_page": page, "page_length": length, "total": matching.len(), "orders": orders}))
+    }
+
+    fn transaction_history(&self, p: &BTreeMap<String, String>) -> Reply {
+        let (symbol, pair) = self.pair(p)?;
+        let start = p.get("startTime").map(|t| parse_utc8(t)).transpose()?.unwrap_or(0);
+        let end = p.get("endTime").map(|t| parse_utc8(t)).transpose()?.unwrap_or(u64::MAX);
+        let from_id = optional(p, "fromId")?.unwrap_or(0.0) as u64;
+        let limit = optional(p, "limit")?.unwrap_or(100.0) as usize;
+
+        Ok(self
+            .fills
+            .iter()
+            .filter(|f| f.symbol == symbol && f.id >= from_id && f.time >= start && f.time <= end)
+            .take(limit)
+            .map(|f| {
+                let rate = if f.is_maker { pair.maker_fee } else { pair.taker_fee };
+                let received = if f.is_buyer { f.qty } else { f.price * f.qty };
+                json!({
+                    "symbol": f.symbol, "id": f.id.to_string(), "orderId": f.order_id,
+                    "price": fmt(f.price), "qty": fmt(f.qty), "quoteQty": fmt(f.price * f.qty),
+                    "commission": fmt(received * rate), "time": f.time, "isBuyer": f.is_buyer, "isMaker": f.is_maker,
+                })
+            })
+            .collect())
+    }
+}
+
+fn order_json(o: &Order) -> Value {
+    json!({
+        "symbol": o.symbol,
+        "orderId": o.id,
+        "clientOrderId": o.custom_id,
+        "price": fmt(o.price),
+        "origQty": fmt(o.amount),
+        "executedQty": fmt(o.executed),
+        "cummulativeQuoteQty": fmt(o.quote),
+        "origQuoteOrderQty": fmt(o.budget),
+        "status": o.status,
+        "type": o.type_,
+        "time": o.time,
+        "updateTime": o.update_time,
+    })
+}
+
+fn matches_history(p: &BTreeMap<String, String>, coin: &str, status: i8, time: i64) -> bool {
+    let bound = |key: &str| p.get(key).and_then(|t| t.parse::<i64>().ok());
+    p.get("coin").is_none_or(|c| c == coin)
+        && p.get("status").is_none_or(|s| s.parse() == Ok(status))
+        && bound("startTime").is_none_or(|start| time >= start)
+        && bound("endTime").is_none_or(|end| time <= end)
+}
+
+fn required<'a>(p: &'a BTreeMap<String, String>, key: &str) -> std::result::Result<&'a str, (i32, String)> {
+    p.get(key)
+        .map(String::as_str)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| (MISSING_PARAMETER, format!("{} is required", key)))
+}
+
+fn number(p: &BTreeMap<String, String>, key: &str) -> std::result::Result<f64, (i32, String)> {
+    optional(p, key)?.ok_or_else(|| (MISSING_PARAMETER, format!("{} is required", key)))
+}
+
+fn optional(p: &BTreeMap<String, String>, key: &str) -> std::result::Result<Option<f64>, (i32, String)> {
+    p.get(key)
+        .map(|v| v.parse().map_err(|_| (INVALID_PARAMETER, format!("{} is not a number", key))))
+        .transpose()
+}
+
+/// Parse `yyyy-MM-dd` or `yyyy-MM-dd HH:mm:ss` (UTC+8) into milliseconds
+fn parse_utc8(value: &str) -> std::result::Result<u64, (i32, String)> {
+    let invalid = || (INVALID_PARAMETER, format!("invalid time {}", value));
+    let (date, time) = value.split_once(' ').unwrap_or((value, "00:00:00"));
+    let numbers = |s: &str, sep: char| -> Option<Vec<i64>> { s.split(sep).map(|n| n.parse().ok()).collect() };
+    let (date, time) = (numbers(date, '-').ok_or_else(invalid)?, numbers(time, ':').ok_or_else(invalid)?);
+    let (&[y, m, d], &[hh, mm, ss]) = (&date[..], &time[..]) else {
+        return Err(invalid());
+    };
+
+    // Days since 1970-01-01 (Howard Hinnant's algorithm)
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86_400 + hh * 3600 + mm * 60 + ss - 8 * 3600;
+    u64::try_from(secs * 1000).map_err(|_| invalid())
+}
+
+/// Format an amount with up to 8 decimals and no trailing zeros
+fn fmt(value: f64) -> String {
+    let formatted = format!("{:.8}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::format_utc8;
+
+    fn exchange() -> Exchange {
+        let mut exchange = Exchange::default();
+        exchange.pairs.insert("lbk_usdt".into(), Pair { price: 1.0, ..Pair::default() });
+        exchange.balances.insert("usdt".into(), Balance { free: 100.0, locked: 0.0 });
+        exchange.balances.insert("lbk".into(), Balance { free: 100.0, locked: 0.0 });
+        exchange
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_resting_orders_match() {
+        let mut exchange = exchange();
+        let sell = params(&[("symbol", "lbk_usdt"), ("type", "sell"), ("price", "1.5"), ("amount", "10")]);
+        exchange.handle(Route::CreateOrder, &sell).unwrap();
+        assert_eq!(exchange.balances["lbk"].locked, 10.0);
+
+        // Takes the resting sell for 10 at 1.5, the rest trades at the reference price of 1
+        let buy = params(&[("symbol", "lbk_usdt"), ("type", "buy_ioc"), ("price", "2"), ("amount", "15")]);
+        let order = exchange.handle(Route::CreateOrder, &buy).unwrap();
+        let info = exchange
+            .handle(Route::OrderInfo, &params(&[("symbol", "lbk_usdt"), ("orderId", order["order_id"].as_str().unwrap())]))
+            .unwrap();
+
+        assert_eq!(info["status"], 2);
+        assert_eq!(info["cummulativeQuoteQty"], "20");
+        assert_eq!(exchange.balances["usdt"].free, 95.0);
+        assert_eq!(exchange.balances["usdt"].locked, 0.0);
+        assert_eq!(exchange.balances["lbk"].free, 105.0);
+        assert_eq!(exchange.balances["lbk"].locked, 0.0);
+    }
+
+    #[test]
+    fn test_post_only_and_insufficient_balance() {
+        let mut exchange = exchange();
+        let maker = params(&[("symbol", "lbk_usdt"), ("type", "buy_maker"), ("price", "1.1"), ("amount", "1")]);
+        let order = exchange.handle(Route::CreateOrder, &maker).unwrap();
+        assert_eq!(exchange.orders[0].status, OrderStatus::Cancelled);
+        assert_eq!(order["order_id"], "mock-1");
+
+        let big = params(&[("symbol", "lbk_usdt"), ("type", "buy"), ("price", "0.5"), ("amount", "1000")]);
+        assert_eq!(exchange.handle(Route::CreateOrder, &big).unwrap_err().0, INSUFFICIENT_BALANCE);
+        assert_eq!(exchange.balances["usdt"].free, 100.0);
+    }
+
+    #[test]
+    fn test_parse_utc8() {
+        assert_eq!(parse_utc8(&format_utc8(1_499_865_549_000)).unwrap(), 1_499_865_549_000);
+        assert_eq!(parse_utc8("1970-01-01 08:00:00").unwrap(), 0);
+        assert!(parse_utc8("yesterday").is_err());
+        assert_eq!(fmt(24.000000001), "24");
+    }
+}
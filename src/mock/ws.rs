@@ -0,0 +1,490 @@
+use crate::config::Config;
+use crate::errors::Result;
+use crate::util::uuid_spot;
+use serde_json::{json, Value};
+use std::io::ErrorKind as IoErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+/// How often connection threads check for outgoing pushes while idle
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Step of a recorded stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayEvent {
+    /// Push `text` to every connection subscribed to its `type` and `pair`
+    Message { at: Duration, text: String },
+    /// Drop every open connection without a close frame
+    Disconnect { at: Duration },
+    /// Pause until one more `subscribe` request has arrived than at the
+    /// previous pause (the first one starts playback). Time spent paused
+    /// doesn't count towards later offsets.
+    AwaitSubscribe { at: Duration },
+}
+
+impl ReplayEvent {
+    fn at(&self) -> Duration {
+        match self {
+            ReplayEvent::Message { at, .. }
+            | ReplayEvent::Disconnect { at }
+            | ReplayEvent::AwaitSubscribe { at } => *at,
+        }
+    }
+}
+
+/// Timeline of WebSocket pushes and disconnects, relative to the first subscription
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push `text` at offset `at`
+    pub fn message(mut self, at: Duration, text: impl Into<String>) -> Self {
+        self.events.push(ReplayEvent::Message { at, text: text.into() });
+        self
+    }
+
+    /// Drop all connections at offset `at`
+    pub fn disconnect(mut self, at: Duration) -> Self {
+        self.events.push(ReplayEvent::Disconnect { at });
+        self
+    }
+
+    /// Pause at offset `at` until a client subscribes again
+    pub fn await_subscribe(mut self, at: Duration) -> Self {
+        self.events.push(ReplayEvent::AwaitSubscribe { at });
+        self
+    }
+
+    /// Parse JSON lines of `{"at_ms": 120, "message": {...}}`, `{"at_ms": 500, "disconnect": true}`
+    /// or `{"at_ms": 500, "await_subscribe": true}`
+    ///
+    /// `message` may be a JSON object or the raw frame as a string. Blank lines are skipped.
+    pub fn from_jsonl(text: &str) -> Result<Recording> {
+        let mut recording = Recording::new();
+        for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let entry: Value = serde_json::from_str(line)?;
+            let at = entry["at_ms"]
+                .as_u64()
+                .map(Duration::from_millis)
+                .ok_or_else(|| format!("line {}: missing at_ms", number + 1))?;
+            recording = match &entry["message"] {
+                Value::String(text) => recording.message(at, text.clone()),
+                Value::Null if entry["disconnect"] == true => recording.disconnect(at),
+                Value::Null if entry["await_subscribe"] == true => recording.await_subscribe(at),
+                Value::Null => {
+                    return Err(format!("line {}: expected message, disconnect or await_subscribe", number + 1).into())
+                }
+                message => recording.message(at, message.to_string()),
+            };
+        }
+        Ok(recording)
+    }
+
+    /// Load a recording from a JSON lines file (see [`Recording::from_jsonl`])
+    pub fn load(path: impl AsRef<Path>) -> Result<Recording> {
+        Self::from_jsonl(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Replay settings for [`MockWsServer`]
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Playback speed; 2.0 replays twice as fast, `f64::INFINITY` without delays
+    pub speed: f64,
+    /// Send `{"action":"ping"}` at this interval and drop connections that
+    /// haven't answered the previous ping
+    pub ping_interval: Option<Duration>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            ping_interval: None,
+        }
+    }
+}
+
+enum Outgoing {
+    Text(String),
+    Drop,
+}
+
+struct Connection {
+    id: usize,
+    sender: Sender<Outgoing>,
+    /// (`subscribe` channel, `pair`)
+    subscriptions: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct Hub {
+    connections: Mutex<Vec<Connection>>,
+    /// `subscribe` requests received so far
+    subscribes: Mutex<usize>,
+    subscribed: Condvar,
+    shutdown: AtomicBool,
+    accepted: AtomicUsize,
+}
+
+impl Hub {
+    fn connections(&self) -> MutexGuard<'_, Vec<Connection>> {
+        self.connections.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn subscribes(&self) -> MutexGuard<'_, usize> {
+        self.subscribes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn count_subscribe(&self) {
+        *self.subscribes() += 1;
+        self.subscribed.notify_all();
+    }
+
+    /// Block until `count` subscribe requests have arrived, returning `false` on shutdown
+    fn wait_for_subscribes(&self, count: usize) -> bool {
+        let mut subscribes = self.subscribes();
+        while *subscribes < count && !self.shutdown.load(Ordering::SeqCst) {
+            subscribes = self.subscribed.wait(subscribes).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        !self.shutdown.load(Ordering::SeqCst)
+    }
+
+    fn stop(&self) {
+        let _subscribes = self.subscribes();
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.subscribed.notify_all();
+    }
+
+    fn broadcast(&self, text: &str) {
+        let target = serde_json::from_str::<Value>(text).ok().and_then(|push| {
+            let channel = push["type"].as_str()?.to_string();
+            Some((channel, push["pair"].as_str().unwrap_or_default().to_string()))
+        });
+        for connection in self.connections().iter() {
+            let subscribed = match &target {
+                Some((channel, pair)) => connection
+                    .subscriptions
+                    .iter()
+                    .any(|(c, p)| c == channel && (p == pair || p == "all")),
+                None => true,
+            };
+            if subscribed {
+                let _ = connection.sender.send(Outgoing::Text(text.to_string()));
+            }
+        }
+    }
+
+    fn drop_all(&self) {
+        for connection in self.connections().drain(..) {
+            let _ = connection.sender.send(Outgoing::Drop);
+        }
+    }
+}
+
+/// In-process WebSocket server replaying a [`Recording`] with LBank's
+/// subscribe / unsubscribe / ping / pong protocol
+///
+/// Playback starts with the first subscription and can pause for later ones
+/// (see [`ReplayEvent::AwaitSubscribe`]). Pushes go to connections
+/// subscribed to their `type` and `pair` (`"all"` matches any pair); pushes
+/// sent while nobody is connected are lost, as they would be live. The server
+/// stops when dropped.
+pub struct MockWsServer {
+    addr: SocketAddr,
+    hub: Arc<Hub>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl MockWsServer {
+    /// Start a server on a free local port
+    pub fn start(recording: Recording, options: ReplayOptions) -> Result<MockWsServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let hub = Arc::new(Hub::default());
+
+        let accept = {
+            let hub = hub.clone();
+            let ping_interval = options.ping_interval;
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if hub.shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let hub = hub.clone();
+                        std::thread::spawn(move || serve(stream, &hub, ping_interval));
+                    }
+                }
+            })
+        };
+        let replay = {
+            let hub = hub.clone();
+            std::thread::spawn(move || replay(recording, options.speed, &hub))
+        };
+
+        Ok(MockWsServer {
+            addr,
+            hub,
+            threads: vec![accept, replay],
+        })
+    }
+
+    /// WebSocket URL of the server, e.g. `ws://127.0.0.1:53127/ws/V2/`
+    pub fn url(&self) -> String {
+        format!("ws://{}/ws/V2/", self.addr)
+    }
+
+    /// Default config with `ws_endpoint` pointing at this server
+    pub fn config(&self) -> Config {
+        Config {
+            ws_endpoint: self.url(),
+            ..Config::default()
+        }
+    }
+
+    /// Number of WebSocket handshakes completed so far (grows on every reconnect)
+    pub fn connection_count(&self) -> usize {
+        self.hub.accepted.load(Ordering::SeqCst)
+    }
+
+    /// Drop every open connection now, in addition to scripted disconnects
+    pub fn disconnect_all(&self) {
+        self.hub.drop_all();
+    }
+}
+
+impl Drop for MockWsServer {
+    fn drop(&mut self) {
+        self.hub.stop();
+        self.hub.drop_all();
+        let _ = TcpStream::connect(self.addr);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn replay(recording: Recording, speed: f64, hub: &Hub) {
+    let mut awaited = 1;
+    if !hub.wait_for_subscribes(awaited) {
+        return;
+    }
+
+    let scaled = |at: Duration| Duration::from_secs_f64(at.as_secs_f64() / speed.max(f64::MIN_POSITIVE));
+    let mut origin = Instant::now();
+    for event in recording.events {
+        let due = origin + scaled(event.at());
+        while Instant::now() < due {
+            if hub.shutdown.load(Ordering::SeqCst) {
+                return;
+            }
+            std::thread::sleep((due - Instant::now()).min(POLL_INTERVAL * 5));
+        }
+        match event {
+            ReplayEvent::Message { text, .. } => hub.broadcast(&text),
+            ReplayEvent::Disconnect { .. } => hub.drop_all(),
+            ReplayEvent::AwaitSubscribe { at } => {
+                awaited += 1;
+                if !hub.wait_for_subscribes(awaited) {
+                    return;
+                }
+                origin = Instant::now().checked_sub(scaled(at)).unwrap_or(origin);
+            }
+        }
+    }
+}
+
+/// Run one client connection until it closes or is dropped
+fn serve(stream: TcpStream, hub: &Hub, ping_interval: Option<Duration>) {
+    let Ok(raw) = stream.try_clone() else {
+        return;
+    };
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    if raw.set_read_timeout(Some(POLL_INTERVAL)).is_err() {
+        return;
+    }
+
+    let id = hub.accepted.fetch_add(1, Ordering::SeqCst);
+    let (sender, outgoing) = mpsc::channel();
+    hub.connections().push(Connection {
+        id,
+        sender,
+        subscriptions: Vec::new(),
+    });
+
+    let _ = run(&mut socket, hub, id, &outgoing, ping_interval);
+    hub.connections().retain(|c| c.id != id);
+}
+
+fn run(
+    socket: &mut WebSocket<TcpStream>,
+    hub: &Hub,
+    id: usize,
+    outgoing: &Receiver<Outgoing>,
+    ping_interval: Option<Duration>,
+) -> tungstenite::Result<()> {
+    let mut last_ping = Instant::now();
+    let mut awaiting_pong: Option<String> = None;
+
+    loop {
+        while let Ok(message) = outgoing.try_recv() {
+            match message {
+                Outgoing::Text(text) => socket.send(Message::text(text))?,
+                Outgoing::Drop => return Ok(()),
+            }
+        }
+
+        if let Some(interval) = ping_interval {
+            if last_ping.elapsed() >= interval {
+                if awaiting_pong.is_some() {
+                    return Ok(());
+                }
+                let ping = uuid_spot();
+                socket.send(Message::text(json!({"action": "ping", "ping": ping}).to_string()))?;
+                awaiting_pong = Some(ping);
+                last_ping = Instant::now();
+            }
+        }
+
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(), IoErrorKind::WouldBlock | IoErrorKind::TimedOut) => {
+                continue
+            }
+            Err(e) => return Err(e),
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        match request["action"].as_str() {
+            Some("ping") => {
+                socket.send(Message::text(json!({"action": "pong", "pong": request["ping"]}).to_string()))?
+            }
+            Some("pong") if awaiting_pong.as_deref() == request["pong"].as_str() => awaiting_pong = None,
+            Some(action @ ("subscribe" | "unsubscribe")) => {
+                let channel = request["subscribe"].as_str().unwrap_or_default().to_string();
+                let pair = request["pair"].as_str().unwrap_or_default().to_string();
+                let private = matches!(channel.as_str(), "orderUpdate" | "assetUpdate");
+                if private && request["subscribeKey"].as_str().is_none_or(str::is_empty) {
+                    let error = json!({"status": "error", "message": "subscribeKey is required"});
+                    socket.send(Message::text(error.to_string()))?;
+                    continue;
+                }
+
+                let mut connections = hub.connections();
+                if let Some(connection) = connections.iter_mut().find(|c| c.id == id) {
+                    connection.subscriptions.retain(|(c, p)| !(c == &channel && p == &pair));
+                    if action == "subscribe" {
+                        connection.subscriptions.push((channel, pair));
+                    }
+                }
+                drop(connections);
+                if action == "subscribe" {
+                    hub.count_subscribe();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Socket = WebSocket<tungstenite::stream::MaybeTlsStream<TcpStream>>;
+
+    /// Bounds every read so a missing message fails the test instead of hanging it
+    const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn connect(server: &MockWsServer) -> Socket {
+        let (mut socket, _) = tungstenite::connect(server.url()).unwrap();
+        if let tungstenite::stream::MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+        }
+        subscribe(&mut socket, "depth");
+        socket
+    }
+
+    fn subscribe(socket: &mut Socket, channel: &str) {
+        let subscribe = json!({"action": "subscribe", "subscribe": channel, "depth": "100", "pair": "eth_usdt"});
+        socket.send(Message::text(subscribe.to_string())).unwrap();
+    }
+
+    /// Next JSON text frame, `None` once the connection is gone or nothing arrives in time
+    fn next_text(socket: &mut Socket) -> Option<Value> {
+        loop {
+            match socket.read().ok()? {
+                Message::Text(text) => return serde_json::from_str(&text).ok(),
+                _ => continue,
+            }
+        }
+    }
+
+    #[test]
+    fn test_replay_filters_and_disconnects() {
+        let recording = Recording::from_jsonl(
+            r#"{"at_ms": 0, "message": {"type": "trade", "pair": "btc_usdt", "trade": {}}}
+               {"at_ms": 0, "message": {"type": "depth", "pair": "eth_usdt", "depth": {"asks": [], "bids": []}}}
+               {"at_ms": 10, "await_subscribe": true}
+               {"at_ms": 10, "disconnect": true}
+               {"at_ms": 20, "await_subscribe": true}
+               {"at_ms": 20, "message": {"type": "depth", "pair": "eth_usdt", "depth": {"asks": [["1", "1"]], "bids": []}}}"#,
+        )
+        .unwrap();
+        let server = MockWsServer::start(recording, ReplayOptions::default()).unwrap();
+
+        let mut socket = connect(&server);
+        let depth = next_text(&mut socket).expect("the depth push");
+        assert_eq!(depth["type"], "depth", "the trade push is filtered out");
+
+        socket.send(Message::text(r#"{"action":"ping","ping":"abc"}"#)).unwrap();
+        assert_eq!(next_text(&mut socket).expect("the pong")["pong"], "abc");
+        // Playback is paused until this subscription, then disconnects
+        subscribe(&mut socket, "trade");
+        assert!(next_text(&mut socket).is_none(), "scripted disconnect drops the connection");
+
+        let mut socket = connect(&server);
+        let depth = next_text(&mut socket).expect("the depth push after reconnecting");
+        assert_eq!(depth["depth"]["asks"][0][0], "1");
+        assert_eq!(server.connection_count(), 2);
+    }
+
+    #[test]
+    fn test_server_ping_requires_pong() {
+        let options = ReplayOptions {
+            ping_interval: Some(Duration::from_millis(30)),
+            ..ReplayOptions::default()
+        };
+        let server = MockWsServer::start(Recording::new(), options).unwrap();
+        let mut socket = connect(&server);
+
+        let ping = next_text(&mut socket).expect("the first ping");
+        assert_eq!(ping["action"], "ping");
+        socket
+            .send(Message::text(json!({"action": "pong", "pong": ping["ping"]}).to_string()))
+            .unwrap();
+        assert_eq!(next_text(&mut socket).expect("the second ping")["action"], "ping");
+
+        // Second ping left unanswered
+        assert!(next_text(&mut socket).is_none());
+    }
+}
@@ -1,4 +1,44 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use crate::errors::{Error, ErrorKind, LBankContentError, Result};
+
+/// Standard LBank response envelope (`result`, `error_code`, `msg`, `data`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiResponse<T> {
+    #[serde(deserialize_with = "bool_from_any")]
+    pub result: bool,
+    #[serde(default, deserialize_with = "i32_from_any")]
+    pub error_code: i32,
+    pub msg: Option<String>,
+    pub data: Option<T>,
+    pub ts: Option<i64>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Check the envelope, returning whatever `data` came with a successful response
+    pub fn into_data(self) -> Result<Option<T>> {
+        if self.result {
+            Ok(self.data)
+        } else {
+            Err(envelope_error(self.error_code, self.msg, "request failed"))
+        }
+    }
+
+    /// Unwrap the `data` payload, turning a failed envelope into an `LBankError`
+    pub fn into_result(self) -> Result<T> {
+        let (error_code, msg) = (self.error_code, self.msg.clone());
+        self.into_data()?
+            .ok_or_else(|| envelope_error(error_code, msg, "missing data"))
+    }
+}
+
+fn envelope_error(error_code: i32, msg: Option<String>, fallback: &str) -> Error {
+    ErrorKind::LBankError(LBankContentError {
+        code: i16::try_from(error_code).unwrap_or(i16::MAX),
+        msg: msg.unwrap_or_else(|| fallback.to_string()),
+    })
+    .into()
+}
 
 /// Empty response for test endpoints
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -14,12 +54,35 @@ pub struct AccountInformation {
     pub ts: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Per-asset amounts keyed by coin, either at the top level or nested under `info`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct AccountData {
-    pub info: Option<serde_json::Value>,  // Flexible to handle various response formats
-    pub freeze: Option<serde_json::Value>,
-    pub asset: Option<serde_json::Value>,
-    pub free: Option<serde_json::Value>,
+    #[serde(default)]
+    pub info: Option<Box<AccountData>>,
+    #[serde(default, deserialize_with = "amount_map")]
+    pub freeze: BTreeMap<String, f64>,
+    #[serde(default, deserialize_with = "amount_map")]
+    pub asset: BTreeMap<String, f64>,
+    #[serde(default, deserialize_with = "amount_map")]
+    pub free: BTreeMap<String, f64>,
+}
+
+impl AccountData {
+    /// One balance per coin with a free or frozen amount
+    pub fn balances(&self) -> Vec<AssetBalance> {
+        if let Some(info) = &self.info {
+            return info.balances();
+        }
+        let coins: BTreeSet<&String> = self.free.keys().chain(self.freeze.keys()).collect();
+        coins
+            .into_iter()
+            .map(|coin| AssetBalance {
+                asset: coin.clone(),
+                free: self.free.get(coin).copied().unwrap_or_default(),
+                frozen: self.freeze.get(coin).copied().unwrap_or_default(),
+            })
+            .collect()
+    }
 }
 
 /// Balance for a single asset
@@ -30,6 +93,61 @@ pub struct Balance {
     pub locked: String,
 }
 
+/// Holdings of one asset, as listed by `Spot::account_info`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AssetBalance {
+    pub asset: String,
+    #[serde(deserialize_with = "f64_from_any")]
+    pub free: f64,
+    /// Amount locked in open orders or pending withdrawals
+    #[serde(rename = "locked", deserialize_with = "f64_from_any")]
+    pub frozen: f64,
+}
+
+impl AssetBalance {
+    pub fn total(&self) -> f64 {
+        self.free + self.frozen
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0.0
+    }
+}
+
+/// Commissions, permissions and balances returned by `Spot::account_info`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub maker_commission: String,
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub taker_commission: String,
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub buyer_commission: String,
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub seller_commission: String,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_trade: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_withdraw: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_deposit: bool,
+    #[serde(default)]
+    pub balances: Vec<AssetBalance>,
+}
+
+impl AccountInfo {
+    /// Balance of `asset`, matched case-insensitively
+    pub fn balance(&self, asset: &str) -> Option<&AssetBalance> {
+        self.balances.iter().find(|b| b.asset.eq_ignore_ascii_case(asset))
+    }
+
+    /// Balances with a non-zero total
+    pub fn holdings(&self) -> impl Iterator<Item = &AssetBalance> {
+        self.balances.iter().filter(|b| !b.is_empty())
+    }
+}
+
 /// Order information
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -67,6 +185,786 @@ pub struct TransactionData {
     pub order_type: String,
 }
 
+/// Order acknowledgement returned by `create_order`
+///
+/// Also deserializes from `order_info` data, so an order recovered by its
+/// `custom_id` has the same shape as a freshly placed one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlacedOrder {
+    #[serde(alias = "orderId")]
+    pub order_id: String,
+    pub symbol: String,
+    #[serde(default, alias = "clientOrderId", alias = "origClientOrderId")]
+    pub custom_id: Option<String>,
+}
+
+/// Latest price of a trading pair, as returned by `Market::price`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SymbolPrice {
+    pub symbol: String,
+    #[serde(deserialize_with = "string_from_any")]
+    pub price: String,
+}
+
+/// One K-line bar, sent by `Market::kline` as `[time, open, high, low, close, volume]`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "Vec<serde_json::Value>", into = "(u64, f64, f64, f64, f64, f64)")]
+pub struct Candle {
+    /// Bar open time in seconds
+    pub time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl TryFrom<Vec<serde_json::Value>> for Candle {
+    type Error = Error;
+
+    fn try_from(row: Vec<serde_json::Value>) -> Result<Self> {
+        let value = |index: usize, name: &'static str| -> Result<f64> {
+            let number = match row.get(index) {
+                Some(serde_json::Value::Number(n)) => n.as_f64(),
+                Some(serde_json::Value::String(s)) => s.parse().ok(),
+                _ => None,
+            };
+            number.ok_or_else(|| ErrorKind::KlineValueMissingError(index, name).into())
+        };
+
+        Ok(Candle {
+            time: value(0, "time")? as u64,
+            open: value(1, "open")?,
+            high: value(2, "high")?,
+            low: value(3, "low")?,
+            close: value(4, "close")?,
+            volume: value(5, "volume")?,
+        })
+    }
+}
+
+impl From<Candle> for (u64, f64, f64, f64, f64, f64) {
+    fn from(c: Candle) -> Self {
+        (c.time, c.open, c.high, c.low, c.close, c.volume)
+    }
+}
+
+/// LBank order status codes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(try_from = "serde_json::Value", into = "i8")]
+pub enum OrderStatus {
+    /// -1: Cancelled
+    Cancelled,
+    /// 0: Unfilled
+    New,
+    /// 1: Partially filled
+    PartiallyFilled,
+    /// 2: Completely filled
+    Filled,
+    /// 3: Partially filled, then cancelled
+    PartiallyFilledCancelled,
+    /// 4: Cancellation is being processed
+    Cancelling,
+}
+
+impl OrderStatus {
+    /// Whether the order can no longer change
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Cancelled | OrderStatus::Filled | OrderStatus::PartiallyFilledCancelled
+        )
+    }
+}
+
+impl TryFrom<i64> for OrderStatus {
+    type Error = String;
+
+    fn try_from(code: i64) -> std::result::Result<Self, Self::Error> {
+        match code {
+            -1 => Ok(OrderStatus::Cancelled),
+            0 => Ok(OrderStatus::New),
+            1 => Ok(OrderStatus::PartiallyFilled),
+            2 => Ok(OrderStatus::Filled),
+            3 => Ok(OrderStatus::PartiallyFilledCancelled),
+            4 => Ok(OrderStatus::Cancelling),
+            other => Err(format!("unknown order status: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for OrderStatus {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> std::result::Result<Self, Self::Error> {
+        let code = match &value {
+            serde_json::Value::Number(n) => n.as_i64(),
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        code.ok_or_else(|| format!("invalid order status: {}", value))
+            .and_then(OrderStatus::try_from)
+    }
+}
+
+impl From<OrderStatus> for i8 {
+    fn from(status: OrderStatus) -> i8 {
+        match status {
+            OrderStatus::Cancelled => -1,
+            OrderStatus::New => 0,
+            OrderStatus::PartiallyFilled => 1,
+            OrderStatus::Filled => 2,
+            OrderStatus::PartiallyFilledCancelled => 3,
+            OrderStatus::Cancelling => 4,
+        }
+    }
+}
+
+/// Order details returned by `order_info`, `open_orders` and `order_history`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInfo {
+    pub symbol: String,
+    pub order_id: String,
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    pub price: String,
+    pub orig_qty: String,
+    pub executed_qty: String,
+    #[serde(default)]
+    pub cummulative_quote_qty: Option<String>,
+    pub status: OrderStatus,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    #[serde(default)]
+    pub time: i64,
+    #[serde(default)]
+    pub update_time: i64,
+    #[serde(default)]
+    pub orig_quote_order_qty: Option<String>,
+}
+
+/// A page of orders, sent either as a bare array or wrapped in `{"orders": [...]}`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum OrderList {
+    Orders(Vec<OrderInfo>),
+    Page {
+        #[serde(default)]
+        orders: Vec<OrderInfo>,
+    },
+}
+
+impl OrderList {
+    pub fn into_orders(self) -> Vec<OrderInfo> {
+        match self {
+            OrderList::Orders(orders) | OrderList::Page { orders } => orders,
+        }
+    }
+}
+
+/// Deposit or withdrawal records, sent either as a bare array or wrapped in an object
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum RecordList<T> {
+    Records(Vec<T>),
+    Page {
+        #[serde(default = "Vec::new", alias = "depositOrders", alias = "withdraws")]
+        records: Vec<T>,
+    },
+}
+
+impl<T> RecordList<T> {
+    pub fn into_records(self) -> Vec<T> {
+        match self {
+            RecordList::Records(records) | RecordList::Page { records } => records,
+        }
+    }
+}
+
+/// Order update pushed on the `orderUpdate` WebSocket subscription
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderUpdate {
+    /// Order ID
+    pub uuid: String,
+    /// Client order ID (`custom_id`), when one was set
+    #[serde(default, rename = "customerID")]
+    pub customer_id: Option<String>,
+    pub symbol: String,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    #[serde(default)]
+    pub tx_uuid: Option<String>,
+    #[serde(default)]
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub volume_price: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub price: Option<String>,
+    #[serde(default)]
+    pub order_price: Option<String>,
+    #[serde(default)]
+    pub order_amt: Option<String>,
+    #[serde(default)]
+    pub avg_price: Option<String>,
+    #[serde(default)]
+    pub acc_amt: Option<String>,
+    #[serde(default)]
+    pub remain_amt: Option<String>,
+    pub order_status: OrderStatus,
+    pub update_time: i64,
+}
+
+/// Envelope of an `orderUpdate` WebSocket push
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderUpdatePush {
+    #[serde(rename = "orderUpdate")]
+    pub order_update: OrderUpdate,
+    pub pair: Option<String>,
+    #[serde(rename = "type")]
+    pub push_type: String,
+}
+
+/// Balance change pushed on the `assetUpdate` WebSocket subscription
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetUpdate {
+    pub asset_code: String,
+    /// Total balance (`free` + `freeze`)
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub asset: String,
+    #[serde(deserialize_with = "string_from_any")]
+    pub free: String,
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub freeze: String,
+    pub time: i64,
+    /// What changed the balance, e.g. "ORDER_CREATE", "ORDER_TRADE", "DEPOSIT" or "WITHDRAW"
+    #[serde(rename = "type")]
+    pub change_type: String,
+}
+
+impl AssetUpdate {
+    pub fn is_deposit(&self) -> bool {
+        self.change_type.eq_ignore_ascii_case("DEPOSIT")
+    }
+
+    pub fn is_withdrawal(&self) -> bool {
+        self.change_type.eq_ignore_ascii_case("WITHDRAW")
+    }
+
+    pub fn change(&self) -> BalanceChange {
+        match self.change_type.to_ascii_uppercase().as_str() {
+            "ORDER_CREATE" => BalanceChange::OrderCreate,
+            "ORDER_DEAL" | "ORDER_TRADE" => BalanceChange::OrderDeal,
+            "ORDER_CANCEL" => BalanceChange::OrderCancel,
+            "DEPOSIT" => BalanceChange::Deposit,
+            "WITHDRAW" => BalanceChange::Withdraw,
+            _ => BalanceChange::Other(self.change_type.clone()),
+        }
+    }
+}
+
+/// Cause of an `assetUpdate` push
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceChange {
+    /// An order was placed, freezing funds
+    OrderCreate,
+    /// An order filled, spending frozen funds
+    OrderDeal,
+    /// An order was cancelled, releasing frozen funds
+    OrderCancel,
+    Deposit,
+    Withdraw,
+    Other(String),
+}
+
+/// Envelope of an `assetUpdate` WebSocket push
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssetUpdatePush {
+    pub data: AssetUpdate,
+    #[serde(rename = "type")]
+    pub push_type: String,
+}
+
+/// Maker and taker commission on one pair, as returned by `Account::trade_fee_rate`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeFee {
+    pub symbol: String,
+    /// Fraction of the traded amount, e.g. `0.001` for 0.1%
+    ///
+    /// The API reports commissions in percent (`"0.10"`); they are converted
+    /// when deserializing and converted back when serializing.
+    #[serde(deserialize_with = "fraction_from_percent", serialize_with = "percent_from_fraction")]
+    pub maker_commission: f64,
+    #[serde(deserialize_with = "fraction_from_percent", serialize_with = "percent_from_fraction")]
+    pub taker_commission: f64,
+}
+
+impl TradeFee {
+    pub fn rate(&self, maker: bool) -> f64 {
+        if maker {
+            self.maker_commission
+        } else {
+            self.taker_commission
+        }
+    }
+}
+
+/// API keys not bound to an IP address expire this long after creation
+pub const UNBOUND_KEY_LIFETIME_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Something an API key may be allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Permission {
+    Reading,
+    Trading,
+    Withdrawals,
+    InternalTransfer,
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Permission::Reading => "reading",
+            Permission::Trading => "spot trading",
+            Permission::Withdrawals => "withdrawals",
+            Permission::InternalTransfer => "internal transfer",
+        })
+    }
+}
+
+/// What an API key may do, as returned by `Account::api_restrictions`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiPermissions {
+    /// Whether the key is bound to IP addresses
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub ip_restrict: bool,
+    /// Key creation time in milliseconds
+    #[serde(default)]
+    pub create_time: i64,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub enable_withdrawals: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub enable_internal_transfer: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub enable_reading: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub enable_spot_trading: bool,
+}
+
+impl ApiPermissions {
+    pub fn has(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::Reading => self.enable_reading,
+            Permission::Trading => self.enable_spot_trading,
+            Permission::Withdrawals => self.enable_withdrawals,
+            Permission::InternalTransfer => self.enable_internal_transfer,
+        }
+    }
+
+    /// Those of `required` the key lacks
+    pub fn missing(&self, required: &[Permission]) -> Vec<Permission> {
+        required.iter().copied().filter(|&p| !self.has(p)).collect()
+    }
+
+    /// When the key stops working (milliseconds), `None` for IP-bound keys
+    /// and keys without a creation time
+    pub fn expires_at(&self) -> Option<i64> {
+        (!self.ip_restrict && self.create_time > 0).then(|| self.create_time + UNBOUND_KEY_LIFETIME_MS)
+    }
+}
+
+/// A fill returned by `transaction_history`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionRecord {
+    pub symbol: String,
+    /// Trade ID
+    pub id: String,
+    pub order_id: String,
+    #[serde(deserialize_with = "string_from_any")]
+    pub price: String,
+    #[serde(deserialize_with = "string_from_any")]
+    pub qty: String,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub quote_qty: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub commission: Option<String>,
+    pub time: i64,
+    pub is_buyer: bool,
+    pub is_maker: bool,
+}
+
+/// Enum for one of LBank's numeric codes, accepted as a number or a string
+/// and serialized as a number
+macro_rules! code_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$vmeta:meta])* $variant:ident = $code:literal,)+ }) => {
+        $(#[$meta])*
+        #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+        #[serde(try_from = "serde_json::Value", into = "i8")]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)+
+        }
+
+        impl TryFrom<i64> for $name {
+            type Error = String;
+
+            fn try_from(code: i64) -> std::result::Result<Self, Self::Error> {
+                match code {
+                    $($code => Ok($name::$variant),)+
+                    other => Err(format!("unknown {}: {}", stringify!($name), other)),
+                }
+            }
+        }
+
+        impl TryFrom<serde_json::Value> for $name {
+            type Error = String;
+
+            fn try_from(value: serde_json::Value) -> std::result::Result<Self, Self::Error> {
+                let code = match &value {
+                    serde_json::Value::Number(n) => n.as_i64(),
+                    serde_json::Value::String(s) => s.trim().parse().ok(),
+                    _ => None,
+                };
+                code.ok_or_else(|| format!("invalid {}: {}", stringify!($name), value))
+                    .and_then($name::try_from)
+            }
+        }
+
+        impl From<$name> for i8 {
+            fn from(value: $name) -> i8 {
+                match value {
+                    $($name::$variant => $code,)+
+                }
+            }
+        }
+    };
+}
+
+code_enum! {
+    /// Deposit status codes
+    DepositStatus {
+        /// 1: Applying
+        Applying = 1,
+        /// 2: Credited
+        Success = 2,
+        /// 3: Failed
+        Failed = 3,
+        /// 4: Cancelled
+        Cancelled = 4,
+        /// 5: Intra-site transfer
+        Transfer = 5,
+    }
+}
+
+impl DepositStatus {
+    /// Whether the deposit can no longer change
+    pub fn is_final(&self) -> bool {
+        !matches!(self, DepositStatus::Applying)
+    }
+}
+
+code_enum! {
+    /// Withdrawal status codes
+    WithdrawStatus {
+        /// 1: Applying
+        Applying = 1,
+        /// 2: Cancelled
+        Cancelled = 2,
+        /// 3: Failed
+        Failed = 3,
+        /// 4: Completed
+        Completed = 4,
+    }
+}
+
+impl WithdrawStatus {
+    /// Whether the withdrawal can no longer change
+    pub fn is_final(&self) -> bool {
+        !matches!(self, WithdrawStatus::Applying)
+    }
+}
+
+code_enum! {
+    /// How a network charges withdrawal fees (`type` in `networkList`)
+    FeeType {
+        /// 1: `withdrawFee` per withdrawal
+        Fixed = 1,
+        /// 2: `withdrawFeeRate` percent of the amount
+        Percent = 2,
+        /// 3: `withdrawFee` plus `withdrawFeeRate` percent of the amount
+        FixedPlusPercent = 3,
+    }
+}
+
+/// A deposit returned by `Wallet::deposit_history`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositRecord {
+    /// Record ID, when the API sends one
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub id: Option<String>,
+    pub insert_time: i64,
+    #[serde(deserialize_with = "string_from_any")]
+    pub amount: String,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub network_name: Option<String>,
+    #[serde(default)]
+    pub tx_id: Option<String>,
+    pub coin: String,
+    pub status: DepositStatus,
+}
+
+/// A withdrawal returned by `Wallet::withdraw_history`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawRecord {
+    /// Withdrawal ID (`withdrawId` in the withdraw response)
+    pub id: i64,
+    #[serde(deserialize_with = "string_from_any")]
+    pub amount: String,
+    /// The API sends this field as `coid`
+    #[serde(alias = "coid")]
+    pub coin: String,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub withdraw_order_id: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub fee: Option<String>,
+    #[serde(default)]
+    pub network_name: Option<String>,
+    #[serde(default)]
+    pub transfer_type: Option<String>,
+    #[serde(default)]
+    pub tx_id: Option<String>,
+    #[serde(default)]
+    pub fee_asset_code: Option<String>,
+    pub apply_time: i64,
+    pub status: WithdrawStatus,
+}
+
+/// A coin's balance and chains as returned by `Wallet::user_info`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinInfo {
+    pub coin: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Free amount
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub usable_amt: String,
+    /// Free plus frozen amount
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub asset_amt: String,
+    /// Frozen amount
+    #[serde(default, deserialize_with = "string_from_any")]
+    pub freeze_amt: String,
+    #[serde(default)]
+    pub network_list: Vec<NetworkInfo>,
+}
+
+impl CoinInfo {
+    /// The chain named `network`, or the default chain when `None`
+    pub fn network(&self, network: Option<&str>) -> Option<&NetworkInfo> {
+        match network {
+            Some(name) => self.network_list.iter().find(|n| n.name == name || n.network == name),
+            None => self.network_list.iter().find(|n| n.is_default),
+        }
+    }
+
+    /// Free and frozen amounts, working out the frozen one from `asset_amt`
+    /// when `freeze_amt` is left out
+    pub fn balance(&self) -> Result<AssetBalance> {
+        let free = parse_amount(&self.usable_amt)?;
+        let frozen = match (self.freeze_amt.trim(), self.asset_amt.trim()) {
+            ("", "") => 0.0,
+            ("", total) => parse_amount(total)? - free,
+            (frozen, _) => parse_amount(frozen)?,
+        };
+        Ok(AssetBalance {
+            asset: self.coin.clone(),
+            free,
+            frozen,
+        })
+    }
+}
+
+/// One chain a coin can be deposited or withdrawn on
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkInfo {
+    /// Chain name passed as `networkName`
+    pub name: String,
+    #[serde(default)]
+    pub network: String,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub is_default: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub withdraw_enable: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub deposit_enable: bool,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub withdraw_fee: Option<String>,
+    /// Percentage of the amount charged on `Percent` and `FixedPlusPercent`
+    /// chains, e.g. `"10"` for 10%; empty on fixed-fee chains
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub withdraw_fee_rate: Option<String>,
+    #[serde(default, rename = "type")]
+    pub fee_type: Option<FeeType>,
+    /// Asset the fee is charged in, when not the withdrawn coin
+    #[serde(default)]
+    pub fee_asset_code: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub withdraw_min: Option<String>,
+    #[serde(default, alias = "depositMin", deserialize_with = "option_string_from_any")]
+    pub min_deposit: Option<String>,
+    /// Minimum amount of an intra-site transfer
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub min_limit: Option<String>,
+}
+
+impl NetworkInfo {
+    /// Fee charged for withdrawing `amount` on this chain, per its fee `type`
+    ///
+    /// Chains without a `type` are treated as fixed-fee. `withdrawFeeRate` is
+    /// only read on chains that charge a percentage, where it counts as a
+    /// percent.
+    pub fn fee_for(&self, amount: f64) -> Result<f64> {
+        let fixed = || parse_amount(self.withdraw_fee.as_deref().unwrap_or(""));
+        let percent = || -> Result<f64> {
+            Ok(amount * parse_amount(self.withdraw_fee_rate.as_deref().unwrap_or(""))? / 100.0)
+        };
+        Ok(match self.fee_type {
+            Some(FeeType::Percent) => percent()?,
+            Some(FeeType::FixedPlusPercent) => fixed()? + percent()?,
+            Some(FeeType::Fixed) | None => fixed()?,
+        })
+    }
+}
+
+/// A coin's chain as returned by `Common::asset_configs`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetConfig {
+    pub asset_code: String,
+    #[serde(default)]
+    pub chain_name: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_deposit: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_draw: bool,
+    /// Whether internal transfers are allowed
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub can_station_draw: bool,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub has_memo: bool,
+    #[serde(default)]
+    pub asset_fee: Option<AssetFee>,
+}
+
+/// Fees and limits of one chain in `AssetConfig`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetFee {
+    #[serde(default, rename = "type")]
+    pub fee_type: Option<FeeType>,
+    /// Asset the withdrawal fee is charged in
+    #[serde(default)]
+    pub fee_code: Option<String>,
+    /// Decimal places allowed in withdrawal amounts
+    #[serde(default)]
+    pub scale: Option<u32>,
+    /// Minimum withdrawal
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub min_amt: Option<String>,
+    /// Fixed withdrawal fee
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub fee_amt: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub fee_rate: Option<String>,
+    /// Internal transfer fee
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub station_fee_amt: Option<String>,
+    #[serde(default)]
+    pub station_scale: Option<u32>,
+    /// Minimum internal transfer
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub station_min_amt: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub min_deposit_amt: Option<String>,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub deposit_fee: Option<String>,
+}
+
+/// Response of `Wallet::system_status`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SystemStatus {
+    /// "0" under maintenance, "1" normal
+    #[serde(deserialize_with = "string_from_any")]
+    pub status: String,
+}
+
+impl SystemStatus {
+    pub fn is_maintenance(&self) -> bool {
+        self.status == "0"
+    }
+}
+
+/// Response of `Wallet::withdraw`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawResult {
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub fee: Option<String>,
+    /// ID of the withdrawal in `withdraw_history`
+    pub withdraw_id: i64,
+}
+
+/// Response of `Wallet::transfer_internal`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalTransfer {
+    /// ID of the transfer in `withdraw_history`
+    pub withdraw_id: i64,
+}
+
+/// Response of `Wallet::deposit_address`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositAddress {
+    pub address: String,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub coin: Option<String>,
+    #[serde(default)]
+    pub network_name: Option<String>,
+}
+
+/// A coin's entry in `Wallet::asset_detail`, keyed by coin
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetDetail {
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub min_withdraw_amount: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub deposit_status: bool,
+    #[serde(default, deserialize_with = "option_string_from_any")]
+    pub withdraw_fee: Option<String>,
+    #[serde(default, deserialize_with = "bool_from_any")]
+    pub withdraw_status: bool,
+    #[serde(default)]
+    pub deposit_tip: Option<String>,
+}
+
 /// Order cancellation response
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OrderCanceled {
@@ -98,3 +996,256 @@ pub struct TradeHistory {
     pub is_maker: bool,
 }
 
+
+/// Accept `true`, `"true"` or `1` for LBank's boolean fields
+fn bool_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<bool, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(b) => b,
+        serde_json::Value::String(s) => s.eq_ignore_ascii_case("true"),
+        serde_json::Value::Number(n) => n.as_i64() == Some(1),
+        _ => false,
+    })
+}
+
+/// Accept decimal values sent either as JSON numbers or as strings
+fn string_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!("expected a number, got {}", other))),
+    }
+}
+
+/// Parse decimal values sent either as JSON numbers or as strings
+fn f64_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n.as_f64().ok_or_else(|| serde::de::Error::custom("number out of range")),
+        serde_json::Value::String(s) if s.trim().is_empty() => Ok(0.0),
+        serde_json::Value::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+        other => Err(serde::de::Error::custom(format!("expected a number, got {}", other))),
+    }
+}
+
+fn fraction_from_percent<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    Ok(f64_from_any(deserializer)? / 100.0)
+}
+
+fn percent_from_fraction<S: serde::Serializer>(fraction: &f64, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&(fraction * 100.0).to_string())
+}
+
+/// Parse a decimal string field, treating an empty one as zero
+fn parse_amount(amount: &str) -> Result<f64> {
+    match amount.trim() {
+        "" => Ok(0.0),
+        amount => Ok(amount.parse()?),
+    }
+}
+
+/// Coin-to-amount maps such as `{"btc": "0.5", "usdt": 10}`
+fn amount_map<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<BTreeMap<String, f64>, D::Error> {
+    #[derive(Deserialize)]
+    struct Amount(#[serde(deserialize_with = "f64_from_any")] f64);
+
+    let map: Option<BTreeMap<String, Amount>> = Option::deserialize(deserializer)?;
+    Ok(map.unwrap_or_default().into_iter().map(|(coin, Amount(amount))| (coin, amount)).collect())
+}
+
+/// Optional variant of `string_from_any`, mapping `null` to `None`
+fn option_string_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<String>, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) => Ok(Some(s)),
+        serde_json::Value::Number(n) => Ok(Some(n.to_string())),
+        other => Err(serde::de::Error::custom(format!("expected a number, got {}", other))),
+    }
+}
+
+/// Accept error codes sent either as numbers or as strings like `"00000"`
+fn i32_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<i32, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => Ok(n.as_i64().unwrap_or_default() as i32),
+        serde_json::Value::String(s) => s.trim().parse().map_err(serde::de::Error::custom),
+        _ => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_response_success() {
+        let text = r#"{"result":"true","data":{"order_id":"abc","symbol":"lbk_usdt","custom_id":"cid"},"error_code":0,"ts":1}"#;
+        let response: ApiResponse<PlacedOrder> = serde_json::from_str(text).unwrap();
+        let order = response.into_result().unwrap();
+
+        assert_eq!(order.order_id, "abc");
+        assert_eq!(order.custom_id.as_deref(), Some("cid"));
+    }
+
+    #[test]
+    fn test_api_response_error() {
+        let text = r#"{"result":false,"error_code":"10036","msg":"customID duplicated","ts":1}"#;
+        let response: ApiResponse<PlacedOrder> = serde_json::from_str(text).unwrap();
+        let err = response.into_result().unwrap_err();
+
+        assert_eq!(err.lbank_code(), Some(crate::errors::CUSTOM_ID_DUPLICATED));
+    }
+
+    #[test]
+    fn test_order_status_codes() {
+        let status: OrderStatus = serde_json::from_str("\"-1\"").unwrap();
+        assert_eq!(status, OrderStatus::Cancelled);
+
+        let status: OrderStatus = serde_json::from_str("1").unwrap();
+        assert_eq!(status, OrderStatus::PartiallyFilled);
+        assert!(!status.is_final());
+        assert!(serde_json::from_str::<OrderStatus>("7").is_err());
+    }
+
+    #[test]
+    fn test_order_list_shapes() {
+        let order = r#"{"symbol":"lbk_usdt","orderId":"a","price":"0.1","origQty":"1","executedQty":"0","status":"0","type":"buy"}"#;
+        let bare: OrderList = serde_json::from_str(&format!("[{}]", order)).unwrap();
+        let paged: OrderList = serde_json::from_str(&format!(r#"{{"total":1,"orders":[{}]}}"#, order)).unwrap();
+
+        assert_eq!(bare.into_orders()[0].order_id, "a");
+        assert_eq!(paged.into_orders()[0].status, OrderStatus::New);
+    }
+
+    #[test]
+    fn test_candle_from_row() {
+        let candles: Vec<Candle> = serde_json::from_str("[[1482311500,5423.23,5472.80,5516.09,5462,234.3250]]").unwrap();
+        assert_eq!(candles[0].time, 1482311500);
+        assert_eq!(candles[0].volume, 234.325);
+
+        let err = serde_json::from_str::<Candle>("[1482311500,5423.23,5472.80]").unwrap_err();
+        assert!(err.to_string().contains("low at 3 is missing"));
+    }
+
+    #[test]
+    fn test_history_records() {
+        let withdraw = r#"{"amount":234,"coid":"lbk","address":"34252342","withdrawOrderId":"w1","fee":2,"networkName":"lbk30","transferType":"Digital Asset Withdrawal","txId":"","feeAssetCode":"","id":93182,"applyTime":1644476930000,"status":"1"}"#;
+        let record: WithdrawRecord = serde_json::from_str(withdraw).unwrap();
+        assert_eq!(record.coin, "lbk");
+        assert_eq!(record.amount, "234");
+        assert_eq!(record.fee.as_deref(), Some("2"));
+
+        let trade = r#"{"symbol":"lbk_usdt","id":"t1","orderId":"o1","price":"4.00000100","qty":"12.00000000","quoteQty":"48.000012","commission":"10.10000000","time":1499865549590,"isBuyer":true,"isMaker":false}"#;
+        let record: TransactionRecord = serde_json::from_str(trade).unwrap();
+        assert_eq!(record.order_id, "o1");
+        assert!(record.is_buyer);
+
+        let page: RecordList<WithdrawRecord> = serde_json::from_str(&format!(r#"{{"totalPages":1,"withdraws":[{}]}}"#, withdraw)).unwrap();
+        let records = page.into_records();
+        assert_eq!(records[0].id, 93182);
+        assert_eq!(records[0].status, WithdrawStatus::Applying);
+    }
+
+    #[test]
+    fn test_user_info_example() {
+        // `user_info` return example from the API docs
+        let coins = r#"[{
+            "usableAmt": "110.00869",
+            "assetAmt": "110.02869",
+            "networkList": [
+              {"isDefault": true, "withdrawFeeRate": "10", "name": "btc", "withdrawMin": 0.01, "minLimit": 0.0001,
+               "minDeposit": 0.001, "feeAssetCode": "usdt", "withdrawFee": "1", "type": 3, "coin": "btc", "network": "btc"},
+              {"isDefault": false, "withdrawFeeRate": "", "name": "btctron", "withdrawMin": 0.0001, "minLimit": 0.02,
+               "minDeposit": 0.0124, "feeAssetCode": "btc", "withdrawFee": "0.01", "type": 1, "coin": "btc", "network": "trx"}
+            ],
+            "freezeAmt": "0.02",
+            "coin": "btc"
+        }]"#;
+        let coins: Vec<CoinInfo> = serde_json::from_str(coins).unwrap();
+        let balance = coins[0].balance().unwrap();
+        assert_eq!((balance.asset.as_str(), balance.free, balance.frozen), ("btc", 110.00869, 0.02));
+        assert!((balance.total() - 110.02869).abs() < 1e-9);
+        let btc = coins[0].network(None).unwrap();
+        assert_eq!(btc.fee_type, Some(FeeType::FixedPlusPercent));
+        assert_eq!(btc.fee_for(2.0).unwrap(), 1.2);
+        let tron = coins[0].network(Some("btctron")).unwrap();
+        assert_eq!(tron.withdraw_fee_rate.as_deref(), Some(""));
+        assert_eq!(tron.fee_for(2.0).unwrap(), 0.01);
+    }
+
+    #[test]
+    fn test_api_restrictions_example() {
+        // `api_Restrictions` return example from the API docs, comments and all
+        let example = r#"{
+          "enableSpotTrading": true, //trading
+          "createTime": 1643091005000,
+          "enableReading": true, //read only
+          "ipRestrict": true, //whether to restrict ip
+          "enableWithdrawals": true //Withdrawal
+        }"#;
+        let json: String = example.lines().map(|line| line.split("//").next().unwrap()).collect();
+        let permissions: ApiPermissions = serde_json::from_str(&json).unwrap();
+        assert!(permissions.has(Permission::Trading));
+        assert!(permissions.has(Permission::Reading) && permissions.has(Permission::Withdrawals));
+        assert!(!permissions.has(Permission::InternalTransfer));
+        assert_eq!((permissions.create_time, permissions.expires_at()), (1643091005000, None));
+    }
+
+    #[test]
+    fn test_trade_fee_example() {
+        // `customer_trade_fee` return example from the API docs, in percent
+        let fees: Vec<TradeFee> =
+            serde_json::from_str(r#"[{"symbol": "lbk_usdt", "makerCommission": "0.10", "takerCommission": "10.00"}]"#).unwrap();
+        assert_eq!((fees[0].rate(true), fees[0].rate(false)), (0.001, 0.1));
+
+        let round_trip: TradeFee = serde_json::from_value(serde_json::to_value(&fees[0]).unwrap()).unwrap();
+        assert_eq!(round_trip, fees[0]);
+    }
+
+    #[test]
+    fn test_placed_order_from_order_info() {
+        let text = r#"{"symbol":"lbk_usdt","orderId":"abc","clientOrderId":"cid","price":"0.1","status":"0"}"#;
+        let order: PlacedOrder = serde_json::from_str(text).unwrap();
+
+        assert_eq!(order.order_id, "abc");
+        assert_eq!(order.custom_id.as_deref(), Some("cid"));
+    }
+
+    #[test]
+    fn test_wallet_models() {
+        let coins = r#"[{"coin":"usdt","usableAmt":"10","assetAmt":"12","networkList":[
+            {"name":"trc20","network":"TRX","isDefault":true,"withdrawEnable":true,"depositEnable":true,"withdrawFee":"1","withdrawMin":"10","minDeposit":"1","type":1},
+            {"name":"erc20","network":"ETH","isDefault":false,"withdrawEnable":"false","withdrawFee":2,"withdrawFeeRate":"0.1","type":"3","feeAssetCode":"eth"}
+        ]}]"#;
+        let coins: Vec<CoinInfo> = serde_json::from_str(coins).unwrap();
+        let default = coins[0].network(None).unwrap();
+        assert_eq!(default.name, "trc20");
+        assert_eq!(default.fee_type, Some(FeeType::Fixed));
+        assert_eq!(default.min_deposit.as_deref(), Some("1"));
+        // No freezeAmt, so the rest of assetAmt is frozen
+        assert_eq!(coins[0].balance().unwrap().frozen, 2.0);
+        let erc20 = coins[0].network(Some("ETH")).unwrap();
+        assert!(!erc20.withdraw_enable);
+        assert_eq!(erc20.fee_type, Some(FeeType::FixedPlusPercent));
+        assert_eq!(erc20.withdraw_fee.as_deref(), Some("2"));
+
+        let deposit: DepositRecord = serde_json::from_str(r#"{"insertTime":1,"amount":"5","coin":"usdt","status":"5"}"#).unwrap();
+        assert_eq!(deposit.status, DepositStatus::Transfer);
+        assert!(serde_json::from_str::<DepositStatus>("6").is_err());
+
+        let result: WithdrawResult = serde_json::from_str(r#"{"fee":1.5,"withdrawId":42}"#).unwrap();
+        assert_eq!((result.fee.as_deref(), result.withdraw_id), (Some("1.5"), 42));
+    }
+
+    #[test]
+    fn test_account_models() {
+        let text = r#"{"makerCommission":"0.001","canTrade":true,"canWithdraw":"false","balances":[
+            {"asset":"usdt","free":"10.5","locked":"2"},{"asset":"lbk","free":0,"locked":"0"}]}"#;
+        let info: AccountInfo = serde_json::from_str(text).unwrap();
+        assert!(info.can_trade && !info.can_withdraw);
+        assert_eq!(info.balance("USDT").unwrap().total(), 12.5);
+        assert_eq!(info.holdings().count(), 1);
+
+        let data: AccountData = serde_json::from_str(r#"{"info":{"free":{"btc":"0.5","eth":1},"freeze":{"btc":"0.25"}}}"#).unwrap();
+        let balances = data.balances();
+        assert_eq!(balances.len(), 2);
+        assert_eq!((balances[0].asset.as_str(), balances[0].total()), ("btc", 0.75));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::errors::{Result, REQUEST_TOO_FREQUENT};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Largest page size LBank accepts for page-numbered endpoints (error 10018 above it)
+pub const MAX_PAGE_LENGTH: u32 = 50;
+/// Minimum spacing between page requests while walking a history
+pub const REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+/// Times a page is retried after "request too frequent" (10004) before giving up
+const RATE_LIMIT_RETRIES: u32 = 5;
+/// First back-off after 10004, doubled on every retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_millis(500);
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+/// `transaction_history` accepts at most two days per query
+pub(crate) const TRANSACTION_WINDOW_MS: u64 = 2 * DAY_MS - 1000;
+/// Largest `limit` accepted by `transaction_history`
+pub(crate) const TRANSACTION_PAGE_LIMIT: u32 = 100;
+/// Window used to walk deposit and withdrawal history
+pub(crate) const WALLET_WINDOW_MS: u64 = 30 * DAY_MS;
+
+/// Position of the next page request
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageCursor {
+    /// Page-numbered endpoints (`current_page` / `page_length`)
+    Page { number: u32, length: u32 },
+    /// Time-ranged endpoints, in milliseconds; `from_id` continues a window
+    /// that returned a full page
+    Window { start: u64, end: u64, from_id: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Walk {
+    Pages,
+    /// Split `..end` into windows of `span`; `limit` is the page size for
+    /// endpoints that can continue a window from the last ID
+    Windows { end: u64, span: u64, limit: Option<usize> },
+}
+
+/// Walks a history endpoint until it is exhausted, dropping items already seen
+pub(crate) struct Paginator<T> {
+    walk: Walk,
+    cursor: Option<PageCursor>,
+    key: fn(&T) -> String,
+    seen: HashSet<String>,
+    buffer: VecDeque<T>,
+    throttle: Throttle,
+}
+
+impl<T> Paginator<T> {
+    /// Walk pages `1..` of `page_length` items (clamped to 1..=50)
+    pub(crate) fn pages(page_length: u32, key: fn(&T) -> String) -> Self {
+        let length = page_length.clamp(1, MAX_PAGE_LENGTH);
+        Self::new(Walk::Pages, PageCursor::Page { number: 1, length }, key)
+    }
+
+    /// Walk `start..end` in windows of `span` milliseconds
+    pub(crate) fn windows(start: u64, end: u64, span: u64, limit: Option<usize>, key: fn(&T) -> String) -> Self {
+        let span = span.max(1);
+        let cursor = PageCursor::Window {
+            start,
+            end: end.min(start.saturating_add(span)),
+            from_id: None,
+        };
+        let mut paginator = Self::new(Walk::Windows { end, span, limit }, cursor, key);
+        if start >= end {
+            paginator.cursor = None;
+        }
+        paginator
+    }
+
+    fn new(walk: Walk, cursor: PageCursor, key: fn(&T) -> String) -> Self {
+        Paginator {
+            walk,
+            cursor: Some(cursor),
+            key,
+            seen: HashSet::new(),
+            buffer: VecDeque::new(),
+            throttle: Throttle::default(),
+        }
+    }
+
+    /// Buffer the unseen items of a page and move the cursor past it
+    fn advance(&mut self, items: Vec<T>) {
+        let received = items.len();
+        let mut fresh = 0;
+        let mut last_key = None;
+        for item in items {
+            let key = (self.key)(&item);
+            if self.seen.insert(key.clone()) {
+                fresh += 1;
+                self.buffer.push_back(item);
+            }
+            last_key = Some(key);
+        }
+
+        self.cursor = match (self.cursor.take(), self.walk) {
+            // A short page is the last one; a page of repeats means the endpoint ignores paging
+            (Some(PageCursor::Page { number, length }), Walk::Pages) if received >= length as usize && fresh > 0 => {
+                Some(PageCursor::Page { number: number + 1, length })
+            }
+            (Some(PageCursor::Window { start, end, .. }), Walk::Windows { end: last, span, limit }) => {
+                if limit.is_some_and(|limit| received >= limit) && fresh > 0 {
+                    Some(PageCursor::Window { start, end, from_id: last_key })
+                } else if end < last {
+                    Some(PageCursor::Window {
+                        start: end,
+                        end: last.min(end.saturating_add(span)),
+                        from_id: None,
+                    })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Spaces requests [`REQUEST_INTERVAL`] apart and retries those rejected as
+/// "request too frequent" with exponential back-off
+#[derive(Debug, Default)]
+pub(crate) struct Throttle {
+    last_request: Option<Instant>,
+}
+
+impl Throttle {
+    pub(crate) fn call<T>(&mut self, mut request: impl FnMut() -> Result<T>) -> Result<T> {
+        std::thread::sleep(self.wait_time());
+        let mut attempt = 0;
+        loop {
+            self.last_request = Some(Instant::now());
+            match request() {
+                Err(e) => match retry_delay(&e, attempt) {
+                    Some(delay) => std::thread::sleep(delay),
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    pub(crate) async fn call_async<T, Fut>(&mut self, mut request: impl FnMut() -> Fut) -> Result<T>
+    where
+        Fut: Future<Output = Result<T>>,
+    {
+        tokio::time::sleep(self.wait_time()).await;
+        let mut attempt = 0;
+        loop {
+            self.last_request = Some(Instant::now());
+            match request().await {
+                Err(e) => match retry_delay(&e, attempt) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+                result => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    fn wait_time(&self) -> Duration {
+        self.last_request
+            .map(|at| REQUEST_INTERVAL.saturating_sub(at.elapsed()))
+            .unwrap_or_default()
+    }
+}
+
+fn retry_delay(error: &crate::errors::Error, attempt: u32) -> Option<Duration> {
+    (error.lbank_code() == Some(REQUEST_TOO_FREQUENT) && attempt < RATE_LIMIT_RETRIES)
+        .then(|| RATE_LIMIT_BACKOFF * 2u32.pow(attempt))
+}
+
+/// Iterator over every item of a paginated history endpoint
+///
+/// Pages are requested lazily, at most one every [`REQUEST_INTERVAL`], and
+/// retried with back-off when LBank answers "request too frequent". Items
+/// repeated across pages or window boundaries are yielded once. Iteration
+/// stops after the first error.
+pub struct HistoryIter<'a, T> {
+    paginator: Paginator<T>,
+    fetch: FetchPage<'a, T>,
+}
+
+type FetchPage<'a, T> = Box<dyn FnMut(&PageCursor) -> Result<Vec<T>> + 'a>;
+
+impl<'a, T> HistoryIter<'a, T> {
+    pub(crate) fn new(paginator: Paginator<T>, fetch: impl FnMut(&PageCursor) -> Result<Vec<T>> + 'a) -> Self {
+        HistoryIter {
+            paginator,
+            fetch: Box::new(fetch),
+        }
+    }
+}
+
+impl<T> Iterator for HistoryIter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if let Some(item) = self.paginator.buffer.pop_front() {
+                return Some(Ok(item));
+            }
+            let cursor = self.paginator.cursor.clone()?;
+            let fetch = &mut self.fetch;
+            match self.paginator.throttle.call(|| fetch(&cursor)) {
+                Ok(items) => self.paginator.advance(items),
+                Err(e) => {
+                    self.paginator.cursor = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Stream over every item of a paginated history endpoint
+///
+/// Async counterpart of [`HistoryIter`], with the same rate limiting and
+/// deduplication.
+pub(crate) fn history_stream<'a, T, F, Fut>(paginator: Paginator<T>, fetch: F) -> BoxStream<'a, Result<T>>
+where
+    T: Send + 'a,
+    F: FnMut(PageCursor) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<Vec<T>>> + Send + 'a,
+{
+    stream::unfold((paginator, fetch), |(mut paginator, mut fetch)| async move {
+        loop {
+            if let Some(item) = paginator.buffer.pop_front() {
+                return Some((Ok(item), (paginator, fetch)));
+            }
+            let cursor = paginator.cursor.clone()?;
+            match paginator.throttle.call_async(|| fetch(cursor.clone())).await {
+                Ok(items) => paginator.advance(items),
+                Err(e) => {
+                    paginator.cursor = None;
+                    return Some((Err(e), (paginator, fetch)));
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(item: &u32) -> String {
+        item.to_string()
+    }
+
+    #[test]
+    fn test_pages_stop_on_short_or_repeated_page() {
+        let pages = vec![vec![1, 2], vec![2, 3], vec![4]];
+        let mut requested = Vec::new();
+        let items: Vec<u32> = HistoryIter::new(Paginator::pages(2, key), |cursor: &PageCursor| {
+            requested.push(cursor.clone());
+            let PageCursor::Page { number, .. } = cursor else { unreachable!() };
+            Ok(pages[*number as usize - 1].clone())
+        })
+        .collect::<Result<_>>()
+        .unwrap();
+
+        assert_eq!(items, vec![1, 2, 3, 4]);
+        assert_eq!(requested.len(), 3);
+
+        // An endpoint that ignores the page number stops after the first repeat
+        let count = HistoryIter::new(Paginator::pages(2, key), |_: &PageCursor| Ok(vec![1, 2])).count();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_windows_continue_full_pages() {
+        let mut paginator = Paginator::windows(0, 25, 10, Some(2), key);
+        paginator.advance(vec![1, 2]);
+        assert_eq!(
+            paginator.cursor,
+            Some(PageCursor::Window { start: 0, end: 10, from_id: Some("2".into()) })
+        );
+        paginator.advance(vec![2]);
+        assert_eq!(paginator.cursor, Some(PageCursor::Window { start: 10, end: 20, from_id: None }));
+        paginator.advance(vec![]);
+        assert_eq!(paginator.cursor, Some(PageCursor::Window { start: 20, end: 25, from_id: None }));
+        paginator.advance(vec![3]);
+        assert_eq!(paginator.cursor, None);
+        assert_eq!(paginator.buffer, vec![1, 2, 3]);
+
+        assert!(Paginator::<u32>::windows(5, 5, 10, None, key).cursor.is_none());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::api::API;
+use crate::client::Method;
+use crate::errors::Result;
+use crate::util::{build_query_string, build_signed_request, parse_response};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+/// Description of one REST call: endpoint, parameters and response type
+///
+/// Endpoint modules build these once in their `requests` module, and both
+/// `Client::execute` and `AsyncClient::execute` send them, so the blocking
+/// and async endpoints cannot drift apart. Responses are the raw body
+/// (`Request<String>`) unless switched to a typed payload with [`Request::parse`].
+pub struct Request<T = String> {
+    pub method: Method,
+    pub endpoint: API,
+    pub params: BTreeMap<String, String>,
+    /// Whether `api_key`, `timestamp`, `echostr` and `sign` are added before sending
+    pub signed: bool,
+    decode: fn(&str) -> Result<T>,
+}
+
+impl<T> Clone for Request<T> {
+    fn clone(&self) -> Self {
+        Request {
+            method: self.method,
+            endpoint: self.endpoint,
+            params: self.params.clone(),
+            signed: self.signed,
+            decode: self.decode,
+        }
+    }
+}
+
+impl Request {
+    /// Unsigned GET with parameters in the query string
+    pub fn get(endpoint: API) -> Self {
+        Self::new(Method::Get, endpoint, false)
+    }
+
+    /// Unsigned POST with form-encoded parameters
+    pub fn post(endpoint: API) -> Self {
+        Self::new(Method::Post, endpoint, false)
+    }
+
+    /// Signed POST with form-encoded parameters
+    pub fn signed(endpoint: API) -> Self {
+        Self::new(Method::Post, endpoint, true)
+    }
+
+    fn new(method: Method, endpoint: API, signed: bool) -> Self {
+        Request {
+            method,
+            endpoint,
+            params: BTreeMap::new(),
+            signed,
+            decode: |text| Ok(text.to_string()),
+        }
+    }
+}
+
+impl<T> Request<T> {
+    /// Add a parameter
+    pub fn param(mut self, key: &str, value: impl ToString) -> Self {
+        self.params.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Add a parameter if it is set
+    pub fn param_opt(self, key: &str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(value) => self.param(key, value),
+            None => self,
+        }
+    }
+
+    /// Decode the response's `data` payload as `U`, turning LBank error codes into errors
+    pub fn parse<U: DeserializeOwned>(self) -> Request<U> {
+        Request {
+            method: self.method,
+            endpoint: self.endpoint,
+            params: self.params,
+            signed: self.signed,
+            decode: |text| parse_response(text),
+        }
+    }
+
+    /// Query string or form body for this request, signed if required
+    pub(crate) fn encode(&self, api_key: &str, secret_key: &str) -> Result<Option<String>> {
+        if self.signed {
+            return build_signed_request(self.params.clone(), api_key, secret_key).map(Some);
+        }
+        Ok(Some(build_query_string(&self.params)).filter(|query| !query.is_empty()))
+    }
+
+    /// Decode a response body
+    pub fn decode(&self, text: &str) -> Result<T> {
+        (self.decode)(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Market, Spot};
+
+    #[test]
+    fn test_encode() {
+        let request = Request::get(API::Market(Market::Trades))
+            .param("symbol", "lbk_usdt")
+            .param("size", 10)
+            .param_opt("time", None::<u64>);
+        assert_eq!(request.encode("", "").unwrap().as_deref(), Some("size=10&symbol=lbk_usdt"));
+        assert_eq!(Request::post(API::Market(Market::SystemPing)).encode("", "").unwrap(), None);
+
+        let signed = Request::signed(API::Spot(Spot::AccountInfo))
+            .encode("key", "0123456789abcdef")
+            .unwrap()
+            .unwrap();
+        assert!(signed.contains("api_key=key") && signed.contains("sign="));
+    }
+
+    #[test]
+    fn test_decode() {
+        let raw = Request::get(API::Market(Market::Price));
+        assert_eq!(raw.decode("not json").unwrap(), "not json");
+
+        let typed = raw.parse::<u64>();
+        assert_eq!(typed.decode(r#"{"result":true,"data":42,"error_code":0}"#).unwrap(), 42);
+        assert!(typed.decode(r#"{"result":false,"error_code":10008}"#).is_err());
+    }
+}
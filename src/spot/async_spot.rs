@@ -1,8 +1,13 @@
 use crate::client::AsyncClient;
-use crate::api::{API, Spot as SpotAPI};
-use crate::errors::Result;
-use crate::util::build_signed_request;
-use std::collections::BTreeMap;
+use crate::spot::requests;
+use crate::errors::{Error, Result, CUSTOM_ID_DUPLICATED, ORDER_NOT_FOUND};
+use crate::model::{AccountInfo, OrderInfo, OrderList, PlacedOrder, TransactionRecord};
+use crate::pagination::{history_stream, PageCursor, Paginator, TRANSACTION_PAGE_LIMIT, TRANSACTION_WINDOW_MS};
+use futures::stream::BoxStream;
+use crate::util::{format_utc8, parse_response};
+
+/// Number of times `place_order` will send the same order before giving up
+const PLACE_ORDER_ATTEMPTS: usize = 3;
 
 /// Spot Trading API client for asynchronous operations
 #[derive(Clone)]
@@ -29,29 +34,7 @@ impl AsyncSpot {
         custom_id: Option<&str>,
         window: Option<u64>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-        parameters.insert("type".to_string(), type_.to_string());
-
-        if let Some(p) = price {
-            parameters.insert("price".to_string(), p.to_string());
-        }
-        if let Some(a) = amount {
-            parameters.insert("amount".to_string(), a.to_string());
-        }
-        if let Some(cid) = custom_id {
-            parameters.insert("custom_id".to_string(), cid.to_string());
-        }
-        if let Some(w) = window {
-            parameters.insert("window".to_string(), w.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::OrderTest), Some(signed_request)).await
+        self.client.execute(requests::create_order_test(symbol, type_, price, amount, custom_id, window)).await
     }
 
     /// Create/place a new order
@@ -72,29 +55,91 @@ impl AsyncSpot {
         custom_id: Option<&str>,
         window: Option<u64>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-        parameters.insert("type".to_string(), type_.to_string());
+        self.client.execute(requests::create_order(symbol, type_, price, amount, custom_id, window)).await
+    }
 
-        if let Some(p) = price {
-            parameters.insert("price".to_string(), p.to_string());
-        }
-        if let Some(a) = amount {
-            parameters.insert("amount".to_string(), a.to_string());
-        }
-        if let Some(cid) = custom_id {
-            parameters.insert("custom_id".to_string(), cid.to_string());
-        }
-        if let Some(w) = window {
-            parameters.insert("window".to_string(), w.to_string());
+    /// Place an order idempotently under an auto-generated `custom_id`
+    ///
+    /// The `custom_id` is built from the client's configured prefix. When the
+    /// outcome of a request is unknown (timeout, network error, garbled response),
+    /// the order is looked up by `custom_id` before anything is resent. Resends
+    /// reuse the same `custom_id`, so LBank rejects them as duplicated (10036)
+    /// if an earlier attempt did land, and that order is returned instead.
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair (e.g., "eth_btc")
+    /// * `type_` - Order type (see `create_order`)
+    /// * `price` - Order price (required for limit orders)
+    /// * `amount` - Order amount (required)
+    /// * `window` - Optional order expiration time in milliseconds
+    pub async fn place_order(
+        &self,
+        symbol: &str,
+        type_: &str,
+        price: Option<&str>,
+        amount: Option<&str>,
+        window: Option<u64>,
+    ) -> Result<PlacedOrder> {
+        let custom_id = self.client.new_custom_id();
+        self.place_order_with_custom_id(&custom_id, symbol, type_, price, amount, window).await
+    }
+
+    /// Place an order idempotently under a caller-chosen `custom_id`
+    ///
+    /// Same retry behaviour as `place_order`. Calling this again with the same
+    /// `custom_id` (e.g. after a restart) never creates a second order.
+    pub async fn place_order_with_custom_id(
+        &self,
+        custom_id: &str,
+        symbol: &str,
+        type_: &str,
+        price: Option<&str>,
+        amount: Option<&str>,
+        window: Option<u64>,
+    ) -> Result<PlacedOrder> {
+        let mut last_error: Option<Error> = None;
+
+        for _ in 0..PLACE_ORDER_ATTEMPTS {
+            let placed = self
+                .create_order(symbol, type_, price, amount, Some(custom_id), window).await
+                .and_then(|text| parse_response::<PlacedOrder>(&text));
+
+            match placed {
+                Ok(order) => return Ok(order),
+                Err(e) if e.is_ambiguous() || e.lbank_code() == Some(CUSTOM_ID_DUPLICATED) => {
+                    match self.find_order_by_custom_id(symbol, custom_id).await {
+                        Ok(Some(order)) => return Ok(order),
+                        Ok(None) => last_error = Some(e),
+                        Err(lookup) if lookup.is_ambiguous() => last_error = Some(lookup),
+                        Err(lookup) => return Err(lookup),
+                    }
+                }
+                Err(e) => return Err(e),
+            }
         }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::CreateOrder), Some(signed_request)).await
+        Err(last_error.unwrap_or_else(|| "place_order made no attempts".into()))
+    }
+
+    /// Look up an order by its `custom_id`, returning `None` if LBank doesn't know it
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair
+    /// * `custom_id` - User-defined order ID the order was placed with
+    pub async fn find_order_by_custom_id(
+        &self,
+        symbol: &str,
+        custom_id: &str,
+    ) -> Result<Option<PlacedOrder>> {
+        let found = self
+            .order_info(symbol, None, Some(custom_id)).await
+            .and_then(|text| parse_response::<PlacedOrder>(&text));
+
+        match found {
+            Ok(order) => Ok(Some(order)),
+            Err(e) if e.lbank_code() == Some(ORDER_NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     /// Cancel a specific order
@@ -109,22 +154,7 @@ impl AsyncSpot {
         order_id: Option<&str>,
         orig_client_order_id: Option<&str>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-
-        if let Some(oid) = order_id {
-            parameters.insert("orderId".to_string(), oid.to_string());
-        }
-        if let Some(cid) = orig_client_order_id {
-            parameters.insert("origClientOrderId".to_string(), cid.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::CancelOrder), Some(signed_request)).await
+        self.client.execute(requests::cancel_order(symbol, order_id, orig_client_order_id)).await
     }
 
     /// Cancel all pending orders for a specific trading pair
@@ -132,15 +162,7 @@ impl AsyncSpot {
     /// # Parameters
     /// * `symbol` - Trading pair
     pub async fn cancel_order_by_symbol(&self, symbol: &str) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::CancelOrderBySymbol), Some(signed_request)).await
+        self.client.execute(requests::cancel_order_by_symbol(symbol)).await
     }
 
     /// Query specific order details
@@ -155,22 +177,7 @@ impl AsyncSpot {
         order_id: Option<&str>,
         orig_client_order_id: Option<&str>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-
-        if let Some(oid) = order_id {
-            parameters.insert("orderId".to_string(), oid.to_string());
-        }
-        if let Some(cid) = orig_client_order_id {
-            parameters.insert("origClientOrderId".to_string(), cid.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::OrderInfo), Some(signed_request)).await
+        self.client.execute(requests::order_info(symbol, order_id, orig_client_order_id)).await
     }
 
     /// Get all current open/pending orders for a trading pair
@@ -185,17 +192,7 @@ impl AsyncSpot {
         current_page: u32,
         page_length: u32,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-        parameters.insert("current_page".to_string(), current_page.to_string());
-        parameters.insert("page_length".to_string(), page_length.to_string());
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::OpenOrders), Some(signed_request)).await
+        self.client.execute(requests::open_orders(symbol, current_page, page_length)).await
     }
 
     /// Get historical orders (default: last 24 hours)
@@ -212,33 +209,12 @@ impl AsyncSpot {
         page_length: u32,
         status: Option<&str>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
-        parameters.insert("current_page".to_string(), current_page.to_string());
-        parameters.insert("page_length".to_string(), page_length.to_string());
-
-        if let Some(s) = status {
-            parameters.insert("status".to_string(), s.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::OrderHistory), Some(signed_request)).await
+        self.client.execute(requests::order_history(symbol, current_page, page_length, status)).await
     }
 
     /// Get account information including balances and permissions
-    pub async fn account_info(&self) -> Result<String> {
-        let parameters: BTreeMap<String, String> = BTreeMap::new();
-        
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::AccountInfo), Some(signed_request)).await
+    pub async fn account_info(&self) -> Result<AccountInfo> {
+        self.client.execute(requests::account_info()).await
     }
 
     /// Get historical transaction/trade details
@@ -257,28 +233,78 @@ impl AsyncSpot {
         from_id: Option<&str>,
         limit: Option<u32>,
     ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("symbol".to_string(), symbol.to_string());
+        self.client.execute(requests::transaction_history(symbol, start_time, end_time, from_id, limit)).await
+    }
 
-        if let Some(st) = start_time {
-            parameters.insert("startTime".to_string(), st.to_string());
-        }
-        if let Some(et) = end_time {
-            parameters.insert("endTime".to_string(), et.to_string());
-        }
-        if let Some(fid) = from_id {
-            parameters.insert("fromId".to_string(), fid.to_string());
-        }
-        if let Some(l) = limit {
-            parameters.insert("limit".to_string(), l.to_string());
-        }
+    /// Stream every order in the order history
+    ///
+    /// See [`Spot::order_history_iter`](crate::spot::Spot::order_history_iter).
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair
+    /// * `page_length` - Orders requested per page (clamped to 1-50)
+    /// * `status` - Optional order status filter
+    pub fn order_history_stream(
+        &self,
+        symbol: &str,
+        page_length: u32,
+        status: Option<&str>,
+    ) -> BoxStream<'_, Result<OrderInfo>> {
+        let paginator = Paginator::pages(page_length, |order: &OrderInfo| order.order_id.clone());
+        let symbol = symbol.to_string();
+        let status = status.map(str::to_string);
+        history_stream(paginator, move |cursor| {
+            let (symbol, status) = (symbol.clone(), status.clone());
+            async move {
+                let PageCursor::Page { number, length } = cursor else {
+                    return Ok(Vec::new());
+                };
+                let text = self.order_history(&symbol, number, length, status.as_deref()).await?;
+                Ok(parse_response::<OrderList>(&text)?.into_orders())
+            }
+        })
+    }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Spot(SpotAPI::TransactionHistory), Some(signed_request)).await
+    /// Stream every fill between `start_time` and `end_time`
+    ///
+    /// See [`Spot::transaction_history_iter`](crate::spot::Spot::transaction_history_iter).
+    ///
+    /// # Parameters
+    /// * `symbol` - Trading pair
+    /// * `start_time` - Start time (timestamp in milliseconds)
+    /// * `end_time` - End time (timestamp in milliseconds)
+    pub fn transaction_history_stream(
+        &self,
+        symbol: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> BoxStream<'_, Result<TransactionRecord>> {
+        let paginator = Paginator::windows(
+            start_time,
+            end_time,
+            TRANSACTION_WINDOW_MS,
+            Some(TRANSACTION_PAGE_LIMIT as usize),
+            |trade: &TransactionRecord| trade.id.clone(),
+        );
+        let symbol = symbol.to_string();
+        history_stream(paginator, move |cursor| {
+            let symbol = symbol.clone();
+            async move {
+                let PageCursor::Window { start, end, from_id } = cursor else {
+                    return Ok(Vec::new());
+                };
+                let text = self
+                    .transaction_history(
+                        &symbol,
+                        Some(&format_utc8(start)),
+                        Some(&format_utc8(end)),
+                        from_id.as_deref(),
+                        Some(TRANSACTION_PAGE_LIMIT),
+                    )
+                    .await?;
+                parse_response(&text)
+            }
+        })
     }
 }
 
@@ -0,0 +1,616 @@
+use crate::errors::{Error, Result, ORDER_CANCELLED, ORDER_CLOSED, ORDER_FILLED};
+use crate::market::{AsyncMarket, Market};
+use crate::model::{OrderInfo, OrderStatus, PlacedOrder, SymbolPrice};
+use crate::spot::{AsyncSpot, Spot};
+use crate::util::{check_response, client_order_id, parse_response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Distance a trailing stop keeps from the best price seen
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Trail {
+    /// Absolute price distance
+    Amount(f64),
+    /// Percentage of the best price (1.0 = 1%)
+    Percent(f64),
+}
+
+/// Trigger condition of a conditional order
+///
+/// Directions follow the side of the order being fired: a sell stop-loss fires
+/// when the price falls to the trigger, a buy stop-loss when it rises to it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    /// Fires once the price moves against the position to `trigger_price`
+    StopLoss { trigger_price: f64 },
+    /// Fires once the price moves in favour of the position to `trigger_price`
+    TakeProfit { trigger_price: f64 },
+    /// Follows the best price seen and fires once the price retraces by `trail`
+    TrailingStop { trail: Trail, best_price: Option<f64> },
+}
+
+/// Execution state of a conditional order
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionalStatus {
+    /// Waiting for its trigger
+    Pending,
+    /// Triggered, the order is being (or will be re-)sent
+    Triggered,
+    /// Rejected by the exchange; kept for inspection and never re-sent
+    Failed,
+}
+
+/// An order held client-side until its trigger condition is met
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConditionalOrder {
+    /// Engine ID, also sent as the order's `custom_id`
+    pub id: String,
+    pub symbol: String,
+    /// Order type sent when triggered (e.g. "sell_market", "sell")
+    pub type_: String,
+    pub price: Option<String>,
+    pub amount: Option<String>,
+    pub condition: Condition,
+    /// Exchange order cancelled before this one is placed
+    pub cancel_order_id: Option<String>,
+    /// Conditionals sharing a group are one-cancels-other
+    pub oco_group: Option<String>,
+    pub status: ConditionalStatus,
+}
+
+impl ConditionalOrder {
+    fn new(symbol: &str, type_: &str, price: Option<&str>, amount: Option<&str>, condition: Condition) -> Self {
+        ConditionalOrder {
+            id: String::new(),
+            symbol: symbol.to_string(),
+            type_: type_.to_string(),
+            price: price.map(str::to_string),
+            amount: amount.map(str::to_string),
+            condition,
+            cancel_order_id: None,
+            oco_group: None,
+            status: ConditionalStatus::Pending,
+        }
+    }
+
+    /// Stop-loss firing `type_` once the price reaches `trigger_price`
+    pub fn stop_loss(symbol: &str, type_: &str, price: Option<&str>, amount: Option<&str>, trigger_price: f64) -> Self {
+        Self::new(symbol, type_, price, amount, Condition::StopLoss { trigger_price })
+    }
+
+    /// Take-profit firing `type_` once the price reaches `trigger_price`
+    pub fn take_profit(symbol: &str, type_: &str, price: Option<&str>, amount: Option<&str>, trigger_price: f64) -> Self {
+        Self::new(symbol, type_, price, amount, Condition::TakeProfit { trigger_price })
+    }
+
+    /// Trailing stop firing `type_` once the price retraces by `trail`
+    pub fn trailing_stop(symbol: &str, type_: &str, price: Option<&str>, amount: Option<&str>, trail: Trail) -> Self {
+        Self::new(symbol, type_, price, amount, Condition::TrailingStop { trail, best_price: None })
+    }
+
+    /// Cancel the resting exchange order `order_id` before this one is placed
+    pub fn cancel_on_trigger(mut self, order_id: &str) -> Self {
+        self.cancel_order_id = Some(order_id.to_string());
+        self
+    }
+
+    fn is_buy(&self) -> bool {
+        self.type_.starts_with("buy")
+    }
+
+    /// Update trailing state with `price` and report whether the trigger is hit
+    fn observe(&mut self, price: f64) -> bool {
+        let buy = self.is_buy();
+        match &mut self.condition {
+            Condition::StopLoss { trigger_price } => {
+                if buy { price >= *trigger_price } else { price <= *trigger_price }
+            }
+            Condition::TakeProfit { trigger_price } => {
+                if buy { price <= *trigger_price } else { price >= *trigger_price }
+            }
+            Condition::TrailingStop { trail, best_price } => {
+                let best = match *best_price {
+                    Some(best) if buy => best.min(price),
+                    Some(best) => best.max(price),
+                    None => price,
+                };
+                *best_price = Some(best);
+                let distance = match *trail {
+                    Trail::Amount(amount) => amount,
+                    Trail::Percent(percent) => best * percent / 100.0,
+                };
+                if buy { price >= best + distance } else { price <= best - distance }
+            }
+        }
+    }
+}
+
+/// Result of executing a triggered conditional order
+#[derive(Debug)]
+pub enum FireOutcome {
+    /// The order was placed
+    Placed(PlacedOrder),
+    /// The linked exchange order had already filled, so nothing was placed
+    Superseded,
+    /// Sending failed
+    ///
+    /// When the outcome is ambiguous (see [`Error::is_ambiguous`]) the
+    /// conditional stays triggered and is re-sent under the same `custom_id`
+    /// on the next execution; otherwise it moves to `Failed`.
+    Failed(Error),
+}
+
+/// A conditional order that was executed, with its outcome
+#[derive(Debug)]
+pub struct FiredOrder {
+    pub conditional: ConditionalOrder,
+    pub outcome: FireOutcome,
+}
+
+/// Client-side engine for stop-loss, take-profit, OCO and trailing-stop orders
+///
+/// Prices come from the tick/trade WebSocket streams (see [`price_from_push`])
+/// or from polling `Market::price`. Triggered orders are sent through
+/// `place_order_with_custom_id` using the conditional's ID, and the engine's
+/// state is written to disk on every change, so after a restart pending
+/// conditionals resume and triggered ones are re-sent without ever placing the
+/// same order twice.
+#[derive(Debug, Default)]
+pub struct ConditionalEngine {
+    orders: BTreeMap<String, ConditionalOrder>,
+    path: Option<PathBuf>,
+    custom_id_prefix: String,
+}
+
+impl ConditionalEngine {
+    /// Create an engine that keeps its state in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an engine persisted to `path`, loading any state already saved there
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let orders = if path.exists() {
+            let saved: Vec<ConditionalOrder> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            saved.into_iter().map(|o| (o.id.clone(), o)).collect()
+        } else {
+            BTreeMap::new()
+        };
+        Ok(ConditionalEngine {
+            orders,
+            path: Some(path),
+            custom_id_prefix: String::new(),
+        })
+    }
+
+    /// Prefix generated IDs like `Client::new_custom_id` does, e.g. with the
+    /// client's `custom_id_prefix`
+    pub fn with_custom_id_prefix(mut self, prefix: &str) -> Self {
+        self.custom_id_prefix = prefix.to_string();
+        self
+    }
+
+    /// Add a conditional order, returning its ID
+    pub fn add(&mut self, mut order: ConditionalOrder) -> Result<String> {
+        if order.id.is_empty() {
+            order.id = client_order_id(&self.custom_id_prefix);
+        }
+        let id = order.id.clone();
+        self.orders.insert(id.clone(), order);
+        self.save()?;
+        Ok(id)
+    }
+
+    /// Add two conditional orders where the first to fire cancels the other
+    pub fn add_oco(&mut self, mut first: ConditionalOrder, mut second: ConditionalOrder) -> Result<(String, String)> {
+        let group = client_order_id(&self.custom_id_prefix);
+        first.oco_group = Some(group.clone());
+        second.oco_group = Some(group);
+        Ok((self.add(first)?, self.add(second)?))
+    }
+
+    /// Remove a conditional order
+    pub fn remove(&mut self, id: &str) -> Result<Option<ConditionalOrder>> {
+        let removed = self.orders.remove(id);
+        if removed.is_some() {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ConditionalOrder> {
+        self.orders.get(id)
+    }
+
+    /// All conditional orders held by the engine
+    pub fn orders(&self) -> Vec<&ConditionalOrder> {
+        self.orders.values().collect()
+    }
+
+    /// Triggered orders waiting to be sent
+    pub fn triggered(&self) -> Vec<&ConditionalOrder> {
+        self.orders.values().filter(|o| o.status == ConditionalStatus::Triggered).collect()
+    }
+
+    /// Feed a price, returning the orders it triggered
+    ///
+    /// OCO siblings of a triggered order can't fire while it is being sent
+    /// and are dropped once it is placed. Nothing is sent until
+    /// [`execute`](Self::execute) runs.
+    pub fn on_price(&mut self, symbol: &str, price: f64) -> Result<Vec<ConditionalOrder>> {
+        // Groups with a leg already triggered, so a price gap can't fire both legs
+        let mut firing: Vec<String> = self
+            .orders
+            .values()
+            .filter(|o| o.status == ConditionalStatus::Triggered)
+            .filter_map(|o| o.oco_group.clone())
+            .collect();
+        let mut fired: Vec<ConditionalOrder> = Vec::new();
+        let mut changed = false;
+        for order in self.orders.values_mut() {
+            if order.symbol != symbol || order.status != ConditionalStatus::Pending {
+                continue;
+            }
+            if order.oco_group.as_ref().is_some_and(|group| firing.contains(group)) {
+                continue;
+            }
+            let before = order.condition.clone();
+            if order.observe(price) {
+                order.status = ConditionalStatus::Triggered;
+                firing.extend(order.oco_group.clone());
+                fired.push(order.clone());
+            }
+            changed |= order.condition != before || order.status == ConditionalStatus::Triggered;
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(fired)
+    }
+
+    /// Send every triggered order
+    pub fn execute(&mut self, spot: &Spot) -> Result<Vec<FiredOrder>> {
+        let mut results = Vec::new();
+        for order in self.triggered().into_iter().cloned().collect::<Vec<_>>() {
+            let linked = match &order.cancel_order_id {
+                Some(order_id) => match spot
+                    .cancel_order(&order.symbol, Some(order_id), None)
+                    .and_then(|text| check_response(&text))
+                {
+                    Err(e) if e.lbank_code() == Some(ORDER_CLOSED) => spot
+                        .order_info(&order.symbol, Some(order_id), None)
+                        .and_then(|text| parse_response::<OrderInfo>(&text))
+                        .map(|info| Some(info.status)),
+                    cancelled => cancelled.map(|()| None),
+                },
+                None => Ok(None),
+            };
+            let outcome = match linked_cancel_outcome(linked) {
+                Some(outcome) => outcome,
+                None => match spot.place_order_with_custom_id(
+                    &order.id,
+                    &order.symbol,
+                    &order.type_,
+                    order.price.as_deref(),
+                    order.amount.as_deref(),
+                    None,
+                ) {
+                    Ok(placed) => FireOutcome::Placed(placed),
+                    Err(e) => FireOutcome::Failed(e),
+                },
+            };
+            results.push(self.settle(order, outcome)?);
+        }
+        Ok(results)
+    }
+
+    /// Send every triggered order (async)
+    ///
+    /// See [`ConditionalEngine::execute`].
+    pub async fn execute_async(&mut self, spot: &AsyncSpot) -> Result<Vec<FiredOrder>> {
+        let mut results = Vec::new();
+        for order in self.triggered().into_iter().cloned().collect::<Vec<_>>() {
+            let linked = match &order.cancel_order_id {
+                Some(order_id) => match spot
+                    .cancel_order(&order.symbol, Some(order_id), None)
+                    .await
+                    .and_then(|text| check_response(&text))
+                {
+                    Err(e) if e.lbank_code() == Some(ORDER_CLOSED) => spot
+                        .order_info(&order.symbol, Some(order_id), None)
+                        .await
+                        .and_then(|text| parse_response::<OrderInfo>(&text))
+                        .map(|info| Some(info.status)),
+                    cancelled => cancelled.map(|()| None),
+                },
+                None => Ok(None),
+            };
+            let outcome = match linked_cancel_outcome(linked) {
+                Some(outcome) => outcome,
+                None => match spot
+                    .place_order_with_custom_id(
+                        &order.id,
+                        &order.symbol,
+                        &order.type_,
+                        order.price.as_deref(),
+                        order.amount.as_deref(),
+                        None,
+                    )
+                    .await
+                {
+                    Ok(placed) => FireOutcome::Placed(placed),
+                    Err(e) => FireOutcome::Failed(e),
+                },
+            };
+            results.push(self.settle(order, outcome)?);
+        }
+        Ok(results)
+    }
+
+    /// Feed a price and send whatever it triggered
+    pub fn process_price(&mut self, spot: &Spot, symbol: &str, price: f64) -> Result<Vec<FiredOrder>> {
+        self.on_price(symbol, price)?;
+        self.execute(spot)
+    }
+
+    /// Feed a price and send whatever it triggered (async)
+    pub async fn process_price_async(&mut self, spot: &AsyncSpot, symbol: &str, price: f64) -> Result<Vec<FiredOrder>> {
+        self.on_price(symbol, price)?;
+        self.execute_async(spot).await
+    }
+
+    /// Poll `Market::price` once for all pairs and send whatever it triggered
+    pub fn poll(&mut self, market: &Market, spot: &Spot) -> Result<Vec<FiredOrder>> {
+        let prices = parse_response::<Vec<SymbolPrice>>(&market.price(None)?)?;
+        self.apply_prices(&prices)?;
+        self.execute(spot)
+    }
+
+    /// Poll `Market::price` once for all pairs and send whatever it triggered (async)
+    pub async fn poll_async(&mut self, market: &AsyncMarket, spot: &AsyncSpot) -> Result<Vec<FiredOrder>> {
+        let prices = parse_response::<Vec<SymbolPrice>>(&market.price(None).await?)?;
+        self.apply_prices(&prices)?;
+        self.execute_async(spot).await
+    }
+
+    fn apply_prices(&mut self, prices: &[SymbolPrice]) -> Result<()> {
+        for quote in prices {
+            if let Ok(price) = quote.price.parse::<f64>() {
+                self.on_price(&quote.symbol, price)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop a conditional whose execution finished, along with its OCO
+    /// siblings, keeping ambiguous failures for a retry and marking rejected
+    /// ones `Failed`
+    fn settle(&mut self, mut conditional: ConditionalOrder, outcome: FireOutcome) -> Result<FiredOrder> {
+        match &outcome {
+            FireOutcome::Failed(e) if e.is_ambiguous() => {}
+            FireOutcome::Failed(_) => {
+                conditional.status = ConditionalStatus::Failed;
+                self.orders.insert(conditional.id.clone(), conditional.clone());
+                self.save()?;
+            }
+            FireOutcome::Placed(_) | FireOutcome::Superseded => {
+                let group = conditional.oco_group.clone();
+                self.orders
+                    .retain(|id, o| id != &conditional.id && (group.is_none() || o.oco_group != group));
+                self.save()?;
+            }
+        }
+        Ok(FiredOrder { conditional, outcome })
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            let orders: Vec<&ConditionalOrder> = self.orders.values().collect();
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&orders)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decide what cancelling the linked order means for the conditional
+///
+/// `linked` is `Ok(None)` once the linked order is cancelled, or the status
+/// it was found in when the cancel reported it already closed (10037), which
+/// doesn't say whether it filled. `None` means the conditional should be
+/// placed.
+fn linked_cancel_outcome(linked: Result<Option<OrderStatus>>) -> Option<FireOutcome> {
+    match linked {
+        Ok(None) => None,
+        Ok(Some(OrderStatus::Filled)) => Some(FireOutcome::Superseded),
+        Ok(Some(status)) if status.is_final() => None,
+        Ok(Some(status)) => Some(FireOutcome::Failed(
+            format!("linked order reported closed but is {:?}", status).into(),
+        )),
+        Err(e) if e.lbank_code() == Some(ORDER_CANCELLED) => None,
+        Err(e) if e.lbank_code() == Some(ORDER_FILLED) => Some(FireOutcome::Superseded),
+        Err(e) => Some(FireOutcome::Failed(e)),
+    }
+}
+
+/// Extract `(pair, price)` from a `tick` or `trade` WebSocket push
+pub fn price_from_push(message: &str) -> Option<(String, f64)> {
+    let value: serde_json::Value = serde_json::from_str(message).ok()?;
+    let pair = value.get("pair")?.as_str()?.to_string();
+    let price = match value.get("type")?.as_str()? {
+        "tick" => value.get("tick")?.get("latest")?,
+        "trade" => value.get("trade")?.get("price")?,
+        _ => return None,
+    };
+    let price = match price {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => s.parse().ok()?,
+        _ => return None,
+    };
+    Some((pair, price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_loss_and_take_profit_directions() {
+        let mut engine = ConditionalEngine::new();
+        engine.add(ConditionalOrder::stop_loss("lbk_usdt", "sell_market", None, Some("100"), 0.009)).unwrap();
+        engine.add(ConditionalOrder::take_profit("lbk_usdt", "buy_market", Some("1"), None, 0.008)).unwrap();
+
+        assert!(engine.on_price("lbk_usdt", 0.010).unwrap().is_empty());
+        assert!(engine.on_price("eth_usdt", 0.001).unwrap().is_empty());
+
+        let fired = engine.on_price("lbk_usdt", 0.009).unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].type_, "sell_market");
+
+        let fired = engine.on_price("lbk_usdt", 0.0079).unwrap();
+        assert_eq!(fired[0].type_, "buy_market");
+        assert_eq!(engine.triggered().len(), 2);
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_best_price() {
+        let mut engine = ConditionalEngine::new();
+        engine
+            .add(ConditionalOrder::trailing_stop("btc_usdt", "sell_market", None, Some("1"), Trail::Percent(10.0)))
+            .unwrap();
+
+        for price in [100.0, 120.0, 110.0] {
+            assert!(engine.on_price("btc_usdt", price).unwrap().is_empty());
+        }
+        assert_eq!(engine.on_price("btc_usdt", 108.0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_oco_drops_sibling() {
+        let mut engine = ConditionalEngine::new();
+        let (stop, take) = engine
+            .add_oco(
+                ConditionalOrder::stop_loss("lbk_usdt", "sell_market", None, Some("100"), 0.009),
+                ConditionalOrder::take_profit("lbk_usdt", "sell_market", None, Some("100"), 0.012),
+            )
+            .unwrap();
+
+        let fired = engine.on_price("lbk_usdt", 0.0125).unwrap().remove(0);
+        assert_eq!(engine.get(&take).unwrap().status, ConditionalStatus::Triggered);
+        // The stop stays until the take-profit is placed, but can't fire meanwhile
+        assert!(engine.on_price("lbk_usdt", 0.008).unwrap().is_empty());
+        assert_eq!(engine.get(&stop).unwrap().status, ConditionalStatus::Pending);
+
+        let placed = PlacedOrder {
+            order_id: "oid".into(),
+            symbol: "lbk_usdt".into(),
+            custom_id: Some(take.clone()),
+        };
+        engine.settle(fired, FireOutcome::Placed(placed)).unwrap();
+        assert!(engine.orders().is_empty());
+    }
+
+    #[test]
+    fn test_oco_sibling_survives_rejected_leg() {
+        use crate::errors::{ErrorKind, LBankContentError};
+
+        let mut engine = ConditionalEngine::new().with_custom_id_prefix("bot-");
+        let (stop, take) = engine
+            .add_oco(
+                ConditionalOrder::stop_loss("lbk_usdt", "sell_market", None, Some("100"), 0.009),
+                ConditionalOrder::take_profit("lbk_usdt", "sell_market", None, Some("100"), 0.012),
+            )
+            .unwrap();
+        assert!(stop.starts_with("bot-") && take.starts_with("bot-"));
+
+        let fired = engine.on_price("lbk_usdt", 0.0125).unwrap().remove(0);
+        let rejected: Error = ErrorKind::LBankError(LBankContentError { code: 10016, msg: String::new() }).into();
+        engine.settle(fired, FireOutcome::Failed(rejected)).unwrap();
+        assert_eq!(engine.on_price("lbk_usdt", 0.008).unwrap()[0].id, stop);
+    }
+
+    #[test]
+    fn test_oco_fires_one_leg_on_gap() {
+        let mut engine = ConditionalEngine::new();
+        engine
+            .add_oco(
+                ConditionalOrder::stop_loss("lbk_usdt", "buy_market", Some("1"), None, 0.010),
+                ConditionalOrder::take_profit("lbk_usdt", "buy_market", Some("1"), None, 0.012),
+            )
+            .unwrap();
+
+        // Both a buy stop at 0.010 and a buy take-profit at 0.012 are satisfied by 0.011
+        assert_eq!(engine.on_price("lbk_usdt", 0.011).unwrap().len(), 1);
+        assert_eq!(engine.triggered().len(), 1);
+    }
+
+    #[test]
+    fn test_state_survives_restart() {
+        let path = std::env::temp_dir().join(format!("lbank-conditional-{}.json", crate::util::uuid_spot()));
+        {
+            let mut engine = ConditionalEngine::open(&path).unwrap();
+            engine
+                .add(ConditionalOrder::trailing_stop("btc_usdt", "sell", Some("90"), Some("1"), Trail::Amount(5.0)))
+                .unwrap();
+            engine.on_price("btc_usdt", 100.0).unwrap();
+        }
+
+        let mut engine = ConditionalEngine::open(&path).unwrap();
+        assert_eq!(engine.orders().len(), 1);
+        assert_eq!(engine.on_price("btc_usdt", 95.0).unwrap().len(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_only_ambiguous_failures_retry() {
+        use crate::errors::{ErrorKind, LBankContentError};
+
+        let mut engine = ConditionalEngine::new();
+        let id = engine.add(ConditionalOrder::stop_loss("lbk_usdt", "sell_market", None, Some("100"), 0.009)).unwrap();
+        let fired = engine.on_price("lbk_usdt", 0.008).unwrap().remove(0);
+
+        let timeout: Error = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into();
+        engine.settle(fired.clone(), FireOutcome::Failed(timeout)).unwrap();
+        assert_eq!(engine.triggered().len(), 1);
+
+        let rejected: Error = ErrorKind::LBankError(LBankContentError { code: 10014, msg: "insufficient".into() }).into();
+        let settled = engine.settle(fired, FireOutcome::Failed(rejected)).unwrap();
+        assert_eq!(settled.conditional.status, ConditionalStatus::Failed);
+        assert_eq!(engine.get(&id).unwrap().status, ConditionalStatus::Failed);
+        assert!(engine.triggered().is_empty());
+        assert!(engine.on_price("lbk_usdt", 0.007).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_linked_cancel_outcomes() {
+        use crate::errors::{ErrorKind, LBankContentError};
+
+        let code = |code| -> Result<Option<OrderStatus>> {
+            Err(ErrorKind::LBankError(LBankContentError { code, msg: String::new() }).into())
+        };
+        assert!(linked_cancel_outcome(Ok(None)).is_none());
+        assert!(linked_cancel_outcome(code(ORDER_CANCELLED)).is_none());
+        assert!(matches!(linked_cancel_outcome(code(ORDER_FILLED)), Some(FireOutcome::Superseded)));
+        // 10037 is resolved by looking the order up
+        assert!(matches!(linked_cancel_outcome(Ok(Some(OrderStatus::Filled))), Some(FireOutcome::Superseded)));
+        assert!(linked_cancel_outcome(Ok(Some(OrderStatus::PartiallyFilledCancelled))).is_none());
+        assert!(matches!(linked_cancel_outcome(Ok(Some(OrderStatus::New))), Some(FireOutcome::Failed(_))));
+    }
+
+    #[test]
+    fn test_price_from_push() {
+        let tick = r#"{"tick":{"latest":0.02698749,"high":0.0271},"type":"tick","pair":"eth_btc"}"#;
+        let trade = r#"{"trade":{"price":12129,"volume":6.36},"type":"trade","pair":"btc_usdt"}"#;
+
+        assert_eq!(price_from_push(tick), Some(("eth_btc".to_string(), 0.02698749)));
+        assert_eq!(price_from_push(trade), Some(("btc_usdt".to_string(), 12129.0)));
+        assert_eq!(price_from_push(r#"{"action":"ping"}"#), None);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::errors::{Error, Result};
+use crate::market::{AsyncMarket, Market};
+use crate::spot::{AsyncSpot, Spot};
+use crate::util::check_response;
+use std::collections::BTreeSet;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+struct State {
+    symbols: BTreeSet<String>,
+    last_heartbeat: Instant,
+    /// Set once open orders were cancelled for the current outage
+    tripped: bool,
+}
+
+/// Client-side cancel-on-disconnect watchdog
+///
+/// LBank has no server-side cancel-on-disconnect, so resting orders survive a
+/// crashed or disconnected process. The switch records heartbeats (successful
+/// `system_ping` calls from its own loop, or WebSocket pongs reported through
+/// [`DeadMansSwitch::heartbeat`]) and, once none arrived for `timeout`, cancels
+/// all open orders for the registered symbols with `cancel_order_by_symbol`.
+/// Cancellation is retried on every tick until it succeeds, and runs once more
+/// when the watchdog shuts down.
+#[derive(Clone)]
+pub struct DeadMansSwitch {
+    state: Arc<Mutex<State>>,
+    timeout: Duration,
+}
+
+impl DeadMansSwitch {
+    /// Create a switch that trips after `timeout` without a heartbeat
+    pub fn new(timeout: Duration) -> Self {
+        DeadMansSwitch {
+            state: Arc::new(Mutex::new(State {
+                symbols: BTreeSet::new(),
+                last_heartbeat: Instant::now(),
+                tripped: false,
+            })),
+            timeout,
+        }
+    }
+
+    /// Cancel open orders for `symbol` when the switch trips
+    pub fn register(&self, symbol: &str) {
+        self.lock().symbols.insert(symbol.to_string());
+    }
+
+    /// Stop watching `symbol`
+    pub fn unregister(&self, symbol: &str) {
+        self.lock().symbols.remove(symbol);
+    }
+
+    /// Symbols whose orders will be cancelled
+    pub fn symbols(&self) -> Vec<String> {
+        self.lock().symbols.iter().cloned().collect()
+    }
+
+    /// Record a successful round trip to the exchange (e.g. a WebSocket pong)
+    pub fn heartbeat(&self) {
+        let mut state = self.lock();
+        state.last_heartbeat = Instant::now();
+        state.tripped = false;
+    }
+
+    /// Whether the last heartbeat is older than the timeout
+    pub fn is_expired(&self) -> bool {
+        self.lock().last_heartbeat.elapsed() >= self.timeout
+    }
+
+    /// Cancel all open orders for the registered symbols
+    ///
+    /// Every symbol is attempted; the first error is returned afterwards.
+    pub fn cancel_all(&self, spot: &Spot) -> Result<()> {
+        let mut first_error: Option<Error> = None;
+        for symbol in self.symbols() {
+            if let Err(e) = spot.cancel_order_by_symbol(&symbol).and_then(|text| check_response(&text)) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Cancel all open orders for the registered symbols (async)
+    ///
+    /// See [`DeadMansSwitch::cancel_all`].
+    pub async fn cancel_all_async(&self, spot: &AsyncSpot) -> Result<()> {
+        let mut first_error: Option<Error> = None;
+        for symbol in self.symbols() {
+            let cancelled = spot.cancel_order_by_symbol(&symbol).await;
+            if let Err(e) = cancelled.and_then(|text| check_response(&text)) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Run the watchdog on a background thread, pinging every `ping_interval`
+    pub fn spawn(&self, spot: Spot, market: Market, ping_interval: Duration) -> DeadMansSwitchHandle {
+        let switch = self.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(ping_interval) {
+                if switch.tick(market.system_ping().is_ok()) {
+                    switch.mark_tripped(switch.cancel_all(&spot).is_ok());
+                }
+            }
+            switch.cancel_all(&spot)
+        });
+
+        DeadMansSwitchHandle {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Run the watchdog as a tokio task, pinging every `ping_interval`
+    pub fn spawn_async(
+        &self,
+        spot: AsyncSpot,
+        market: AsyncMarket,
+        ping_interval: Duration,
+    ) -> AsyncDeadMansSwitchHandle {
+        let switch = self.clone();
+        let (stop, mut stopped) = watch::channel(false);
+
+        let task = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ping_interval);
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if switch.tick(market.system_ping().await.is_ok()) {
+                            let cancelled = switch.cancel_all_async(&spot).await;
+                            switch.mark_tripped(cancelled.is_ok());
+                        }
+                    }
+                    _ = stopped.changed() => break,
+                }
+            }
+            switch.cancel_all_async(&spot).await
+        });
+
+        AsyncDeadMansSwitchHandle { stop, task }
+    }
+
+    /// Record a ping result, returning whether open orders should be cancelled now
+    fn tick(&self, ping_ok: bool) -> bool {
+        if ping_ok {
+            self.heartbeat();
+        }
+        let state = self.lock();
+        !state.tripped && state.last_heartbeat.elapsed() >= self.timeout
+    }
+
+    fn mark_tripped(&self, cancelled: bool) {
+        if cancelled {
+            self.lock().tripped = true;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Handle to a watchdog thread started with [`DeadMansSwitch::spawn`]
+///
+/// Dropping the handle stops the watchdog and waits for its final cancellation.
+pub struct DeadMansSwitchHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl DeadMansSwitchHandle {
+    /// Stop the watchdog, cancelling open orders for the registered symbols
+    pub fn shutdown(mut self) -> Result<()> {
+        self.stop.take();
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err("dead man's switch thread panicked".into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for DeadMansSwitchHandle {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Handle to a watchdog task started with [`DeadMansSwitch::spawn_async`]
+///
+/// Dropping the handle stops the watchdog, but only [`shutdown`](Self::shutdown)
+/// waits for the final cancellation to complete.
+pub struct AsyncDeadMansSwitchHandle {
+    stop: watch::Sender<bool>,
+    task: tokio::task::JoinHandle<Result<()>>,
+}
+
+impl AsyncDeadMansSwitchHandle {
+    /// Stop the watchdog, cancelling open orders for the registered symbols
+    pub async fn shutdown(self) -> Result<()> {
+        let _ = self.stop.send(true);
+        match self.task.await {
+            Ok(result) => result,
+            Err(e) => Err(format!("dead man's switch task failed: {}", e).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_once_per_outage() {
+        let switch = DeadMansSwitch::new(Duration::from_millis(0));
+
+        assert!(switch.tick(false));
+        switch.mark_tripped(false);
+        assert!(switch.tick(false), "failed cancellation is retried");
+
+        switch.mark_tripped(true);
+        assert!(!switch.tick(false));
+
+        switch.heartbeat();
+        assert!(switch.tick(false), "a new outage trips again");
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_switch_armed() {
+        let switch = DeadMansSwitch::new(Duration::from_secs(60));
+        switch.register("lbk_usdt");
+        switch.register("eth_usdt");
+        switch.unregister("eth_usdt");
+
+        assert!(!switch.tick(true));
+        assert!(!switch.is_expired());
+        assert_eq!(switch.symbols(), vec!["lbk_usdt".to_string()]);
+    }
+}
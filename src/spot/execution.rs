@@ -0,0 +1,426 @@
+use crate::errors::{Result, ORDER_CANCELLED, ORDER_CLOSED, ORDER_FILLED};
+use crate::market::{kline_interval, AsyncMarket};
+use crate::model::{Candle, OrderInfo, PlacedOrder};
+use crate::spot::AsyncSpot;
+use crate::util::{check_response, get_timestamp, parse_response};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Order to be worked by an execution algorithm
+#[derive(Debug, Clone)]
+pub struct ExecutionParams {
+    pub symbol: String,
+    /// Child order type, e.g. "buy", "sell", "buy_ioc" or "sell_market"
+    pub type_: String,
+    /// Limit price for child orders (omit for market sells)
+    pub price: Option<String>,
+    /// Total base-asset amount to execute
+    pub total_amount: f64,
+    /// Decimal places allowed for amounts on this pair (see `Common::accuracy`)
+    pub amount_decimals: u32,
+}
+
+/// Execution algorithm and its settings
+#[derive(Debug, Clone)]
+pub enum Algorithm {
+    /// Equal slices spread evenly over `duration`
+    Twap { duration: Duration, slices: usize },
+    /// Slices spread over `duration`, weighted by the volume traded in the
+    /// same window 24 hours earlier, read from `kline_type` bars
+    Vwap { duration: Duration, slices: usize, kline_type: String },
+    /// Shows at most `clip` on the book, refreshing it each time it fills
+    Iceberg { clip: f64, poll_interval: Duration },
+}
+
+/// Snapshot of a running execution
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionProgress {
+    pub target: f64,
+    pub filled: f64,
+    pub orders_placed: usize,
+    pub finished: bool,
+}
+
+/// Final result of an execution
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub target: f64,
+    pub filled: f64,
+    /// What was left of `target`, including the unfilled part of the last slice
+    pub unfilled: f64,
+    pub orders: Vec<PlacedOrder>,
+    /// Whether the execution was stopped before completing
+    pub cancelled: bool,
+}
+
+/// Handle to a running execution algorithm
+pub struct ExecutionHandle {
+    progress: watch::Receiver<ExecutionProgress>,
+    cancel: watch::Sender<bool>,
+    task: JoinHandle<Result<ExecutionReport>>,
+}
+
+impl ExecutionHandle {
+    /// Receiver that is updated every time the execution makes progress
+    pub fn progress(&self) -> watch::Receiver<ExecutionProgress> {
+        self.progress.clone()
+    }
+
+    /// Stop the execution; the resting child order is cancelled
+    pub fn cancel(&self) {
+        let _ = self.cancel.send(true);
+    }
+
+    /// Wait for the execution to finish
+    ///
+    /// If it fails, the child order on the book is cancelled first (best
+    /// effort); the progress channel still holds what was filled.
+    pub async fn wait(self) -> Result<ExecutionReport> {
+        match self.task.await {
+            Ok(report) => report,
+            Err(e) => Err(format!("execution task failed: {}", e).into()),
+        }
+    }
+}
+
+/// Start an execution algorithm on the async spot client
+///
+/// `market` is only used by `Algorithm::Vwap` to read the volume profile.
+pub fn execute(
+    spot: AsyncSpot,
+    market: AsyncMarket,
+    params: ExecutionParams,
+    algorithm: Algorithm,
+) -> ExecutionHandle {
+    let (progress_tx, progress) = watch::channel(ExecutionProgress {
+        target: params.total_amount,
+        ..Default::default()
+    });
+    let (cancel, cancelled) = watch::channel(false);
+
+    let mut execution = Execution {
+        spot,
+        params,
+        progress: progress_tx,
+        cancelled,
+        orders: Vec::new(),
+        live: None,
+        filled: 0.0,
+    };
+    let task = tokio::spawn(async move {
+        let result = execution.run(&market, algorithm).await;
+        execution.progress.send_modify(|p| p.finished = true);
+        result
+    });
+
+    ExecutionHandle { progress, cancel, task }
+}
+
+/// Split `total` into `slices` equal amounts rounded down to `decimals`
+///
+/// Rounding leftovers are added to the last slice.
+pub fn twap_schedule(total: f64, slices: usize, decimals: u32) -> Vec<f64> {
+    vwap_schedule(total, &vec![1.0; slices.max(1)], decimals)
+}
+
+/// Split `total` proportionally to `volumes`, rounded down to `decimals`
+///
+/// Falls back to equal slices when the profile has no volume. Rounding
+/// leftovers are added to the last slice.
+pub fn vwap_schedule(total: f64, volumes: &[f64], decimals: u32) -> Vec<f64> {
+    if volumes.is_empty() {
+        return vec![round_down(total, decimals)];
+    }
+    let sum: f64 = volumes.iter().sum();
+    let weights: Vec<f64> = if sum > 0.0 {
+        volumes.iter().map(|v| v / sum).collect()
+    } else {
+        vec![1.0 / volumes.len() as f64; volumes.len()]
+    };
+
+    let mut slices: Vec<f64> = weights.iter().map(|w| round_down(total * w, decimals)).collect();
+    let allocated: f64 = slices.iter().sum();
+    if let Some(last) = slices.last_mut() {
+        *last = round_down(*last + (total - allocated), decimals);
+    }
+    slices
+}
+
+/// Start (aligned to a bar) and number of `bar`-second bars covering
+/// `duration` from `from`, both in seconds
+fn profile_window(from: u64, duration: Duration, bar: u64) -> (u64, u64) {
+    let start = from - from % bar;
+    let end = from + duration.as_secs();
+    (start, (end - start).div_ceil(bar).max(1))
+}
+
+/// Total volume of `candles` in each of `slices` equal parts of the
+/// `span` seconds from `start`
+fn volume_profile(candles: &[Candle], start: u64, span: u64, slices: usize) -> Vec<f64> {
+    let mut volumes = vec![0.0; slices];
+    for candle in candles {
+        if candle.time < start || candle.time >= start + span {
+            continue;
+        }
+        let slice = ((candle.time - start) as u128 * slices as u128 / span as u128) as usize;
+        volumes[slice.min(slices - 1)] += candle.volume;
+    }
+    volumes
+}
+
+fn round_down(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    // Nudge by a hair so 0.3 * 10 == 2.9999999 doesn't lose a whole unit
+    ((value * factor) + 1e-9).floor().max(0.0) / factor
+}
+
+struct Execution {
+    spot: AsyncSpot,
+    params: ExecutionParams,
+    progress: watch::Sender<ExecutionProgress>,
+    cancelled: watch::Receiver<bool>,
+    orders: Vec<PlacedOrder>,
+    /// Child order that may still be on the book
+    live: Option<PlacedOrder>,
+    filled: f64,
+}
+
+impl Execution {
+    async fn run(&mut self, market: &AsyncMarket, algorithm: Algorithm) -> Result<ExecutionReport> {
+        let result = self.work(market, algorithm).await;
+        if result.is_err() {
+            if let Some(order) = self.live.take() {
+                let _ = self.spot.cancel_order(&order.symbol, Some(&order.order_id), None).await;
+            }
+        }
+        result
+    }
+
+    async fn work(&mut self, market: &AsyncMarket, algorithm: Algorithm) -> Result<ExecutionReport> {
+        if self.params.type_ == "buy_market" {
+            return Err("buy_market orders are sized in quote currency and can't be sliced by amount".into());
+        }
+
+        let cancelled = match algorithm {
+            Algorithm::Twap { duration, slices } => {
+                let plan = twap_schedule(self.params.total_amount, slices, self.params.amount_decimals);
+                self.run_schedule(plan, duration / slices.max(1) as u32).await?
+            }
+            Algorithm::Vwap { duration, slices, kline_type } => {
+                let bar = kline_interval(&kline_type)
+                    .ok_or_else(|| format!("VWAP needs fixed-length bars, not {}", kline_type))?;
+                let (start, bars) = profile_window(get_timestamp() / 1000 - 24 * 60 * 60, duration, bar);
+                let series = market
+                    .kline_history(&self.params.symbol, &kline_type, start, start + bars * bar - 1)
+                    .await?;
+                let volumes = volume_profile(&series.candles, start, bars * bar, slices.max(1));
+                let plan = vwap_schedule(self.params.total_amount, &volumes, self.params.amount_decimals);
+                self.run_schedule(plan, duration / slices.max(1) as u32).await?
+            }
+            Algorithm::Iceberg { clip, poll_interval } => self.run_iceberg(clip, poll_interval).await?,
+        };
+
+        Ok(ExecutionReport {
+            target: self.params.total_amount,
+            filled: self.filled,
+            unfilled: round_down(self.params.total_amount - self.filled, self.params.amount_decimals),
+            orders: self.orders.clone(),
+            cancelled,
+        })
+    }
+
+    /// Place one slice per interval, rolling each slice's unfilled remainder into the next
+    ///
+    /// The last slice is cancelled after one interval and whatever it left is
+    /// reported as unfilled.
+    async fn run_schedule(&mut self, plan: Vec<f64>, interval: Duration) -> Result<bool> {
+        let decimals = self.params.amount_decimals;
+        let mut carry = 0.0;
+        let mut resting: Option<(PlacedOrder, f64)> = None;
+
+        for (i, slice) in plan.iter().enumerate() {
+            if i > 0 && self.sleep_or_cancel(interval).await {
+                self.settle_resting(resting.take()).await?;
+                return Ok(true);
+            }
+            if let Some((order, amount)) = resting.take() {
+                carry += amount - self.settle(&order).await?;
+            }
+
+            let amount = round_down(slice + carry, decimals);
+            carry = slice + carry - amount;
+            if amount > 0.0 {
+                resting = Some((self.place(amount).await?, amount));
+            }
+        }
+
+        let cancelled = self.sleep_or_cancel(interval).await;
+        self.settle_resting(resting).await?;
+        Ok(cancelled)
+    }
+
+    /// Keep a single clip on the book until the total is filled
+    async fn run_iceberg(&mut self, clip: f64, poll_interval: Duration) -> Result<bool> {
+        let decimals = self.params.amount_decimals;
+        loop {
+            let amount = round_down(clip.min(self.params.total_amount - self.filled), decimals);
+            if amount <= 0.0 {
+                return Ok(false);
+            }
+            let order = self.place(amount).await?;
+
+            loop {
+                if self.sleep_or_cancel(poll_interval).await {
+                    self.settle(&order).await?;
+                    return Ok(true);
+                }
+                let info = self.order_info(&order).await?;
+                if info.status.is_final() {
+                    self.live = None;
+                    self.record_fill(info.executed_qty.parse().unwrap_or(0.0));
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn place(&mut self, amount: f64) -> Result<PlacedOrder> {
+        let amount = format!("{:.*}", self.params.amount_decimals as usize, amount);
+        let order = self
+            .spot
+            .place_order(&self.params.symbol, &self.params.type_, self.params.price.as_deref(), Some(&amount), None)
+            .await?;
+        self.orders.push(order.clone());
+        self.live = Some(order.clone());
+        self.progress.send_modify(|p| p.orders_placed += 1);
+        Ok(order)
+    }
+
+    async fn settle_resting(&mut self, resting: Option<(PlacedOrder, f64)>) -> Result<()> {
+        if let Some((order, _)) = resting {
+            self.settle(&order).await?;
+        }
+        Ok(())
+    }
+
+    /// Cancel whatever is left of `order` and record its fills, returning the executed amount
+    async fn settle(&mut self, order: &PlacedOrder) -> Result<f64> {
+        let cancelled = self
+            .spot
+            .cancel_order(&order.symbol, Some(&order.order_id), None)
+            .await
+            .and_then(|text| check_response(&text));
+        match cancelled {
+            Err(e) if !matches!(e.lbank_code(), Some(ORDER_FILLED) | Some(ORDER_CANCELLED) | Some(ORDER_CLOSED)) => {
+                return Err(e)
+            }
+            _ => {}
+        }
+
+        let executed = self.order_info(order).await?.executed_qty.parse().unwrap_or(0.0);
+        self.live = None;
+        self.record_fill(executed);
+        Ok(executed)
+    }
+
+    async fn order_info(&self, order: &PlacedOrder) -> Result<OrderInfo> {
+        let text = self.spot.order_info(&order.symbol, Some(&order.order_id), None).await?;
+        parse_response::<OrderInfo>(&text)
+    }
+
+    fn record_fill(&mut self, executed: f64) {
+        self.filled += executed;
+        let filled = self.filled;
+        self.progress.send_modify(|p| p.filled = filled);
+    }
+
+    /// Sleep for `duration`, returning `true` early if the execution was cancelled
+    async fn sleep_or_cancel(&mut self, duration: Duration) -> bool {
+        if *self.cancelled.borrow() {
+            return true;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => false,
+            changed = self.cancelled.changed() => changed.is_err() || *self.cancelled.borrow(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twap_schedule_rounds_into_last_slice() {
+        let plan = twap_schedule(1.0, 3, 2);
+        assert_eq!(plan, vec![0.33, 0.33, 0.34]);
+    }
+
+    #[test]
+    fn test_vwap_schedule_follows_volume() {
+        let plan = vwap_schedule(100.0, &[10.0, 30.0, 60.0], 0);
+        assert_eq!(plan, vec![10.0, 30.0, 60.0]);
+
+        let flat = vwap_schedule(9.0, &[0.0, 0.0, 0.0], 0);
+        assert_eq!(flat, vec![3.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn test_vwap_profile_covers_duration() {
+        // 30 minutes of minute bars from 12:00:30 starts at the 12:00 bar
+        let (start, bars) = profile_window(43_230, Duration::from_secs(30 * 60), 60);
+        assert_eq!((start, bars), (43_200, 31));
+        let (_, bars) = profile_window(43_200, Duration::from_secs(10), 3600);
+        assert_eq!(bars, 1);
+
+        // One bar past the window is ignored
+        let candles: Vec<Candle> = (0..32)
+            .map(|i| Candle { time: 43_200 + i * 60, open: 1.0, high: 1.0, low: 1.0, close: 1.0, volume: i as f64 })
+            .collect();
+        let volumes = volume_profile(&candles, 43_200, 31 * 60, 3);
+        assert_eq!(volumes.len(), 3);
+        assert_eq!(volumes.iter().sum::<f64>(), (0..31).sum::<u64>() as f64);
+        assert!(volumes[0] < volumes[1] && volumes[1] < volumes[2]);
+    }
+
+    #[test]
+    fn test_round_down() {
+        assert_eq!(round_down(0.3, 1), 0.3);
+        assert_eq!(round_down(1.239, 2), 1.23);
+        assert_eq!(round_down(-0.5, 2), 0.0);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[tokio::test]
+    async fn test_unfilled_remainder_reported() {
+        let server = MockServer::start_for_tests();
+        server.add_pair("lbk_usdt", 0.01);
+        server.set_balance("lbk", 100.0);
+        let client = server.test_async_client();
+        let params = ExecutionParams {
+            symbol: "lbk_usdt".into(),
+            type_: "sell".into(),
+            price: Some("0.02".into()),
+            total_amount: 10.0,
+            amount_decimals: 0,
+        };
+        let algorithm = Algorithm::Twap {
+            duration: Duration::from_millis(40),
+            slices: 2,
+        };
+
+        let report = execute(AsyncSpot { client: client.clone() }, AsyncMarket { client }, params, algorithm)
+            .wait()
+            .await
+            .unwrap();
+        assert_eq!((report.filled, report.unfilled, report.orders.len()), (0.0, 10.0, 2));
+        // Nothing is left resting on the book
+        assert_eq!(server.balance("lbk"), (100.0, 0.0));
+    }
+}
@@ -1,6 +1,21 @@
 mod spot;
 mod async_spot;
+mod order_manager;
+mod dead_mans_switch;
+mod conditional;
+mod execution;
+pub mod requests;
 
 pub use spot::Spot;
 pub use async_spot::AsyncSpot;
+pub use order_manager::{OrderEvent, OrderManager, OrderState, TrackedOrder};
+pub use dead_mans_switch::{AsyncDeadMansSwitchHandle, DeadMansSwitch, DeadMansSwitchHandle};
+pub use conditional::{
+    price_from_push, Condition, ConditionalEngine, ConditionalOrder, ConditionalStatus, FireOutcome,
+    FiredOrder, Trail,
+};
+pub use execution::{
+    execute, twap_schedule, vwap_schedule, Algorithm, ExecutionHandle, ExecutionParams,
+    ExecutionProgress, ExecutionReport,
+};
 
@@ -0,0 +1,576 @@
+use crate::errors::Result;
+use crate::events::EventHub;
+use crate::model::{OrderInfo, OrderList, OrderStatus, OrderUpdate, OrderUpdatePush, PlacedOrder};
+use crate::spot::{AsyncSpot, Spot};
+use crate::util::parse_response;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc as async_mpsc;
+
+/// Page size used when reconciling against `open_orders`
+const RECONCILE_PAGE_LENGTH: u32 = 200;
+/// Filled and cancelled orders kept for lookups before the oldest are dropped
+pub const DEFAULT_FINAL_RETENTION: usize = 1000;
+/// How long a submitted order waits for its acknowledgement before it is dropped
+pub const DEFAULT_PENDING_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Lifecycle state of an order tracked by the `OrderManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderState {
+    /// Sent to the exchange, not yet acknowledged
+    Submitted,
+    New,
+    PartiallyFilled,
+    Cancelling,
+    Filled,
+    Cancelled,
+}
+
+impl OrderState {
+    /// Whether the order can no longer change
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderState::Filled | OrderState::Cancelled)
+    }
+}
+
+impl From<OrderStatus> for OrderState {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::New => OrderState::New,
+            OrderStatus::PartiallyFilled => OrderState::PartiallyFilled,
+            OrderStatus::Cancelling => OrderState::Cancelling,
+            OrderStatus::Filled => OrderState::Filled,
+            OrderStatus::Cancelled | OrderStatus::PartiallyFilledCancelled => OrderState::Cancelled,
+        }
+    }
+}
+
+/// Local view of an order
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub symbol: String,
+    /// Exchange order ID, `None` until the order is acknowledged
+    pub order_id: Option<String>,
+    pub custom_id: Option<String>,
+    pub state: OrderState,
+    pub executed_qty: String,
+    pub avg_price: Option<String>,
+    /// Last exchange update time in milliseconds, 0 until the exchange reports one
+    pub update_time: i64,
+}
+
+/// Emitted whenever a tracked order changes state or fills further
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderEvent {
+    pub previous: OrderState,
+    pub order: TrackedOrder,
+}
+
+/// A submitted order waiting for its exchange order ID
+struct Pending {
+    order: TrackedOrder,
+    submitted_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Acknowledged orders keyed by exchange order ID
+    orders: HashMap<String, TrackedOrder>,
+    /// Submitted orders keyed by custom ID
+    pending: HashMap<String, Pending>,
+    /// IDs of orders in `orders` that reached a final state, oldest first
+    finished: VecDeque<String>,
+    /// IDs of finished orders dropped from `orders`, oldest first; later
+    /// updates for them are ignored
+    dropped: VecDeque<String>,
+    dropped_ids: HashSet<String>,
+}
+
+impl Inner {
+    fn drop_finished(&mut self, order_id: String, retention: usize) {
+        if self.dropped_ids.insert(order_id.clone()) {
+            self.dropped.push_back(order_id);
+        }
+        while self.dropped.len() > retention {
+            if let Some(oldest) = self.dropped.pop_front() {
+                self.dropped_ids.remove(&oldest);
+            }
+        }
+    }
+
+    fn expire_pending(&mut self, ttl: Duration) {
+        self.pending.retain(|_, pending| pending.submitted_at.elapsed() < ttl);
+    }
+}
+
+/// In-process bookkeeping of live orders
+///
+/// Records submitted orders, applies `orderUpdate` WebSocket pushes and REST
+/// snapshots, and notifies subscribers of every state transition. Cloning is
+/// cheap and clones share the same state, so one manager can be fed from a
+/// WebSocket task and a reconciliation loop at the same time.
+///
+/// Only the latest [`DEFAULT_FINAL_RETENTION`] filled or cancelled orders are
+/// kept; older ones are dropped as new orders finish, or earlier with
+/// `forget`. Late updates for a dropped order, and final updates for orders
+/// the manager doesn't track, are ignored rather than tracked anew. Submitted
+/// orders that are neither acknowledged nor seen in an update within
+/// [`DEFAULT_PENDING_TTL`] are dropped too.
+#[derive(Clone)]
+pub struct OrderManager {
+    inner: Arc<Mutex<Inner>>,
+    events: EventHub<OrderEvent>,
+    final_retention: usize,
+    pending_ttl: Duration,
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        OrderManager {
+            inner: Arc::default(),
+            events: EventHub::new(),
+            final_retention: DEFAULT_FINAL_RETENTION,
+            pending_ttl: DEFAULT_PENDING_TTL,
+        }
+    }
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `count` filled or cancelled orders
+    pub fn with_final_retention(mut self, count: usize) -> Self {
+        self.final_retention = count;
+        self
+    }
+
+    /// Drop submitted orders not acknowledged within `ttl`
+    pub fn with_pending_ttl(mut self, ttl: Duration) -> Self {
+        self.pending_ttl = ttl;
+        self
+    }
+
+    /// Register a callback invoked for every order event
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&OrderEvent) + Send + Sync + 'static,
+    {
+        self.events.on_event(callback);
+    }
+
+    /// Subscribe to order events through a blocking channel
+    pub fn subscribe(&self) -> mpsc::Receiver<OrderEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to order events through an async channel
+    pub fn subscribe_async(&self) -> async_mpsc::UnboundedReceiver<OrderEvent> {
+        self.events.subscribe_async()
+    }
+
+    /// Record an order that is about to be sent under `custom_id`
+    pub fn record_submitted(&self, symbol: &str, custom_id: &str) {
+        let order = TrackedOrder {
+            symbol: symbol.to_string(),
+            order_id: None,
+            custom_id: Some(custom_id.to_string()),
+            state: OrderState::Submitted,
+            executed_qty: "0".to_string(),
+            avg_price: None,
+            update_time: 0,
+        };
+        let mut inner = self.lock();
+        inner.expire_pending(self.pending_ttl);
+        inner.pending.insert(
+            custom_id.to_string(),
+            Pending {
+                order,
+                submitted_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Forget a submitted order whose placement definitively failed
+    pub fn discard_submitted(&self, custom_id: &str) {
+        self.lock().pending.remove(custom_id);
+    }
+
+    /// Stop tracking an order, returning it if it was tracked
+    ///
+    /// Pushes for a forgotten order that is still live start tracking it
+    /// again; those for a forgotten finished order are ignored.
+    pub fn forget(&self, order_id: &str) -> Option<TrackedOrder> {
+        let mut inner = self.lock();
+        inner.finished.retain(|id| id != order_id);
+        let order = inner.orders.remove(order_id)?;
+        if order.state.is_final() {
+            inner.drop_finished(order_id.to_string(), self.final_retention);
+        }
+        Some(order)
+    }
+
+    /// Record an order acknowledged by `create_order`/`place_order`
+    pub fn record_placed(&self, placed: &PlacedOrder) {
+        let mut inner = self.lock();
+        if inner.orders.contains_key(&placed.order_id) {
+            return;
+        }
+
+        let pending = placed.custom_id.as_ref().and_then(|cid| inner.pending.remove(cid));
+        let previous = pending.map(|p| p.order.state).unwrap_or(OrderState::Submitted);
+        let order = TrackedOrder {
+            symbol: placed.symbol.clone(),
+            order_id: Some(placed.order_id.clone()),
+            custom_id: placed.custom_id.clone(),
+            state: OrderState::New,
+            executed_qty: "0".to_string(),
+            avg_price: None,
+            update_time: 0,
+        };
+        inner.orders.insert(placed.order_id.clone(), order.clone());
+        drop(inner);
+        self.events.emit([OrderEvent { previous, order }]);
+    }
+
+    /// Apply an `orderUpdate` WebSocket push
+    pub fn apply_update(&self, update: &OrderUpdate) {
+        self.apply(TrackedOrder {
+            symbol: update.symbol.clone(),
+            order_id: Some(update.uuid.clone()),
+            custom_id: update.customer_id.clone().filter(|cid| !cid.is_empty()),
+            state: update.order_status.into(),
+            executed_qty: update.acc_amt.clone().unwrap_or_else(|| "0".to_string()),
+            avg_price: update.avg_price.clone(),
+            update_time: update.update_time,
+        });
+    }
+
+    /// Apply a raw WebSocket message, returning `false` if it isn't an `orderUpdate` push
+    pub fn apply_push(&self, message: &str) -> Result<bool> {
+        let value: serde_json::Value = serde_json::from_str(message)?;
+        if value.get("type").and_then(|t| t.as_str()) != Some("orderUpdate") {
+            return Ok(false);
+        }
+        let push: OrderUpdatePush = serde_json::from_value(value)?;
+        self.apply_update(&push.order_update);
+        Ok(true)
+    }
+
+    /// Apply an order snapshot from `order_info`, `open_orders` or `order_history`
+    pub fn apply_snapshot(&self, info: &OrderInfo) {
+        let avg_price = match (
+            info.executed_qty.parse::<f64>(),
+            info.cummulative_quote_qty.as_deref().map(str::parse::<f64>),
+        ) {
+            (Ok(qty), Some(Ok(quote))) if qty > 0.0 => Some((quote / qty).to_string()),
+            _ => None,
+        };
+
+        self.apply(TrackedOrder {
+            symbol: info.symbol.clone(),
+            order_id: Some(info.order_id.clone()),
+            custom_id: info.client_order_id.clone().filter(|cid| !cid.is_empty()),
+            state: info.status.into(),
+            executed_qty: info.executed_qty.clone(),
+            avg_price,
+            update_time: info.update_time.max(info.time),
+        });
+    }
+
+    /// Look up an order by exchange order ID
+    pub fn get(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.lock().orders.get(order_id).cloned()
+    }
+
+    /// Look up an order by client order ID
+    pub fn get_by_custom_id(&self, custom_id: &str) -> Option<TrackedOrder> {
+        let mut inner = self.lock();
+        inner.expire_pending(self.pending_ttl);
+        inner
+            .orders
+            .values()
+            .find(|o| o.custom_id.as_deref() == Some(custom_id))
+            .or_else(|| inner.pending.get(custom_id).map(|p| &p.order))
+            .cloned()
+    }
+
+    /// All orders that are not in a final state, optionally filtered by symbol
+    pub fn open_orders(&self, symbol: Option<&str>) -> Vec<TrackedOrder> {
+        let mut inner = self.lock();
+        inner.expire_pending(self.pending_ttl);
+        inner
+            .orders
+            .values()
+            .chain(inner.pending.values().map(|p| &p.order))
+            .filter(|o| !o.state.is_final())
+            .filter(|o| symbol.is_none_or(|s| o.symbol == s))
+            .cloned()
+            .collect()
+    }
+
+    /// Reconcile tracked orders for `symbol` against the exchange
+    ///
+    /// Applies every open order reported by `open_orders`, then queries
+    /// `order_info` for each locally open order the exchange no longer lists.
+    pub fn reconcile(&self, spot: &Spot, symbol: &str) -> Result<()> {
+        let mut seen = Vec::new();
+        let mut page = 1;
+        loop {
+            let text = spot.open_orders(symbol, page, RECONCILE_PAGE_LENGTH)?;
+            let orders = parse_response::<OrderList>(&text)?.into_orders();
+            let count = orders.len();
+            for info in &orders {
+                self.apply_snapshot(info);
+                seen.push(info.order_id.clone());
+            }
+            if count < RECONCILE_PAGE_LENGTH as usize {
+                break;
+            }
+            page += 1;
+        }
+
+        for order_id in self.missing_orders(symbol, &seen) {
+            let text = spot.order_info(symbol, Some(&order_id), None)?;
+            self.apply_snapshot(&parse_response::<OrderInfo>(&text)?);
+        }
+        Ok(())
+    }
+
+    /// Reconcile tracked orders for `symbol` against the exchange (async)
+    ///
+    /// See [`OrderManager::reconcile`].
+    pub async fn reconcile_async(&self, spot: &AsyncSpot, symbol: &str) -> Result<()> {
+        let mut seen = Vec::new();
+        let mut page = 1;
+        loop {
+            let text = spot.open_orders(symbol, page, RECONCILE_PAGE_LENGTH).await?;
+            let orders = parse_response::<OrderList>(&text)?.into_orders();
+            let count = orders.len();
+            for info in &orders {
+                self.apply_snapshot(info);
+                seen.push(info.order_id.clone());
+            }
+            if count < RECONCILE_PAGE_LENGTH as usize {
+                break;
+            }
+            page += 1;
+        }
+
+        for order_id in self.missing_orders(symbol, &seen) {
+            let text = spot.order_info(symbol, Some(&order_id), None).await?;
+            self.apply_snapshot(&parse_response::<OrderInfo>(&text)?);
+        }
+        Ok(())
+    }
+
+    /// Locally open orders for `symbol` that are absent from the exchange's open list
+    fn missing_orders(&self, symbol: &str, seen: &[String]) -> Vec<String> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && !o.state.is_final())
+            .filter_map(|o| o.order_id.clone())
+            .filter(|id| !seen.contains(id))
+            .collect()
+    }
+
+    fn apply(&self, incoming: TrackedOrder) {
+        let mut inner = self.lock();
+        let order_id = match incoming.order_id.clone() {
+            Some(id) => id,
+            None => return,
+        };
+        if inner.dropped_ids.contains(&order_id) {
+            return;
+        }
+
+        let existing = inner.orders.remove(&order_id).or_else(|| {
+            let cid = incoming.custom_id.as_ref()?;
+            inner.pending.remove(cid).map(|p| p.order)
+        });
+
+        let (previous, merged) = match existing {
+            // Only live orders start being tracked; a finished one may have been dropped already
+            None if incoming.state.is_final() => return,
+            None => (OrderState::Submitted, incoming),
+            Some(current) => {
+                // Final states are sticky and stale snapshots never roll an order back
+                if current.state.is_final() || incoming.update_time < current.update_time {
+                    inner.orders.insert(order_id, current);
+                    return;
+                }
+                let unchanged = current.state == incoming.state
+                    && current.executed_qty == incoming.executed_qty
+                    && current.order_id.is_some();
+                let merged = TrackedOrder {
+                    custom_id: incoming.custom_id.or(current.custom_id.clone()),
+                    avg_price: incoming.avg_price.or(current.avg_price.clone()),
+                    ..incoming
+                };
+                if unchanged {
+                    inner.orders.insert(order_id, merged);
+                    return;
+                }
+                (current.state, merged)
+            }
+        };
+
+        inner.orders.insert(order_id.clone(), merged.clone());
+        if merged.state.is_final() {
+            inner.finished.push_back(order_id);
+            while inner.finished.len() > self.final_retention {
+                if let Some(oldest) = inner.finished.pop_front() {
+                    inner.orders.remove(&oldest);
+                    inner.drop_finished(oldest, self.final_retention);
+                }
+            }
+        }
+        drop(inner);
+        self.events.emit([OrderEvent { previous, order: merged }]);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(status: OrderStatus, acc_amt: &str, update_time: i64) -> OrderUpdate {
+        OrderUpdate {
+            uuid: "oid".into(),
+            customer_id: Some("cid".into()),
+            symbol: "lbk_usdt".into(),
+            order_type: "buy".into(),
+            tx_uuid: None,
+            amount: None,
+            volume_price: None,
+            role: None,
+            price: None,
+            order_price: Some("0.01".into()),
+            order_amt: Some("100".into()),
+            avg_price: None,
+            acc_amt: Some(acc_amt.into()),
+            remain_amt: None,
+            order_status: status,
+            update_time,
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_transitions() {
+        let manager = OrderManager::new();
+        let events = manager.subscribe();
+
+        manager.record_submitted("lbk_usdt", "cid");
+        manager.record_placed(&PlacedOrder {
+            order_id: "oid".into(),
+            symbol: "lbk_usdt".into(),
+            custom_id: Some("cid".into()),
+        });
+        manager.apply_update(&update(OrderStatus::PartiallyFilled, "40", 1000));
+        manager.apply_update(&update(OrderStatus::Filled, "100", 2000));
+
+        let states: Vec<_> = events.try_iter().map(|e| (e.previous, e.order.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                (OrderState::Submitted, OrderState::New),
+                (OrderState::New, OrderState::PartiallyFilled),
+                (OrderState::PartiallyFilled, OrderState::Filled),
+            ]
+        );
+        assert!(manager.open_orders(None).is_empty());
+        assert_eq!(manager.get_by_custom_id("cid").unwrap().executed_qty, "100");
+    }
+
+    #[test]
+    fn test_stale_and_final_updates_ignored() {
+        let manager = OrderManager::new();
+        manager.apply_update(&update(OrderStatus::PartiallyFilled, "40", 2000));
+        manager.apply_update(&update(OrderStatus::New, "0", 1000));
+        assert_eq!(manager.get("oid").unwrap().state, OrderState::PartiallyFilled);
+
+        manager.apply_update(&update(OrderStatus::PartiallyFilledCancelled, "40", 3000));
+        manager.apply_update(&update(OrderStatus::PartiallyFilled, "50", 4000));
+        assert_eq!(manager.get("oid").unwrap().state, OrderState::Cancelled);
+    }
+
+    #[test]
+    fn test_callbacks_run_unlocked_and_final_orders_evicted() {
+        let manager = OrderManager::new().with_final_retention(1);
+        let reader = manager.clone();
+        let (tx, seen) = mpsc::channel();
+        // Reading the manager from a callback would deadlock if it ran under the lock
+        manager.on_event(move |event| {
+            let _ = tx.send(reader.get(event.order.order_id.as_deref().unwrap()).map(|o| o.state));
+        });
+
+        manager.apply_update(&update(OrderStatus::New, "0", 500));
+        manager.apply_update(&update(OrderStatus::Filled, "100", 1000));
+        assert_eq!(seen.try_iter().collect::<Vec<_>>(), vec![Some(OrderState::New), Some(OrderState::Filled)]);
+
+        let mut other = update(OrderStatus::New, "0", 500);
+        other.uuid = "oid2".into();
+        manager.apply_update(&other);
+        other.order_status = OrderStatus::Cancelled;
+        other.update_time = 1000;
+        manager.apply_update(&other);
+        assert!(manager.get("oid").is_none());
+        assert_eq!(manager.forget("oid2").unwrap().state, OrderState::Cancelled);
+        assert!(manager.get("oid2").is_none());
+    }
+
+    #[test]
+    fn test_late_updates_for_dropped_orders_ignored() {
+        let manager = OrderManager::new().with_final_retention(1);
+        let events = manager.subscribe();
+        manager.apply_update(&update(OrderStatus::New, "0", 500));
+        manager.apply_update(&update(OrderStatus::Filled, "100", 1000));
+        let mut other = update(OrderStatus::New, "0", 500);
+        other.uuid = "oid2".into();
+        manager.apply_update(&other);
+        other.order_status = OrderStatus::Cancelled;
+        manager.apply_update(&other);
+        assert_eq!(events.try_iter().count(), 4);
+        assert!(manager.get("oid").is_none());
+
+        // A delayed push for the evicted order doesn't bring it back as live
+        manager.apply_update(&update(OrderStatus::PartiallyFilled, "40", 800));
+        manager.apply_update(&update(OrderStatus::Filled, "100", 1000));
+        // Nor does a final update for an order that was never tracked
+        let mut unknown = update(OrderStatus::Filled, "100", 1000);
+        unknown.uuid = "oid3".into();
+        manager.apply_update(&unknown);
+        assert!(manager.get("oid").is_none() && manager.get("oid3").is_none());
+        assert!(manager.open_orders(None).is_empty());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_unacknowledged_submissions_expire() {
+        let manager = OrderManager::new().with_pending_ttl(Duration::ZERO);
+        manager.record_submitted("lbk_usdt", "cid");
+        assert!(manager.open_orders(None).is_empty());
+        assert!(manager.get_by_custom_id("cid").is_none());
+
+        let manager = OrderManager::new();
+        manager.record_submitted("lbk_usdt", "cid");
+        assert_eq!(manager.open_orders(Some("lbk_usdt"))[0].state, OrderState::Submitted);
+    }
+
+    #[test]
+    fn test_apply_push() {
+        let manager = OrderManager::new();
+        let push = r#"{"orderUpdate":{"uuid":"oid","symbol":"eth_btc","type":"buy","orderStatus":1,"accAmt":"0.001","updateTime":1561704577786},"pair":"eth_btc","type":"orderUpdate","SERVER":"V2"}"#;
+
+        assert!(manager.apply_push(push).unwrap());
+        assert!(!manager.apply_push(r#"{"action":"ping","ping":"x"}"#).unwrap());
+        assert_eq!(manager.get("oid").unwrap().state, OrderState::PartiallyFilled);
+    }
+}
@@ -0,0 +1,97 @@
+//! Requests behind [`Spot`](super::Spot) and [`AsyncSpot`](super::AsyncSpot)
+
+use crate::api::{API, Spot};
+use crate::model::AccountInfo;
+use crate::request::Request;
+
+fn order(
+    endpoint: Spot,
+    symbol: &str,
+    type_: &str,
+    price: Option<&str>,
+    amount: Option<&str>,
+    custom_id: Option<&str>,
+    window: Option<u64>,
+) -> Request {
+    Request::signed(API::Spot(endpoint))
+        .param("symbol", symbol)
+        .param("type", type_)
+        .param_opt("price", price)
+        .param_opt("amount", amount)
+        .param_opt("custom_id", custom_id)
+        .param_opt("window", window)
+}
+
+pub fn create_order_test(
+    symbol: &str,
+    type_: &str,
+    price: Option<&str>,
+    amount: Option<&str>,
+    custom_id: Option<&str>,
+    window: Option<u64>,
+) -> Request {
+    order(Spot::OrderTest, symbol, type_, price, amount, custom_id, window)
+}
+
+pub fn create_order(
+    symbol: &str,
+    type_: &str,
+    price: Option<&str>,
+    amount: Option<&str>,
+    custom_id: Option<&str>,
+    window: Option<u64>,
+) -> Request {
+    order(Spot::CreateOrder, symbol, type_, price, amount, custom_id, window)
+}
+
+pub fn cancel_order(symbol: &str, order_id: Option<&str>, orig_client_order_id: Option<&str>) -> Request {
+    Request::signed(API::Spot(Spot::CancelOrder))
+        .param("symbol", symbol)
+        .param_opt("orderId", order_id)
+        .param_opt("origClientOrderId", orig_client_order_id)
+}
+
+pub fn cancel_order_by_symbol(symbol: &str) -> Request {
+    Request::signed(API::Spot(Spot::CancelOrderBySymbol)).param("symbol", symbol)
+}
+
+pub fn order_info(symbol: &str, order_id: Option<&str>, orig_client_order_id: Option<&str>) -> Request {
+    Request::signed(API::Spot(Spot::OrderInfo))
+        .param("symbol", symbol)
+        .param_opt("orderId", order_id)
+        .param_opt("origClientOrderId", orig_client_order_id)
+}
+
+pub fn open_orders(symbol: &str, current_page: u32, page_length: u32) -> Request {
+    Request::signed(API::Spot(Spot::OpenOrders))
+        .param("symbol", symbol)
+        .param("current_page", current_page)
+        .param("page_length", page_length)
+}
+
+pub fn order_history(symbol: &str, current_page: u32, page_length: u32, status: Option<&str>) -> Request {
+    Request::signed(API::Spot(Spot::OrderHistory))
+        .param("symbol", symbol)
+        .param("current_page", current_page)
+        .param("page_length", page_length)
+        .param_opt("status", status)
+}
+
+pub fn account_info() -> Request<AccountInfo> {
+    Request::signed(API::Spot(Spot::AccountInfo)).parse()
+}
+
+pub fn transaction_history(
+    symbol: &str,
+    start_time: Option<&str>,
+    end_time: Option<&str>,
+    from_id: Option<&str>,
+    limit: Option<u32>,
+) -> Request {
+    Request::signed(API::Spot(Spot::TransactionHistory))
+        .param("symbol", symbol)
+        .param_opt("startTime", start_time)
+        .param_opt("endTime", end_time)
+        .param_opt("fromId", from_id)
+        .param_opt("limit", limit)
+}
@@ -1,4 +1,6 @@
 use crate::errors::Result;
+use crate::model::ApiResponse;
+use serde::de::DeserializeOwned;
 use std::collections::BTreeMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
@@ -50,22 +52,9 @@ pub fn build_signed_request(
     Ok(query_string)
 }
 
-/// Sign the request parameters using either RSA or HmacSHA256 (for LBank API)
-///
-/// According to LBank API documentation:
-/// For both RSA and HmacSHA256:
-/// 1. Sort parameters and create parameter string
-/// 2. Calculate MD5 hash of parameter string (uppercase hex)
-///
-/// For RSA:
-/// 3. Sign the MD5 hash using RSA private key with SHA256
-/// 4. Base64 encode the signature
-///
-/// For HmacSHA256:
-/// 3. HMAC-SHA256 hash the MD5 string using secret key
-/// 4. Convert to uppercase hex
-fn sign_request(parameters: &BTreeMap<String, String>, secret_key: &str) -> Result<String> {
-    // Step 1: Sort parameters and create sign string (LBank format)
+/// MD5 digest (uppercase hex) of the sorted parameters, which is what gets signed
+pub(crate) fn signature_digest(parameters: &BTreeMap<String, String>) -> String {
+    // Sort parameters and create sign string (LBank format)
     let mut sign_str = String::new();
     for (key, value) in parameters.iter() {
         if key != "sign" {
@@ -81,11 +70,28 @@ fn sign_request(parameters: &BTreeMap<String, String>, secret_key: &str) -> Resu
         sign_str.pop();
     }
 
-    // Step 2: Calculate MD5 hash and convert to uppercase hex
+    // Calculate MD5 hash and convert to uppercase hex
     let mut hasher = Md5::new();
     hasher.update(sign_str.as_bytes());
-    let result = hasher.finalize();
-    let md5_hex = format!("{:X}", result);
+    format!("{:X}", hasher.finalize())
+}
+
+/// Sign the request parameters using either RSA or HmacSHA256 (for LBank API)
+///
+/// According to LBank API documentation:
+/// For both RSA and HmacSHA256:
+/// 1. Sort parameters and create parameter string
+/// 2. Calculate MD5 hash of parameter string (uppercase hex)
+///
+/// For RSA:
+/// 3. Sign the MD5 hash using RSA private key with SHA256
+/// 4. Base64 encode the signature
+///
+/// For HmacSHA256:
+/// 3. HMAC-SHA256 hash the MD5 string using secret key
+/// 4. Convert to uppercase hex
+fn sign_request(parameters: &BTreeMap<String, String>, secret_key: &str) -> Result<String> {
+    let md5_hex = signature_digest(parameters);
 
     // Check signature method from parameters
     let signature_method = parameters.get("signature_method")
@@ -159,6 +165,28 @@ pub fn get_timestamp() -> u64 {
         .as_millis() as u64
 }
 
+/// Format a millisecond timestamp as `yyyy-MM-dd HH:mm:ss` in UTC+8 (LBank server time)
+pub fn format_utc8(timestamp_ms: u64) -> String {
+    let secs = timestamp_ms / 1000 + 8 * 60 * 60;
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
+
 /// Check if start time is valid (less than current time)
 pub fn is_start_time_valid(start_time: &u64) -> bool {
     let current_time = get_timestamp();
@@ -170,6 +198,23 @@ pub fn uuid_spot() -> String {
     Uuid::new_v4().to_string().replace("-", "")
 }
 
+/// Generate a client order ID (`custom_id`) with the given prefix
+pub fn client_order_id(prefix: &str) -> String {
+    format!("{}{}", prefix, uuid_spot())
+}
+
+/// Parse a raw LBank response body and unwrap its `data` payload
+pub fn parse_response<T: DeserializeOwned>(text: &str) -> Result<T> {
+    let response: ApiResponse<T> = serde_json::from_str(text)?;
+    response.into_result()
+}
+
+/// Check a raw LBank response body for an error envelope, ignoring its payload
+pub fn check_response(text: &str) -> Result<()> {
+    let response: ApiResponse<serde_json::Value> = serde_json::from_str(text)?;
+    response.into_data().map(|_| ())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,6 +241,21 @@ mod tests {
         assert!(!uuid.contains('-'));
     }
 
+    #[test]
+    fn test_client_order_id() {
+        let id = client_order_id("bot1-");
+        assert!(id.starts_with("bot1-"));
+        assert_eq!(id.len(), 5 + 32);
+        assert_ne!(id, client_order_id("bot1-"));
+    }
+
+    #[test]
+    fn test_format_utc8() {
+        assert_eq!(format_utc8(0), "1970-01-01 08:00:00");
+        assert_eq!(format_utc8(1_499_865_549_590), "2017-07-12 21:19:09");
+        assert_eq!(format_utc8(1_709_222_400_000), "2024-03-01 00:00:00");
+    }
+
     #[test]
     fn test_build_query_string() {
         let mut params = BTreeMap::new();
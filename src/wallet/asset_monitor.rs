@@ -0,0 +1,328 @@
+use crate::common::{AsyncCommon, Common};
+use crate::errors::Result;
+use crate::events::EventHub;
+use crate::model::{AssetConfig, SystemStatus};
+use crate::util::parse_response;
+use crate::wallet::{AsyncWallet, Wallet};
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Arc, Mutex};
+use tokio::sync::mpsc as async_mpsc;
+
+/// Deposit and withdrawal availability of one coin on one chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkStatus {
+    pub can_deposit: bool,
+    pub can_withdraw: bool,
+}
+
+impl NetworkStatus {
+    const ENABLED: NetworkStatus = NetworkStatus {
+        can_deposit: true,
+        can_withdraw: true,
+    };
+}
+
+/// Exchange-wide transfer availability at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetSnapshot {
+    pub maintenance: bool,
+    /// Keyed by (coin, chain)
+    pub networks: BTreeMap<(String, String), NetworkStatus>,
+}
+
+impl AssetSnapshot {
+    /// Snapshot from `Wallet::system_status` and `Common::asset_configs`
+    ///
+    /// Configs without a chain are keyed by the coin itself.
+    pub fn new(status: &SystemStatus, configs: &[AssetConfig]) -> Self {
+        let networks = configs
+            .iter()
+            .map(|config| {
+                let chain = config.chain_name.clone().unwrap_or_else(|| config.asset_code.clone());
+                let status = NetworkStatus {
+                    can_deposit: config.can_deposit,
+                    can_withdraw: config.can_draw,
+                };
+                ((config.asset_code.clone(), chain), status)
+            })
+            .collect();
+        AssetSnapshot {
+            maintenance: status.is_maintenance(),
+            networks,
+        }
+    }
+
+    /// Events leading from `self` to `next`
+    pub fn diff(&self, next: &AssetSnapshot) -> Vec<AssetEvent> {
+        let mut events = Vec::new();
+        match (self.maintenance, next.maintenance) {
+            (false, true) => events.push(AssetEvent::SystemMaintenance),
+            (true, false) => events.push(AssetEvent::SystemRestored),
+            _ => {}
+        }
+
+        for (key, status) in &next.networks {
+            let (coin, network) = key;
+            let Some(before) = self.networks.get(key) else {
+                events.push(AssetEvent::NetworkAdded {
+                    coin: coin.clone(),
+                    network: network.clone(),
+                });
+                // A new chain that starts out suspended is reported as such
+                events.extend(status_events(coin, network, &NetworkStatus::ENABLED, status));
+                continue;
+            };
+            events.extend(status_events(coin, network, before, status));
+        }
+        for key @ (coin, network) in self.networks.keys() {
+            if !next.networks.contains_key(key) {
+                events.push(AssetEvent::NetworkRemoved {
+                    coin: coin.clone(),
+                    network: network.clone(),
+                });
+            }
+        }
+        events
+    }
+
+    /// Whether withdrawals of `coin` on `network` are currently possible
+    pub fn can_withdraw(&self, coin: &str, network: &str) -> bool {
+        !self.maintenance
+            && self
+                .networks
+                .get(&(coin.to_string(), network.to_string()))
+                .is_some_and(|status| status.can_withdraw)
+    }
+}
+
+fn status_events(coin: &str, network: &str, before: &NetworkStatus, after: &NetworkStatus) -> Vec<AssetEvent> {
+    let (coin, network) = (coin.to_string(), network.to_string());
+    let mut events = Vec::new();
+    match (before.can_deposit, after.can_deposit) {
+        (true, false) => events.push(AssetEvent::DepositSuspended {
+            coin: coin.clone(),
+            network: network.clone(),
+        }),
+        (false, true) => events.push(AssetEvent::DepositResumed {
+            coin: coin.clone(),
+            network: network.clone(),
+        }),
+        _ => {}
+    }
+    match (before.can_withdraw, after.can_withdraw) {
+        (true, false) => events.push(AssetEvent::WithdrawSuspended { coin, network }),
+        (false, true) => events.push(AssetEvent::WithdrawResumed { coin, network }),
+        _ => {}
+    }
+    events
+}
+
+/// A change in exchange or network availability
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetEvent {
+    /// `system_status` reports maintenance
+    SystemMaintenance,
+    /// Maintenance is over
+    SystemRestored,
+    DepositSuspended { coin: String, network: String },
+    DepositResumed { coin: String, network: String },
+    WithdrawSuspended { coin: String, network: String },
+    WithdrawResumed { coin: String, network: String },
+    /// A chain appeared in `asset_configs`
+    NetworkAdded { coin: String, network: String },
+    /// A chain disappeared from `asset_configs`
+    NetworkRemoved { coin: String, network: String },
+}
+
+
+/// Watches `system_status` and `asset_configs` for availability changes
+///
+/// Each `poll` takes a fresh [`AssetSnapshot`] and emits the events that
+/// separate it from the previous one. The first poll compares against an
+/// exchange with everything enabled, so maintenance and suspensions already
+/// in place are reported, but existing chains don't count as added. Clones
+/// share the same state.
+#[derive(Clone, Default)]
+pub struct AssetMonitor {
+    snapshot: Arc<Mutex<Option<AssetSnapshot>>>,
+    events: EventHub<AssetEvent>,
+}
+
+impl AssetMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback invoked for every event
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&AssetEvent) + Send + Sync + 'static,
+    {
+        self.events.on_event(callback);
+    }
+
+    /// Subscribe to events through a blocking channel
+    pub fn subscribe(&self) -> mpsc::Receiver<AssetEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to events through an async channel
+    pub fn subscribe_async(&self) -> async_mpsc::UnboundedReceiver<AssetEvent> {
+        self.events.subscribe_async()
+    }
+
+    /// The latest snapshot, `None` before the first poll
+    pub fn snapshot(&self) -> Option<AssetSnapshot> {
+        self.lock().clone()
+    }
+
+    /// Replace the current snapshot, emitting and returning the differences
+    pub fn update(&self, snapshot: AssetSnapshot) -> Vec<AssetEvent> {
+        let mut current = self.lock();
+        let events = match &*current {
+            Some(previous) => previous.diff(&snapshot),
+            None => {
+                let baseline = AssetSnapshot {
+                    maintenance: false,
+                    networks: snapshot
+                        .networks
+                        .keys()
+                        .map(|key| (key.clone(), NetworkStatus::ENABLED))
+                        .collect(),
+                };
+                baseline.diff(&snapshot)
+            }
+        };
+        *current = Some(snapshot);
+        drop(current);
+        self.events.emit(events.clone());
+        events
+    }
+
+    /// Fetch `system_status` and `asset_configs` and apply them
+    pub fn poll(&self, common: &Common, wallet: &Wallet) -> Result<Vec<AssetEvent>> {
+        let status: SystemStatus = parse_response(&wallet.system_status()?)?;
+        let configs: Vec<AssetConfig> = parse_response(&common.asset_configs()?)?;
+        Ok(self.update(AssetSnapshot::new(&status, &configs)))
+    }
+
+    /// Fetch `system_status` and `asset_configs` and apply them (async)
+    pub async fn poll_async(&self, common: &AsyncCommon, wallet: &AsyncWallet) -> Result<Vec<AssetEvent>> {
+        let status: SystemStatus = parse_response(&wallet.system_status().await?)?;
+        let configs: Vec<AssetConfig> = parse_response(&common.asset_configs().await?)?;
+        Ok(self.update(AssetSnapshot::new(&status, &configs)))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<AssetSnapshot>> {
+        self.snapshot.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(maintenance: bool, networks: &[(&str, &str, bool, bool)]) -> AssetSnapshot {
+        AssetSnapshot {
+            maintenance,
+            networks: networks
+                .iter()
+                .map(|&(coin, network, can_deposit, can_withdraw)| {
+                    ((coin.into(), network.into()), NetworkStatus { can_deposit, can_withdraw })
+                })
+                .collect(),
+        }
+    }
+
+    fn withdraw_suspended(coin: &str, network: &str) -> AssetEvent {
+        AssetEvent::WithdrawSuspended {
+            coin: coin.into(),
+            network: network.into(),
+        }
+    }
+
+    #[test]
+    fn test_one_chain_suspended() {
+        // `assetConfigs` example from the API docs, fees trimmed
+        let configs = r#"[
+            {"assetCode": "btc", "chainName": "btc", "canDraw": true, "canStationDraw": true, "canDeposit": true, "hasMemo": false,
+             "assetFee": {"type": 1, "feeCode": "btc", "scale": 4, "minAmt": "0.001", "feeAmt": "0.0002", "feeRate": "0"}},
+            {"assetCode": "btc", "chainName": "trc20", "canDraw": true, "canStationDraw": true, "canDeposit": true, "hasMemo": false,
+             "assetFee": {"type": 1, "feeCode": "btc", "scale": 4, "minAmt": "0.001", "feeAmt": "0.0001", "feeRate": "0"}}
+        ]"#;
+        let mut configs: Vec<AssetConfig> = serde_json::from_str(configs).unwrap();
+        let status = SystemStatus { status: "1".into() };
+        let fee = configs[1].asset_fee.as_ref().unwrap();
+        assert_eq!((fee.fee_type, fee.fee_amt.as_deref()), (Some(crate::model::FeeType::Fixed), Some("0.0001")));
+
+        let monitor = AssetMonitor::new();
+        assert!(monitor.update(AssetSnapshot::new(&status, &configs)).is_empty());
+        assert_eq!(monitor.snapshot().unwrap().networks.len(), 2);
+
+        configs[1].can_draw = false;
+        let events = monitor.update(AssetSnapshot::new(&status, &configs));
+        assert_eq!(events, vec![withdraw_suspended("btc", "trc20")]);
+        assert!(monitor.snapshot().unwrap().can_withdraw("btc", "btc"));
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let monitor = AssetMonitor::new();
+        // Callbacks run unlocked, so they can read the new snapshot
+        let (reader, (tx, maintenance)) = (monitor.clone(), mpsc::channel());
+        monitor.on_event(move |_| tx.send(reader.snapshot().unwrap().maintenance).unwrap());
+        let events = monitor.update(snapshot(false, &[("usdt", "trc20", true, true), ("usdt", "erc20", true, false)]));
+        assert_eq!(events, vec![withdraw_suspended("usdt", "erc20")]);
+
+        let events = monitor.update(snapshot(true, &[("usdt", "trc20", false, false), ("eth", "eth", true, true)]));
+        assert_eq!(
+            events,
+            vec![
+                AssetEvent::SystemMaintenance,
+                AssetEvent::NetworkAdded { coin: "eth".into(), network: "eth".into() },
+                AssetEvent::DepositSuspended { coin: "usdt".into(), network: "trc20".into() },
+                withdraw_suspended("usdt", "trc20"),
+                AssetEvent::NetworkRemoved { coin: "usdt".into(), network: "erc20".into() },
+            ]
+        );
+        assert!(!monitor.snapshot().unwrap().can_withdraw("eth", "eth"));
+
+        let events = monitor.update(snapshot(false, &[("usdt", "trc20", false, true), ("eth", "eth", true, true)]));
+        assert_eq!(
+            events,
+            vec![
+                AssetEvent::SystemRestored,
+                AssetEvent::WithdrawResumed { coin: "usdt".into(), network: "trc20".into() },
+            ]
+        );
+        let seen: Vec<bool> = maintenance.try_iter().collect();
+        assert_eq!(seen, vec![false, true, true, true, true, true, false, false]);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[test]
+    fn test_poll_reports_changes() {
+        let server = MockServer::start_for_tests();
+        server.set_balance("usdt", 1.0);
+        let client = server.test_client();
+        let (common, wallet) = (Common { client: client.clone() }, Wallet { client });
+        let monitor = AssetMonitor::new();
+        assert!(monitor.poll(&common, &wallet).unwrap().is_empty());
+
+        server.set_maintenance(true);
+        server.set_transfers_enabled("usdt", true, false);
+        let events = monitor.poll(&common, &wallet).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                AssetEvent::SystemMaintenance,
+                AssetEvent::WithdrawSuspended { coin: "usdt".into(), network: "usdt".into() },
+            ]
+        );
+    }
+}
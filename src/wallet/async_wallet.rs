@@ -1,8 +1,14 @@
 use crate::client::AsyncClient;
-use crate::api::{API, Wallet as WalletAPI};
+use crate::wallet::requests;
+use crate::wallet::{cheapest_withdrawal, quote_withdrawal, WithdrawalQuote};
 use crate::errors::Result;
-use crate::util::build_signed_request;
+use crate::model::{
+    AssetDetail, CoinInfo, DepositAddress, DepositRecord, InternalTransfer, RecordList, WithdrawRecord, WithdrawResult,
+};
+use crate::pagination::{history_stream, PageCursor, Paginator, WALLET_WINDOW_MS};
+use futures::stream::BoxStream;
 use std::collections::BTreeMap;
+use crate::wallet::wallet::{check_internal_transfer, deposit_key, withdraw_key};
 
 /// Wallet API client for asynchronous operations
 #[derive(Clone)]
@@ -14,25 +20,21 @@ impl AsyncWallet {
     /// Get system status (0: maintenance, 1: normal)
     /// Note: This endpoint doesn't require authentication according to docs
     pub async fn system_status(&self) -> Result<String> {
-        self.client.post(API::Wallet(WalletAPI::SystemStatus), None).await
+        self.client.execute(requests::system_status()).await
     }
 
     /// Get all coin balances and multi-chain information for user
     /// Requires authentication
-    pub async fn user_info(&self) -> Result<String> {
-        let parameters: BTreeMap<String, String> = BTreeMap::new();
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::UserInfo), Some(signed_request)).await
+    pub async fn user_info(&self) -> Result<Vec<CoinInfo>> {
+        self.client.execute(requests::user_info()).await
     }
 
-    /// Submit withdrawal request (supports multi-chain)
+    /// Submit an on-chain withdrawal request (supports multi-chain)
+    ///
+    /// Use `transfer_internal` to send to another LBank account.
     /// 
     /// # Parameters
-    /// * `address` - Withdrawal address (or transfer account if type=1)
+    /// * `address` - Withdrawal address
     /// * `coin` - Currency code
     /// * `amount` - Withdrawal amount
     /// * `fee` - Withdrawal fee
@@ -41,7 +43,6 @@ impl AsyncWallet {
     /// * `mark` - Optional withdrawal notes
     /// * `name` - Optional address remark (adds to address book)
     /// * `withdraw_order_id` - Optional custom withdrawal ID
-    /// * `type_` - Optional type (1 for intra-site transfer)
     pub async fn withdraw(
         &self,
         address: &str,
@@ -53,39 +54,45 @@ impl AsyncWallet {
         mark: Option<&str>,
         name: Option<&str>,
         withdraw_order_id: Option<&str>,
-        type_: Option<&str>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("address".to_string(), address.to_string());
-        parameters.insert("coin".to_string(), coin.to_string());
-        parameters.insert("amount".to_string(), amount.to_string());
-        parameters.insert("fee".to_string(), fee.to_string());
-
-        if let Some(network) = network_name {
-            parameters.insert("networkName".to_string(), network.to_string());
-        }
-        if let Some(m) = memo {
-            parameters.insert("memo".to_string(), m.to_string());
-        }
-        if let Some(mk) = mark {
-            parameters.insert("mark".to_string(), mk.to_string());
-        }
-        if let Some(n) = name {
-            parameters.insert("name".to_string(), n.to_string());
-        }
-        if let Some(wid) = withdraw_order_id {
-            parameters.insert("withdrawOrderId".to_string(), wid.to_string());
-        }
-        if let Some(t) = type_ {
-            parameters.insert("type".to_string(), t.to_string());
-        }
+    ) -> Result<WithdrawResult> {
+        self.client.execute(requests::withdraw(
+            address,
+            coin,
+            amount,
+            fee,
+            network_name,
+            memo,
+            mark,
+            name,
+            withdraw_order_id,
+        )).await
+    }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::Withdraw), Some(signed_request)).await
+    /// Transfer to another LBank account (intra-site, no fee)
+    ///
+    /// Fails without sending anything when `amount` is below the coin's
+    /// `minLimit` from `user_info`.
+    ///
+    /// # Parameters
+    /// * `account` - Receiving LBank account
+    /// * `coin` - Currency code
+    /// * `amount` - Transfer amount
+    /// * `memo` - Optional memo
+    /// * `mark` - Optional transfer notes
+    /// * `withdraw_order_id` - Optional custom withdrawal ID
+    pub async fn transfer_internal(
+        &self,
+        account: &str,
+        coin: &str,
+        amount: &str,
+        memo: Option<&str>,
+        mark: Option<&str>,
+        withdraw_order_id: Option<&str>,
+    ) -> Result<InternalTransfer> {
+        check_internal_transfer(&self.user_info().await?, account, coin, amount)?;
+        self.client
+            .execute(requests::transfer_internal(account, coin, amount, memo, mark, withdraw_order_id))
+            .await
     }
 
     /// Get deposit history with optional filters
@@ -101,28 +108,11 @@ impl AsyncWallet {
         coin: Option<&str>,
         start_time: Option<u64>,
         end_time: Option<u64>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-
-        if let Some(s) = status {
-            parameters.insert("status".to_string(), s.to_string());
-        }
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
-        }
-        if let Some(st) = start_time {
-            parameters.insert("startTime".to_string(), st.to_string());
-        }
-        if let Some(et) = end_time {
-            parameters.insert("endTime".to_string(), et.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::DepositHistory), Some(signed_request)).await
+    ) -> Result<Vec<DepositRecord>> {
+        self.client
+            .execute(requests::deposit_history(status, coin, start_time, end_time))
+            .await
+            .map(RecordList::into_records)
     }
 
     /// Get withdrawal history with optional filters
@@ -140,31 +130,11 @@ impl AsyncWallet {
         withdraw_order_id: Option<&str>,
         start_time: Option<u64>,
         end_time: Option<u64>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-
-        if let Some(s) = status {
-            parameters.insert("status".to_string(), s.to_string());
-        }
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
-        }
-        if let Some(wid) = withdraw_order_id {
-            parameters.insert("withdrawOrderId".to_string(), wid.to_string());
-        }
-        if let Some(st) = start_time {
-            parameters.insert("startTime".to_string(), st.to_string());
-        }
-        if let Some(et) = end_time {
-            parameters.insert("endTime".to_string(), et.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::WithdrawHistory), Some(signed_request)).await
+    ) -> Result<Vec<WithdrawRecord>> {
+        self.client
+            .execute(requests::withdraw_history(status, coin, withdraw_order_id, start_time, end_time))
+            .await
+            .map(RecordList::into_records)
     }
 
     /// Get deposit address for a specific coin and chain
@@ -172,39 +142,87 @@ impl AsyncWallet {
     /// # Parameters
     /// * `coin` - Currency code
     /// * `network_name` - Optional chain name
-    pub async fn deposit_address(&self, coin: &str, network_name: Option<&str>) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("coin".to_string(), coin.to_string());
-
-        if let Some(network) = network_name {
-            parameters.insert("networkName".to_string(), network.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::DepositAddress), Some(signed_request)).await
+    pub async fn deposit_address(&self, coin: &str, network_name: Option<&str>) -> Result<DepositAddress> {
+        self.client.execute(requests::deposit_address(coin, network_name)).await
     }
 
     /// Get asset details including withdrawal/deposit status and fees
     /// 
     /// # Parameters
     /// * `coin` - Optional currency filter
-    pub async fn asset_detail(&self, coin: Option<&str>) -> Result<String> {
-        let mut parameters = BTreeMap::new();
+    pub async fn asset_detail(&self, coin: Option<&str>) -> Result<BTreeMap<String, AssetDetail>> {
+        self.client.execute(requests::asset_detail(coin)).await
+    }
+
+    /// Fee and net amount of withdrawing `amount` of `coin` on `network`
+    /// (the default chain when `None`), from live `user_info` data
+    pub async fn withdrawal_quote(&self, coin: &str, network: Option<&str>, amount: f64) -> Result<WithdrawalQuote> {
+        quote_withdrawal(&self.user_info().await?, coin, network, amount)
+    }
+
+    /// The eligible network with the lowest fee for withdrawing `amount` of `coin`
+    pub async fn cheapest_withdrawal(&self, coin: &str, amount: f64) -> Result<WithdrawalQuote> {
+        cheapest_withdrawal(&self.user_info().await?, coin, amount)
+    }
 
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
-        }
+    /// Stream every deposit between `start_time` and `end_time`
+    ///
+    /// See [`Wallet::deposit_history_iter`](crate::wallet::Wallet::deposit_history_iter).
+    ///
+    /// # Parameters
+    /// * `status` - Optional deposit status filter
+    /// * `coin` - Optional currency filter
+    /// * `start_time` - Start time (timestamp in milliseconds)
+    /// * `end_time` - End time (timestamp in milliseconds)
+    pub fn deposit_history_stream(
+        &self,
+        status: Option<&str>,
+        coin: Option<&str>,
+        start_time: u64,
+        end_time: u64,
+    ) -> BoxStream<'_, Result<DepositRecord>> {
+        let paginator = Paginator::windows(start_time, end_time, WALLET_WINDOW_MS, None, deposit_key);
+        let status = status.map(str::to_string);
+        let coin = coin.map(str::to_string);
+        history_stream(paginator, move |cursor| {
+            let (status, coin) = (status.clone(), coin.clone());
+            async move {
+                let PageCursor::Window { start, end, .. } = cursor else {
+                    return Ok(Vec::new());
+                };
+                self.deposit_history(status.as_deref(), coin.as_deref(), Some(start), Some(end)).await
+            }
+        })
+    }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::AssetDetail), Some(signed_request)).await
+    /// Stream every withdrawal between `start_time` and `end_time`
+    ///
+    /// See [`Wallet::withdraw_history_iter`](crate::wallet::Wallet::withdraw_history_iter).
+    ///
+    /// # Parameters
+    /// * `status` - Optional withdrawal status filter
+    /// * `coin` - Optional currency filter
+    /// * `start_time` - Start time (timestamp in milliseconds)
+    /// * `end_time` - End time (timestamp in milliseconds)
+    pub fn withdraw_history_stream(
+        &self,
+        status: Option<&str>,
+        coin: Option<&str>,
+        start_time: u64,
+        end_time: u64,
+    ) -> BoxStream<'_, Result<WithdrawRecord>> {
+        let paginator = Paginator::windows(start_time, end_time, WALLET_WINDOW_MS, None, withdraw_key);
+        let status = status.map(str::to_string);
+        let coin = coin.map(str::to_string);
+        history_stream(paginator, move |cursor| {
+            let (status, coin) = (status.clone(), coin.clone());
+            async move {
+                let PageCursor::Window { start, end, .. } = cursor else {
+                    return Ok(Vec::new());
+                };
+                self.withdraw_history(status.as_deref(), coin.as_deref(), None, Some(start), Some(end)).await
+            }
+        })
     }
 }
 
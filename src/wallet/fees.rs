@@ -0,0 +1,181 @@
+use crate::errors::Result;
+use crate::model::{CoinInfo, FeeType, NetworkInfo};
+
+/// Decimal places fees are rounded up to when sent as the `fee` parameter
+const FEE_DECIMALS: i32 = 8;
+
+/// Fee and net amount of withdrawing `amount` on one network
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithdrawalQuote {
+    pub coin: String,
+    /// Chain name to pass as `networkName`
+    pub network: String,
+    pub amount: f64,
+    pub fee_type: FeeType,
+    /// Fee charged, in `fee_asset`
+    pub fee: f64,
+    pub fee_asset: String,
+    /// What the recipient gets: `amount` minus `fee` when the fee is charged
+    /// in the withdrawn coin, otherwise all of `amount`
+    pub net_amount: f64,
+}
+
+impl WithdrawalQuote {
+    /// Quote withdrawing `amount` of `coin` on `network`
+    ///
+    /// Fails if the network has withdrawals disabled, `amount` is below its
+    /// `withdrawMin`, or the fee would eat the whole amount.
+    pub fn new(coin: &CoinInfo, network: &NetworkInfo, amount: f64) -> Result<Self> {
+        if !network.withdraw_enable {
+            return Err(format!("withdrawals of {} on {} are disabled", coin.coin, network.name).into());
+        }
+        let min: f64 = network.withdraw_min.as_deref().unwrap_or("0").parse()?;
+        if amount < min {
+            return Err(format!("{} {} is below the {} minimum of {}", amount, coin.coin, network.name, min).into());
+        }
+
+        let fee = round_up(network.fee_for(amount)?);
+        let fee_asset = network
+            .fee_asset_code
+            .clone()
+            .filter(|asset| !asset.is_empty())
+            .unwrap_or_else(|| coin.coin.clone());
+        let net_amount = if fee_asset.eq_ignore_ascii_case(&coin.coin) {
+            amount - fee
+        } else {
+            amount
+        };
+        if net_amount <= 0.0 {
+            return Err(format!("the {} fee of {} exceeds the amount {}", network.name, fee, amount).into());
+        }
+
+        Ok(WithdrawalQuote {
+            coin: coin.coin.clone(),
+            network: network.name.clone(),
+            amount,
+            fee_type: network.fee_type.unwrap_or(FeeType::Fixed),
+            fee,
+            fee_asset,
+            net_amount,
+        })
+    }
+
+    /// Whether the fee is charged in the withdrawn coin
+    pub fn fee_in_coin(&self) -> bool {
+        self.fee_asset.eq_ignore_ascii_case(&self.coin)
+    }
+
+    /// `fee` formatted for the `fee` parameter of `Wallet::withdraw`
+    pub fn fee_param(&self) -> String {
+        let formatted = format!("{:.*}", FEE_DECIMALS as usize, self.fee);
+        formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Quote withdrawing `amount` of `coin` on `network` (its default chain when
+/// `None`), using `coins` from `Wallet::user_info`
+pub fn quote_withdrawal(coins: &[CoinInfo], coin: &str, network: Option<&str>, amount: f64) -> Result<WithdrawalQuote> {
+    let info = find_coin(coins, coin)?;
+    let chain = info.network(network).ok_or_else(|| {
+        format!("unknown network {} for {}", network.unwrap_or("(default)"), coin)
+    })?;
+    WithdrawalQuote::new(info, chain, amount)
+}
+
+/// Quote `amount` of `coin` on every eligible network, cheapest first
+///
+/// Networks charging the fee in the withdrawn coin are ranked by net amount;
+/// those charging it in another asset can't be compared without a price and
+/// come after them, ordered by fee. Ineligible networks are skipped.
+pub fn withdrawal_quotes(coins: &[CoinInfo], coin: &str, amount: f64) -> Result<Vec<WithdrawalQuote>> {
+    let info = find_coin(coins, coin)?;
+    let mut quotes: Vec<WithdrawalQuote> = info
+        .network_list
+        .iter()
+        .filter_map(|network| WithdrawalQuote::new(info, network, amount).ok())
+        .collect();
+    quotes.sort_by(|a, b| {
+        (!a.fee_in_coin(), a.fee)
+            .partial_cmp(&(!b.fee_in_coin(), b.fee))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(quotes)
+}
+
+/// The cheapest eligible network for withdrawing `amount` of `coin`
+///
+/// See [`withdrawal_quotes`] for the ranking.
+pub fn cheapest_withdrawal(coins: &[CoinInfo], coin: &str, amount: f64) -> Result<WithdrawalQuote> {
+    withdrawal_quotes(coins, coin, amount)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("no network can withdraw {} {}", amount, coin).into())
+}
+
+fn find_coin<'a>(coins: &'a [CoinInfo], coin: &str) -> Result<&'a CoinInfo> {
+    coins
+        .iter()
+        .find(|c| c.coin.eq_ignore_ascii_case(coin))
+        .ok_or_else(|| format!("unknown coin {}", coin).into())
+}
+
+/// Round up so the fee sent is never below what LBank computes
+fn round_up(fee: f64) -> f64 {
+    let scale = 10f64.powi(FEE_DECIMALS);
+    // Trim float noise first so exact fees aren't bumped by one unit
+    ((fee * scale * 1e6).round() / 1e6).ceil() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coins() -> Vec<CoinInfo> {
+        serde_json::from_str(
+            r#"[{"coin":"usdt","networkList":[
+                {"name":"erc20","isDefault":true,"withdrawEnable":true,"withdrawFee":"5","withdrawMin":"10","type":"1"},
+                {"name":"trc20","withdrawEnable":true,"withdrawFee":"1","withdrawFeeRate":"0.1","withdrawMin":"5","type":"3"},
+                {"name":"bep20","withdrawEnable":true,"withdrawFeeRate":"0.3","type":"2"},
+                {"name":"lbk","withdrawEnable":true,"withdrawFee":"0.5","type":"1","feeAssetCode":"lbk"},
+                {"name":"sol","withdrawEnable":false,"withdrawFee":"0"}
+            ]}]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fee_types() {
+        let coins = coins();
+        let fixed = quote_withdrawal(&coins, "usdt", None, 100.0).unwrap();
+        assert_eq!((fixed.network.as_str(), fixed.fee, fixed.net_amount), ("erc20", 5.0, 95.0));
+
+        let mixed = quote_withdrawal(&coins, "usdt", Some("trc20"), 100.0).unwrap();
+        assert_eq!(mixed.fee_type, FeeType::FixedPlusPercent);
+        assert_eq!(mixed.fee_param(), "1.1");
+
+        let percent = quote_withdrawal(&coins, "usdt", Some("bep20"), 100.0).unwrap();
+        assert_eq!(percent.fee_param(), "0.3");
+
+        let other_asset = quote_withdrawal(&coins, "usdt", Some("lbk"), 100.0).unwrap();
+        assert_eq!((other_asset.fee_asset.as_str(), other_asset.net_amount), ("lbk", 100.0));
+
+        assert!(quote_withdrawal(&coins, "usdt", Some("sol"), 100.0).is_err());
+        assert!(quote_withdrawal(&coins, "usdt", None, 8.0).is_err());
+    }
+
+    #[test]
+    fn test_cheapest_network() {
+        let coins = coins();
+        assert_eq!(cheapest_withdrawal(&coins, "usdt", 100.0).unwrap().network, "bep20");
+        // Percent fees grow with the amount
+        assert_eq!(cheapest_withdrawal(&coins, "usdt", 1000.0).unwrap().network, "trc20");
+
+        let networks: Vec<String> = withdrawal_quotes(&coins, "usdt", 6.0)
+            .unwrap()
+            .into_iter()
+            .map(|q| q.network)
+            .collect();
+        assert_eq!(networks, vec!["bep20", "trc20", "lbk"]);
+        assert!(cheapest_withdrawal(&coins, "btc", 1.0).is_err());
+    }
+}
@@ -1,6 +1,15 @@
 mod wallet;
 mod async_wallet;
+mod withdraw_guard;
+mod fees;
+mod transfer_tracker;
+mod asset_monitor;
+pub mod requests;
 
 pub use wallet::Wallet;
 pub use async_wallet::AsyncWallet;
+pub use withdraw_guard::{WithdrawGuard, WithdrawRequest};
+pub use transfer_tracker::{TrackedTransfer, TransferEvent, TransferKind, TransferState, TransferTracker};
+pub use asset_monitor::{AssetEvent, AssetMonitor, AssetSnapshot, NetworkStatus};
+pub use fees::{cheapest_withdrawal, quote_withdrawal, withdrawal_quotes, WithdrawalQuote};
 
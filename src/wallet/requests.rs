@@ -0,0 +1,103 @@
+//! Requests behind [`Wallet`](super::Wallet) and [`AsyncWallet`](super::AsyncWallet)
+
+use crate::api::{API, Wallet};
+use crate::model::{
+    AssetDetail, CoinInfo, DepositAddress, DepositRecord, InternalTransfer, RecordList, WithdrawRecord, WithdrawResult,
+};
+use crate::request::Request;
+use std::collections::BTreeMap;
+
+pub fn system_status() -> Request {
+    Request::post(API::Wallet(Wallet::SystemStatus))
+}
+
+pub fn user_info() -> Request<Vec<CoinInfo>> {
+    Request::signed(API::Wallet(Wallet::UserInfo)).parse()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    address: &str,
+    coin: &str,
+    amount: &str,
+    fee: &str,
+    network_name: Option<&str>,
+    memo: Option<&str>,
+    mark: Option<&str>,
+    name: Option<&str>,
+    withdraw_order_id: Option<&str>,
+) -> Request<WithdrawResult> {
+    Request::signed(API::Wallet(Wallet::Withdraw))
+        .param("address", address)
+        .param("coin", coin)
+        .param("amount", amount)
+        .param("fee", fee)
+        .param_opt("networkName", network_name)
+        .param_opt("memo", memo)
+        .param_opt("mark", mark)
+        .param_opt("name", name)
+        .param_opt("withdrawOrderId", withdraw_order_id)
+        .parse()
+}
+
+/// Withdrawal of `type` 1: `address` is the receiving LBank account and no fee is charged
+pub fn transfer_internal(
+    account: &str,
+    coin: &str,
+    amount: &str,
+    memo: Option<&str>,
+    mark: Option<&str>,
+    withdraw_order_id: Option<&str>,
+) -> Request<InternalTransfer> {
+    Request::signed(API::Wallet(Wallet::Withdraw))
+        .param("address", account)
+        .param("coin", coin)
+        .param("amount", amount)
+        .param("fee", 0)
+        .param("type", 1)
+        .param_opt("memo", memo)
+        .param_opt("mark", mark)
+        .param_opt("withdrawOrderId", withdraw_order_id)
+        .parse()
+}
+
+pub fn deposit_history(
+    status: Option<&str>,
+    coin: Option<&str>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+) -> Request<RecordList<DepositRecord>> {
+    Request::signed(API::Wallet(Wallet::DepositHistory))
+        .param_opt("status", status)
+        .param_opt("coin", coin)
+        .param_opt("startTime", start_time)
+        .param_opt("endTime", end_time)
+        .parse()
+}
+
+pub fn withdraw_history(
+    status: Option<&str>,
+    coin: Option<&str>,
+    withdraw_order_id: Option<&str>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+) -> Request<RecordList<WithdrawRecord>> {
+    Request::signed(API::Wallet(Wallet::WithdrawHistory))
+        .param_opt("status", status)
+        .param_opt("coin", coin)
+        .param_opt("withdrawOrderId", withdraw_order_id)
+        .param_opt("startTime", start_time)
+        .param_opt("endTime", end_time)
+        .parse()
+}
+
+pub fn deposit_address(coin: &str, network_name: Option<&str>) -> Request<DepositAddress> {
+    Request::signed(API::Wallet(Wallet::DepositAddress))
+        .param("coin", coin)
+        .param_opt("networkName", network_name)
+        .parse()
+}
+
+pub fn asset_detail(coin: Option<&str>) -> Request<BTreeMap<String, AssetDetail>> {
+    Request::signed(API::Wallet(Wallet::AssetDetail)).param_opt("coin", coin).parse()
+}
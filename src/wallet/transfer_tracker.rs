@@ -0,0 +1,495 @@
+use crate::errors::Result;
+use crate::events::EventHub;
+use crate::model::{
+    AssetUpdate, AssetUpdatePush, DepositRecord, DepositStatus, WithdrawRecord, WithdrawResult, WithdrawStatus,
+};
+use crate::util::get_timestamp;
+use crate::wallet::wallet::deposit_key;
+use crate::wallet::{AsyncWallet, Wallet};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc as async_mpsc;
+
+/// Whether a tracked transfer is a deposit or a withdrawal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferKind {
+    Deposit,
+    Withdrawal,
+}
+
+/// Lifecycle state of a tracked transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferState {
+    Pending,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl TransferState {
+    /// Whether the transfer can no longer change
+    pub fn is_final(&self) -> bool {
+        !matches!(self, TransferState::Pending)
+    }
+}
+
+impl From<DepositStatus> for TransferState {
+    fn from(status: DepositStatus) -> Self {
+        match status {
+            DepositStatus::Applying => TransferState::Pending,
+            DepositStatus::Success | DepositStatus::Transfer => TransferState::Completed,
+            DepositStatus::Failed => TransferState::Failed,
+            DepositStatus::Cancelled => TransferState::Cancelled,
+        }
+    }
+}
+
+impl From<WithdrawStatus> for TransferState {
+    fn from(status: WithdrawStatus) -> Self {
+        match status {
+            WithdrawStatus::Applying => TransferState::Pending,
+            WithdrawStatus::Completed => TransferState::Completed,
+            WithdrawStatus::Failed => TransferState::Failed,
+            WithdrawStatus::Cancelled => TransferState::Cancelled,
+        }
+    }
+}
+
+/// Local view of a deposit or withdrawal
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedTransfer {
+    pub kind: TransferKind,
+    /// Withdrawal ID, or a key built from the deposit's coin, txId, amount and time
+    pub id: String,
+    pub coin: String,
+    /// Empty until the first history record is seen for a withdrawal
+    pub amount: String,
+    pub network_name: Option<String>,
+    pub address: Option<String>,
+    pub withdraw_order_id: Option<String>,
+    /// On-chain transaction hash, once LBank reports one
+    pub tx_id: Option<String>,
+    pub state: TransferState,
+}
+
+/// Emitted by [`TransferTracker`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferEvent {
+    /// A new deposit appeared, or a withdrawal started being tracked
+    Detected(TrackedTransfer),
+    /// The state or txId of a transfer changed
+    Updated {
+        previous: TransferState,
+        transfer: TrackedTransfer,
+    },
+    /// Still pending after the tracker's `stuck_after`; sent once per transfer
+    Stuck(TrackedTransfer),
+    /// An `assetUpdate` push for a deposit or withdrawal
+    ///
+    /// Pushes don't say which transfer moved the balance; poll to find out.
+    BalanceChanged(AssetUpdate),
+}
+
+struct Entry {
+    transfer: TrackedTransfer,
+    tracked_at: Instant,
+    alerted: bool,
+}
+
+struct Inner {
+    transfers: HashMap<(TransferKind, String), Entry>,
+    deposit_coins: BTreeSet<String>,
+    /// History is polled from this time (milliseconds)
+    since: u64,
+}
+
+/// Follows deposits and withdrawals until they complete
+///
+/// Withdrawals are tracked from their `withdraw` response and deposits are
+/// picked up for watched coins. `poll` reads `withdraw_history` and
+/// `deposit_history` and emits an event for every new transfer, state change
+/// and newly reported txId, plus a `Stuck` alert for transfers still pending
+/// after `stuck_after`. `assetUpdate` pushes can be fed in with `apply_push`
+/// to learn when a poll is worth doing. Clones share the same state.
+#[derive(Clone)]
+pub struct TransferTracker {
+    inner: Arc<Mutex<Inner>>,
+    events: EventHub<TransferEvent>,
+    stuck_after: Duration,
+}
+
+impl TransferTracker {
+    /// Track transfers from now on, alerting on those pending longer than `stuck_after`
+    pub fn new(stuck_after: Duration) -> Self {
+        TransferTracker {
+            inner: Arc::new(Mutex::new(Inner {
+                transfers: HashMap::new(),
+                deposit_coins: BTreeSet::new(),
+                since: get_timestamp(),
+            })),
+            events: EventHub::new(),
+            stuck_after,
+        }
+    }
+
+    /// Poll history from `start_time` (milliseconds) instead of the tracker's creation
+    pub fn since(self, start_time: u64) -> Self {
+        self.lock().since = start_time;
+        self
+    }
+
+    /// Register a callback invoked for every transfer event
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&TransferEvent) + Send + Sync + 'static,
+    {
+        self.events.on_event(callback);
+    }
+
+    /// Subscribe to transfer events through a blocking channel
+    pub fn subscribe(&self) -> mpsc::Receiver<TransferEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to transfer events through an async channel
+    pub fn subscribe_async(&self) -> async_mpsc::UnboundedReceiver<TransferEvent> {
+        self.events.subscribe_async()
+    }
+
+    /// Follow a withdrawal accepted by `Wallet::withdraw`
+    pub fn track_withdrawal(&self, coin: &str, withdraw_order_id: Option<&str>, result: &WithdrawResult) {
+        let transfer = TrackedTransfer {
+            kind: TransferKind::Withdrawal,
+            id: result.withdraw_id.to_string(),
+            coin: coin.to_string(),
+            amount: String::new(),
+            network_name: None,
+            address: None,
+            withdraw_order_id: withdraw_order_id.map(str::to_string),
+            tx_id: None,
+            state: TransferState::Pending,
+        };
+        self.apply(transfer, true);
+    }
+
+    /// Report deposits of `coin`, matched case-insensitively
+    pub fn watch_deposits(&self, coin: &str) {
+        self.lock().deposit_coins.insert(coin.to_lowercase());
+    }
+
+    /// Apply a `withdraw_history` record; withdrawals not being tracked are ignored
+    pub fn apply_withdrawal(&self, record: &WithdrawRecord) {
+        self.apply(
+            TrackedTransfer {
+                kind: TransferKind::Withdrawal,
+                id: record.id.to_string(),
+                coin: record.coin.clone(),
+                amount: record.amount.clone(),
+                network_name: record.network_name.clone(),
+                address: record.address.clone(),
+                withdraw_order_id: record.withdraw_order_id.clone(),
+                tx_id: record.tx_id.clone().filter(|tx| !tx.is_empty()),
+                state: record.status.into(),
+            },
+            false,
+        );
+    }
+
+    /// Apply a `deposit_history` record
+    pub fn apply_deposit(&self, record: &DepositRecord) {
+        self.apply(
+            TrackedTransfer {
+                kind: TransferKind::Deposit,
+                id: deposit_key(record),
+                coin: record.coin.clone(),
+                amount: record.amount.clone(),
+                network_name: record.network_name.clone(),
+                address: record.address.clone(),
+                withdraw_order_id: None,
+                tx_id: record.tx_id.clone().filter(|tx| !tx.is_empty()),
+                state: record.status.into(),
+            },
+            true,
+        );
+    }
+
+    /// Apply an `assetUpdate` push, returning whether it came from a deposit or withdrawal
+    pub fn apply_asset_update(&self, update: &AssetUpdate) -> bool {
+        if !update.is_deposit() && !update.is_withdrawal() {
+            return false;
+        }
+        self.events.emit([TransferEvent::BalanceChanged(update.clone())]);
+        true
+    }
+
+    /// Apply a raw WebSocket message, returning `true` for a deposit or
+    /// withdrawal `assetUpdate`, after which a `poll` will pick up the change
+    pub fn apply_push(&self, message: &str) -> Result<bool> {
+        let value: serde_json::Value = serde_json::from_str(message)?;
+        if value.get("type").and_then(|t| t.as_str()) != Some("assetUpdate") {
+            return Ok(false);
+        }
+        let push: AssetUpdatePush = serde_json::from_value(value)?;
+        Ok(self.apply_asset_update(&push.data))
+    }
+
+    /// Refresh pending withdrawals and watched deposits, then check for stuck transfers
+    pub fn poll(&self, wallet: &Wallet) -> Result<()> {
+        let (since, withdrawal_coins, deposit_coins) = self.poll_plan();
+        for coin in &withdrawal_coins {
+            for record in wallet.withdraw_history(None, Some(coin), None, Some(since), None)? {
+                self.apply_withdrawal(&record);
+            }
+        }
+        for coin in &deposit_coins {
+            for record in wallet.deposit_history(None, Some(coin), Some(since), None)? {
+                self.apply_deposit(&record);
+            }
+        }
+        self.check_stuck();
+        Ok(())
+    }
+
+    /// Refresh pending withdrawals and watched deposits (async)
+    ///
+    /// See [`TransferTracker::poll`].
+    pub async fn poll_async(&self, wallet: &AsyncWallet) -> Result<()> {
+        let (since, withdrawal_coins, deposit_coins) = self.poll_plan();
+        for coin in &withdrawal_coins {
+            for record in wallet.withdraw_history(None, Some(coin), None, Some(since), None).await? {
+                self.apply_withdrawal(&record);
+            }
+        }
+        for coin in &deposit_coins {
+            for record in wallet.deposit_history(None, Some(coin), Some(since), None).await? {
+                self.apply_deposit(&record);
+            }
+        }
+        self.check_stuck();
+        Ok(())
+    }
+
+    /// Emit `Stuck` for transfers pending longer than `stuck_after`
+    pub fn check_stuck(&self) {
+        let mut stuck = Vec::new();
+        for entry in self.lock().transfers.values_mut() {
+            if !entry.alerted && !entry.transfer.state.is_final() && entry.tracked_at.elapsed() >= self.stuck_after {
+                entry.alerted = true;
+                stuck.push(TransferEvent::Stuck(entry.transfer.clone()));
+            }
+        }
+        self.events.emit(stuck);
+    }
+
+    /// Look up a transfer by kind and ID
+    pub fn get(&self, kind: TransferKind, id: &str) -> Option<TrackedTransfer> {
+        self.lock()
+            .transfers
+            .get(&(kind, id.to_string()))
+            .map(|entry| entry.transfer.clone())
+    }
+
+    /// Look up a withdrawal by its `withdrawOrderId`
+    pub fn get_by_withdraw_order_id(&self, withdraw_order_id: &str) -> Option<TrackedTransfer> {
+        self.lock()
+            .transfers
+            .values()
+            .map(|entry| &entry.transfer)
+            .find(|t| t.withdraw_order_id.as_deref() == Some(withdraw_order_id))
+            .cloned()
+    }
+
+    /// Transfers that haven't reached a final state
+    pub fn pending(&self) -> Vec<TrackedTransfer> {
+        self.lock()
+            .transfers
+            .values()
+            .filter(|entry| !entry.transfer.state.is_final())
+            .map(|entry| entry.transfer.clone())
+            .collect()
+    }
+
+    /// Start time and coins to query on the next poll
+    fn poll_plan(&self) -> (u64, BTreeSet<String>, BTreeSet<String>) {
+        let inner = self.lock();
+        let withdrawal_coins = inner
+            .transfers
+            .values()
+            .filter(|e| e.transfer.kind == TransferKind::Withdrawal && !e.transfer.state.is_final())
+            .map(|e| e.transfer.coin.clone())
+            .collect();
+        (inner.since, withdrawal_coins, inner.deposit_coins.clone())
+    }
+
+    /// Merge `incoming` into the tracked transfer, inserting it if `insert` is set
+    fn apply(&self, incoming: TrackedTransfer, insert: bool) {
+        if let Some(event) = self.merge(incoming, insert) {
+            self.events.emit([event]);
+        }
+    }
+
+    /// Update the tracked state, returning the event to emit
+    fn merge(&self, incoming: TrackedTransfer, insert: bool) -> Option<TransferEvent> {
+        let mut inner = self.lock();
+        if incoming.kind == TransferKind::Deposit && !inner.deposit_coins.contains(&incoming.coin.to_lowercase()) {
+            return None;
+        }
+        let key = (incoming.kind, incoming.id.clone());
+        let Some(entry) = inner.transfers.get_mut(&key) else {
+            if insert {
+                inner.transfers.insert(
+                    key,
+                    Entry {
+                        transfer: incoming.clone(),
+                        tracked_at: Instant::now(),
+                        alerted: false,
+                    },
+                );
+                return Some(TransferEvent::Detected(incoming));
+            }
+            return None;
+        };
+
+        // Final states are sticky
+        let current = &entry.transfer;
+        if current.state.is_final() || (current.state == incoming.state && current.tx_id == incoming.tx_id) {
+            if current.amount.is_empty() {
+                entry.transfer = incoming;
+            }
+            return None;
+        }
+        let previous = current.state;
+        entry.transfer = TrackedTransfer {
+            tx_id: incoming.tx_id.or(current.tx_id.clone()),
+            withdraw_order_id: incoming.withdraw_order_id.or(current.withdraw_order_id.clone()),
+            ..incoming
+        };
+        let transfer = entry.transfer.clone();
+        Some(TransferEvent::Updated { previous, transfer })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn withdrawal(status: &str, tx_id: &str) -> WithdrawRecord {
+        serde_json::from_str(&format!(
+            r#"{{"id":7,"amount":"20","coin":"usdt","address":"T1","withdrawOrderId":"w7","networkName":"trc20","txId":"{}","applyTime":1,"status":"{}"}}"#,
+            tx_id, status
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_withdrawal_lifecycle() {
+        let tracker = TransferTracker::new(Duration::from_secs(3600));
+        let events = tracker.subscribe();
+        tracker.apply_withdrawal(&withdrawal("1", ""));
+        assert!(events.try_recv().is_err(), "untracked withdrawals are ignored");
+
+        let result = WithdrawResult { fee: None, withdraw_id: 7 };
+        tracker.track_withdrawal("usdt", Some("w7"), &result);
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Detected(t) if t.state == TransferState::Pending));
+
+        // First record only fills in details
+        tracker.apply_withdrawal(&withdrawal("1", ""));
+        assert!(events.try_recv().is_err());
+        assert_eq!(tracker.get_by_withdraw_order_id("w7").unwrap().amount, "20");
+
+        tracker.apply_withdrawal(&withdrawal("1", "0xabc"));
+        match events.try_recv().unwrap() {
+            TransferEvent::Updated { previous, transfer } => {
+                assert_eq!(previous, TransferState::Pending);
+                assert_eq!(transfer.tx_id.as_deref(), Some("0xabc"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        tracker.apply_withdrawal(&withdrawal("4", "0xabc"));
+        tracker.apply_withdrawal(&withdrawal("3", "0xabc"));
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Updated { transfer, .. } if transfer.state == TransferState::Completed));
+        assert!(events.try_recv().is_err(), "final states are sticky");
+        assert!(tracker.pending().is_empty());
+    }
+
+    #[test]
+    fn test_deposit_keeps_key_when_tx_id_arrives() {
+        let tracker = TransferTracker::new(Duration::from_secs(3600));
+        let events = tracker.subscribe();
+        tracker.watch_deposits("LBK");
+        let deposit = |tx_id: &str, status: &str| -> DepositRecord {
+            serde_json::from_str(&format!(
+                r#"{{"insertTime":1644378926000,"amount":443,"address":"Qi","networkName":"lbk20","txId":"{}","coin":"lbk","status":"{}"}}"#,
+                tx_id, status
+            ))
+            .unwrap()
+        };
+
+        tracker.apply_deposit(&deposit("", "1"));
+        tracker.apply_deposit(&deposit("0x4baf", "2"));
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Detected(_)));
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Updated { transfer, .. } if transfer.tx_id.as_deref() == Some("0x4baf")));
+        assert!(tracker.pending().is_empty());
+    }
+
+    #[test]
+    fn test_deposits_pushes_and_stuck_alerts() {
+        let tracker = TransferTracker::new(Duration::ZERO);
+        let events = tracker.subscribe();
+        // Callbacks run unlocked, so they can read the tracker
+        let (reader, (tx, pending)) = (tracker.clone(), mpsc::channel());
+        tracker.on_event(move |_| tx.send(reader.pending().len()).unwrap());
+        let deposit: DepositRecord =
+            serde_json::from_str(r#"{"insertTime":5,"amount":"3","coin":"eth","txId":"0x1","status":"1"}"#).unwrap();
+        tracker.apply_deposit(&deposit);
+        assert!(events.try_recv().is_err(), "coin isn't watched");
+
+        tracker.watch_deposits("eth");
+        tracker.apply_deposit(&deposit);
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Detected(_)));
+
+        assert_eq!(pending.try_recv().unwrap(), 1);
+        tracker.check_stuck();
+        tracker.check_stuck();
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::Stuck(t) if t.coin == "eth"));
+        assert!(events.try_recv().is_err());
+
+        let push = r#"{"data":{"asset":"3","assetCode":"eth","free":"3","freeze":"0","time":6,"type":"DEPOSIT"},"SERVER":"V2","type":"assetUpdate","TS":"2024-01-01T00:00:00.000"}"#;
+        assert!(tracker.apply_push(push).unwrap());
+        assert!(matches!(events.try_recv().unwrap(), TransferEvent::BalanceChanged(u) if u.asset_code == "eth"));
+        assert!(!tracker.apply_push(&push.replace("DEPOSIT", "ORDER_TRADE")).unwrap());
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[test]
+    fn test_poll() {
+        let server = MockServer::start_for_tests();
+        server.set_balance("usdt", 100.0);
+        let wallet = Wallet { client: server.test_client() };
+        let tracker = TransferTracker::new(Duration::from_secs(3600));
+        let events = tracker.subscribe();
+        tracker.watch_deposits("eth");
+
+        let result = wallet.withdraw("addr", "usdt", "10", "0", None, None, None, None, Some("w1")).unwrap();
+        tracker.track_withdrawal("usdt", Some("w1"), &result);
+        server.deposit("eth", 2.0);
+        tracker.poll(&wallet).unwrap();
+
+        let withdrawal = tracker.get_by_withdraw_order_id("w1").unwrap();
+        assert_eq!((withdrawal.amount.as_str(), withdrawal.state), ("10", TransferState::Pending));
+        let detected: Vec<TransferEvent> = events.try_iter().collect();
+        assert_eq!(detected.len(), 2);
+        assert!(matches!(&detected[1], TransferEvent::Detected(t) if t.coin == "eth" && t.state == TransferState::Completed));
+    }
+}
@@ -1,7 +1,12 @@
 use crate::client::Client;
-use crate::api::{API, Wallet as WalletAPI};
+use crate::wallet::requests;
+use crate::wallet::{cheapest_withdrawal, quote_withdrawal, WithdrawalQuote};
+use crate::errors::ErrorKind;
 use crate::errors::Result;
-use crate::util::build_signed_request;
+use crate::model::{
+    AssetDetail, CoinInfo, DepositAddress, DepositRecord, InternalTransfer, RecordList, WithdrawRecord, WithdrawResult,
+};
+use crate::pagination::{HistoryIter, PageCursor, Paginator, WALLET_WINDOW_MS};
 use std::collections::BTreeMap;
 
 /// Wallet API client for synchronous operations
@@ -14,25 +19,21 @@ impl Wallet {
     /// Get system status (0: maintenance, 1: normal)
     /// Note: This endpoint doesn't require authentication according to docs
     pub fn system_status(&self) -> Result<String> {
-        self.client.post(API::Wallet(WalletAPI::SystemStatus), None)
+        self.client.execute(requests::system_status())
     }
 
     /// Get all coin balances and multi-chain information for user
     /// Requires authentication
-    pub fn user_info(&self) -> Result<String> {
-        let parameters: BTreeMap<String, String> = BTreeMap::new();
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::UserInfo), Some(signed_request))
+    pub fn user_info(&self) -> Result<Vec<CoinInfo>> {
+        self.client.execute(requests::user_info())
     }
 
-    /// Submit withdrawal request (supports multi-chain)
+    /// Submit an on-chain withdrawal request (supports multi-chain)
+    ///
+    /// Use `transfer_internal` to send to another LBank account.
     /// 
     /// # Parameters
-    /// * `address` - Withdrawal address (or transfer account if type=1)
+    /// * `address` - Withdrawal address
     /// * `coin` - Currency code
     /// * `amount` - Withdrawal amount
     /// * `fee` - Withdrawal fee
@@ -41,7 +42,6 @@ impl Wallet {
     /// * `mark` - Optional withdrawal notes
     /// * `name` - Optional address remark (adds to address book)
     /// * `withdraw_order_id` - Optional custom withdrawal ID
-    /// * `type_` - Optional type (1 for intra-site transfer)
     pub fn withdraw(
         &self,
         address: &str,
@@ -53,39 +53,44 @@ impl Wallet {
         mark: Option<&str>,
         name: Option<&str>,
         withdraw_order_id: Option<&str>,
-        type_: Option<&str>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("address".to_string(), address.to_string());
-        parameters.insert("coin".to_string(), coin.to_string());
-        parameters.insert("amount".to_string(), amount.to_string());
-        parameters.insert("fee".to_string(), fee.to_string());
-
-        if let Some(network) = network_name {
-            parameters.insert("networkName".to_string(), network.to_string());
-        }
-        if let Some(m) = memo {
-            parameters.insert("memo".to_string(), m.to_string());
-        }
-        if let Some(mk) = mark {
-            parameters.insert("mark".to_string(), mk.to_string());
-        }
-        if let Some(n) = name {
-            parameters.insert("name".to_string(), n.to_string());
-        }
-        if let Some(wid) = withdraw_order_id {
-            parameters.insert("withdrawOrderId".to_string(), wid.to_string());
-        }
-        if let Some(t) = type_ {
-            parameters.insert("type".to_string(), t.to_string());
-        }
+    ) -> Result<WithdrawResult> {
+        self.client.execute(requests::withdraw(
+            address,
+            coin,
+            amount,
+            fee,
+            network_name,
+            memo,
+            mark,
+            name,
+            withdraw_order_id,
+        ))
+    }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::Withdraw), Some(signed_request))
+    /// Transfer to another LBank account (intra-site, no fee)
+    ///
+    /// Fails without sending anything when `amount` is below the coin's
+    /// `minLimit` from `user_info`.
+    ///
+    /// # Parameters
+    /// * `account` - Receiving LBank account
+    /// * `coin` - Currency code
+    /// * `amount` - Transfer amount
+    /// * `memo` - Optional memo
+    /// * `mark` - Optional transfer notes
+    /// * `withdraw_order_id` - Optional custom withdrawal ID
+    pub fn transfer_internal(
+        &self,
+        account: &str,
+        coin: &str,
+        amount: &str,
+        memo: Option<&str>,
+        mark: Option<&str>,
+        withdraw_order_id: Option<&str>,
+    ) -> Result<InternalTransfer> {
+        check_internal_transfer(&self.user_info()?, account, coin, amount)?;
+        self.client
+            .execute(requests::transfer_internal(account, coin, amount, memo, mark, withdraw_order_id))
     }
 
     /// Get deposit history with optional filters
@@ -101,28 +106,10 @@ impl Wallet {
         coin: Option<&str>,
         start_time: Option<u64>,
         end_time: Option<u64>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-
-        if let Some(s) = status {
-            parameters.insert("status".to_string(), s.to_string());
-        }
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
-        }
-        if let Some(st) = start_time {
-            parameters.insert("startTime".to_string(), st.to_string());
-        }
-        if let Some(et) = end_time {
-            parameters.insert("endTime".to_string(), et.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::DepositHistory), Some(signed_request))
+    ) -> Result<Vec<DepositRecord>> {
+        self.client
+            .execute(requests::deposit_history(status, coin, start_time, end_time))
+            .map(RecordList::into_records)
     }
 
     /// Get withdrawal history with optional filters
@@ -140,31 +127,10 @@ impl Wallet {
         withdraw_order_id: Option<&str>,
         start_time: Option<u64>,
         end_time: Option<u64>,
-    ) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-
-        if let Some(s) = status {
-            parameters.insert("status".to_string(), s.to_string());
-        }
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
-        }
-        if let Some(wid) = withdraw_order_id {
-            parameters.insert("withdrawOrderId".to_string(), wid.to_string());
-        }
-        if let Some(st) = start_time {
-            parameters.insert("startTime".to_string(), st.to_string());
-        }
-        if let Some(et) = end_time {
-            parameters.insert("endTime".to_string(), et.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::WithdrawHistory), Some(signed_request))
+    ) -> Result<Vec<WithdrawRecord>> {
+        self.client
+            .execute(requests::withdraw_history(status, coin, withdraw_order_id, start_time, end_time))
+            .map(RecordList::into_records)
     }
 
     /// Get deposit address for a specific coin and chain
@@ -172,39 +138,186 @@ impl Wallet {
     /// # Parameters
     /// * `coin` - Currency code
     /// * `network_name` - Optional chain name
-    pub fn deposit_address(&self, coin: &str, network_name: Option<&str>) -> Result<String> {
-        let mut parameters = BTreeMap::new();
-        parameters.insert("coin".to_string(), coin.to_string());
-
-        if let Some(network) = network_name {
-            parameters.insert("networkName".to_string(), network.to_string());
-        }
-
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::DepositAddress), Some(signed_request))
+    pub fn deposit_address(&self, coin: &str, network_name: Option<&str>) -> Result<DepositAddress> {
+        self.client.execute(requests::deposit_address(coin, network_name))
     }
 
     /// Get asset details including withdrawal/deposit status and fees
     /// 
     /// # Parameters
     /// * `coin` - Optional currency filter
-    pub fn asset_detail(&self, coin: Option<&str>) -> Result<String> {
-        let mut parameters = BTreeMap::new();
+    pub fn asset_detail(&self, coin: Option<&str>) -> Result<BTreeMap<String, AssetDetail>> {
+        self.client.execute(requests::asset_detail(coin))
+    }
+
+    /// Fee and net amount of withdrawing `amount` of `coin` on `network`
+    /// (the default chain when `None`), from live `user_info` data
+    pub fn withdrawal_quote(&self, coin: &str, network: Option<&str>, amount: f64) -> Result<WithdrawalQuote> {
+        quote_withdrawal(&self.user_info()?, coin, network, amount)
+    }
+
+    /// The eligible network with the lowest fee for withdrawing `amount` of `coin`
+    pub fn cheapest_withdrawal(&self, coin: &str, amount: f64) -> Result<WithdrawalQuote> {
+        cheapest_withdrawal(&self.user_info()?, coin, amount)
+    }
+
+    /// Iterate over every deposit between `start_time` and `end_time`
+    ///
+    /// Walks `deposit_history` in 30-day windows, yielding each deposit once
+    /// and pacing requests to stay under the rate limit.
+    ///
+    /// # Parameters
+    /// * `status` - Optional deposit status filter
+    /// * `coin` - Optional currency filter
+    /// * `start_time` - Start time (timestamp in milliseconds)
+    /// * `end_time` - End time (timestamp in milliseconds)
+    pub fn deposit_history_iter<'a>(
+        &'a self,
+        status: Option<&'a str>,
+        coin: Option<&'a str>,
+        start_time: u64,
+        end_time: u64,
+    ) -> HistoryIter<'a, DepositRecord> {
+        let paginator = Paginator::windows(start_time, end_time, WALLET_WINDOW_MS, None, deposit_key);
+        HistoryIter::new(paginator, move |cursor: &PageCursor| {
+            let &PageCursor::Window { start, end, .. } = cursor else {
+                return Ok(Vec::new());
+            };
+            self.deposit_history(status, coin, Some(start), Some(end))
+        })
+    }
 
-        if let Some(c) = coin {
-            parameters.insert("coin".to_string(), c.to_string());
+    /// Iterate over every withdrawal between `start_time` and `end_time`
+    ///
+    /// Walks `withdraw_history` in 30-day windows, yielding each withdrawal
+    /// once and pacing requests to stay under the rate limit.
+    ///
+    /// # Parameters
+    /// * `status` - Optional withdrawal status filter
+    /// * `coin` - Optional currency filter
+    /// * `start_time` - Start time (timestamp in milliseconds)
+    /// * `end_time` - End time (timestamp in milliseconds)
+    pub fn withdraw_history_iter<'a>(
+        &'a self,
+        status: Option<&'a str>,
+        coin: Option<&'a str>,
+        start_time: u64,
+        end_time: u64,
+    ) -> HistoryIter<'a, WithdrawRecord> {
+        let paginator = Paginator::windows(start_time, end_time, WALLET_WINDOW_MS, None, withdraw_key);
+        HistoryIter::new(paginator, move |cursor: &PageCursor| {
+            let &PageCursor::Window { start, end, .. } = cursor else {
+                return Ok(Vec::new());
+            };
+            self.withdraw_history(status, coin, None, Some(start), Some(end))
+        })
+    }
+}
+
+/// Stable key of a deposit: its ID when given, otherwise its address, amount and time
+///
+/// The transaction hash is left out because it can be filled in after the
+/// deposit first appears, and intra-site transfers have none.
+pub(crate) fn deposit_key(deposit: &DepositRecord) -> String {
+    match deposit.id.as_deref().filter(|id| !id.is_empty()) {
+        Some(id) => id.to_string(),
+        None => format!(
+            "{}:{}:{}:{}",
+            deposit.coin,
+            deposit.address.as_deref().unwrap_or_default(),
+            deposit.amount,
+            deposit.insert_time
+        ),
+    }
+}
+
+pub(crate) fn withdraw_key(withdrawal: &WithdrawRecord) -> String {
+    withdrawal.id.to_string()
+}
+
+/// Validate an intra-site transfer against `coins` from `user_info`
+pub(crate) fn check_internal_transfer(coins: &[CoinInfo], account: &str, coin: &str, amount: &str) -> Result<()> {
+    let rejected = |reason: String| -> crate::errors::Error { ErrorKind::WithdrawalRejected(reason).into() };
+    if account.trim().is_empty() {
+        return Err(rejected("the receiving account is empty".into()));
+    }
+    let amount: f64 = amount.parse().map_err(|_| rejected(format!("amount is not a number: {}", amount)))?;
+    if amount <= 0.0 {
+        return Err(rejected("amount must be positive".into()));
+    }
+    let info = coins
+        .iter()
+        .find(|c| c.coin.eq_ignore_ascii_case(coin))
+        .ok_or_else(|| rejected(format!("unknown coin {}", coin)))?;
+    let min_limit = info
+        .network(None)
+        .or(info.network_list.first())
+        .and_then(|network| network.min_limit.as_deref())
+        .map(str::parse::<f64>)
+        .transpose()?
+        .unwrap_or(0.0);
+    if amount < min_limit {
+        return Err(rejected(format!("amount {} is below the internal transfer minimum of {}", amount, min_limit)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coins() -> Vec<CoinInfo> {
+        serde_json::from_str(
+            r#"[{"coin":"usdt","networkList":[
+                {"name":"erc20","isDefault":false,"minLimit":"5"},
+                {"name":"trc20","isDefault":true,"minLimit":"1"}
+            ]},{"coin":"lbk","networkList":[]}]"#,
+        )
+        .unwrap()
+    }
+
+    fn rejection(result: Result<()>) -> String {
+        match result.unwrap_err().0 {
+            ErrorKind::WithdrawalRejected(reason) => reason,
+            other => panic!("unexpected error: {:?}", other),
         }
+    }
 
-        let signed_request = build_signed_request(
-            parameters,
-            &self.client.api_key,
-            &self.client.secret_key,
-        )?;
-        self.client.post(API::Wallet(WalletAPI::AssetDetail), Some(signed_request))
+    #[test]
+    fn test_check_internal_transfer() {
+        let coins = coins();
+        assert!(check_internal_transfer(&coins, "friend", "USDT", "1").is_ok());
+        assert!(check_internal_transfer(&coins, "friend", "lbk", "0.1").is_ok());
+
+        assert!(rejection(check_internal_transfer(&coins, "friend", "usdt", "0.5")).contains("minimum of 1"));
+        assert!(rejection(check_internal_transfer(&coins, " ", "usdt", "5")).contains("receiving account"));
+        assert!(rejection(check_internal_transfer(&coins, "friend", "usdt", "abc")).contains("not a number"));
+        assert!(rejection(check_internal_transfer(&coins, "friend", "usdt", "-1")).contains("positive"));
+        assert!(rejection(check_internal_transfer(&coins, "friend", "eth", "5")).contains("unknown coin"));
     }
 }
 
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[test]
+    fn test_transfer_internal() {
+        let server = MockServer::start_for_tests();
+        server.set_balance("usdt", 100.0);
+        let wallet = Wallet { client: server.test_client() };
+
+        // Below the mock's minLimit of 1
+        assert!(wallet.transfer_internal("friend", "usdt", "0.5", None, None, None).is_err());
+        let transfer = wallet.transfer_internal("friend", "usdt", "25", None, None, Some("t1")).unwrap();
+        assert_eq!(server.balance("usdt"), (75.0, 0.0));
+        let coins = wallet.user_info().unwrap();
+        let usdt = coins.iter().find(|c| c.coin == "usdt").unwrap();
+        assert_eq!(usdt.balance().unwrap().free, 75.0);
+
+        let records = wallet.withdraw_history(None, Some("usdt"), Some("t1"), None, None).unwrap();
+        assert_eq!(records[0].id, transfer.withdraw_id);
+        assert_eq!(records[0].transfer_type.as_deref(), Some("Intra-site Transfer"));
+    }
+}
@@ -0,0 +1,480 @@
+use crate::errors::{ErrorKind, Result};
+use crate::model::{CoinInfo, WithdrawResult};
+use crate::util::uuid_spot;
+use crate::wallet::{AsyncWallet, Wallet, WithdrawalQuote};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Window daily caps are enforced over
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Slack allowed when comparing the given fee with the expected one
+const FEE_TOLERANCE: f64 = 1e-9;
+
+/// A withdrawal to be checked by [`WithdrawGuard`]
+///
+/// Mirrors the arguments of `Wallet::withdraw`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WithdrawRequest {
+    pub address: String,
+    pub coin: String,
+    pub amount: String,
+    pub fee: String,
+    /// Chain name; the coin's default chain when `None`
+    pub network_name: Option<String>,
+    pub memo: Option<String>,
+    pub mark: Option<String>,
+    pub name: Option<String>,
+    pub withdraw_order_id: Option<String>,
+}
+
+impl WithdrawRequest {
+    pub fn new(address: &str, coin: &str, amount: &str, fee: &str) -> Self {
+        WithdrawRequest {
+            address: address.to_string(),
+            coin: coin.to_string(),
+            amount: amount.to_string(),
+            fee: fee.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn network(mut self, network_name: &str) -> Self {
+        self.network_name = Some(network_name.to_string());
+        self
+    }
+
+    pub fn memo(mut self, memo: &str) -> Self {
+        self.memo = Some(memo.to_string());
+        self
+    }
+}
+
+/// A withdrawal counted towards the daily cap
+struct Reservation {
+    id: u64,
+    at: Instant,
+    coin: String,
+    amount: f64,
+}
+
+#[derive(Default)]
+struct State {
+    /// Withdrawals sent (or in flight) in the last 24 hours
+    sent: Vec<Reservation>,
+    next_id: u64,
+}
+
+/// Safety checks in front of `Wallet::withdraw`
+///
+/// Coins and networks are matched case-insensitively.
+///
+/// Before a withdrawal is sent, the guard checks that:
+/// - the address is allowlisted for the coin and network
+/// - the network exists, is enabled and the amount is above its `withdrawMin`
+/// - `fee` matches the [`WithdrawalQuote`] for the network's fee `type`
+/// - the coin's daily cap is not exceeded
+/// - amounts above the coin's confirmation threshold carry the token from
+///   [`WithdrawGuard::confirmation_token`]
+///
+/// Daily caps are a rolling 24 hours of withdrawals sent through this guard
+/// (and its clones); withdrawals made elsewhere are not counted. Coins
+/// without a cap or threshold are not limited.
+#[derive(Clone)]
+pub struct WithdrawGuard {
+    /// (coin, network) -> allowed addresses
+    allowlist: BTreeMap<(String, String), BTreeSet<String>>,
+    daily_caps: BTreeMap<String, f64>,
+    confirm_above: BTreeMap<String, f64>,
+    /// Mixed into confirmation tokens so they only work for this guard
+    secret: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl WithdrawGuard {
+    pub fn new() -> Self {
+        WithdrawGuard {
+            allowlist: BTreeMap::new(),
+            daily_caps: BTreeMap::new(),
+            confirm_above: BTreeMap::new(),
+            secret: uuid_spot(),
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Allow withdrawing `coin` to `address` on `network`
+    pub fn allow_address(mut self, coin: &str, network: &str, address: &str) -> Self {
+        self.allowlist
+            .entry((coin.to_lowercase(), network.to_lowercase()))
+            .or_default()
+            .insert(address.to_string());
+        self
+    }
+
+    /// Limit withdrawals of `coin` to `amount` per rolling 24 hours
+    pub fn daily_cap(mut self, coin: &str, amount: f64) -> Self {
+        self.daily_caps.insert(coin.to_lowercase(), amount);
+        self
+    }
+
+    /// Require a confirmation token for withdrawals of `coin` above `amount`
+    pub fn confirm_above(mut self, coin: &str, amount: f64) -> Self {
+        self.confirm_above.insert(coin.to_lowercase(), amount);
+        self
+    }
+
+    /// Amount of `coin` withdrawn through this guard in the last 24 hours
+    pub fn withdrawn_today(&self, coin: &str) -> f64 {
+        let coin = coin.to_lowercase();
+        let mut state = self.lock();
+        prune(&mut state);
+        used(&state, &coin)
+    }
+
+    /// Token confirming this exact withdrawal
+    ///
+    /// Show the request to a human and pass the token back only once they
+    /// approved it; any change to the address, coin, network, amount or fee
+    /// invalidates it.
+    pub fn confirmation_token(&self, request: &WithdrawRequest) -> String {
+        let mut hasher = Sha256::new();
+        for field in [
+            self.secret.as_str(),
+            &request.address,
+            &request.coin,
+            request.network_name.as_deref().unwrap_or_default(),
+            request.memo.as_deref().unwrap_or_default(),
+            &request.amount,
+            &request.fee,
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(&hasher.finalize()[..8])
+    }
+
+    /// Check `request` against the rules and `coins` (from `Wallet::user_info`)
+    ///
+    /// Fails with [`ErrorKind::WithdrawalRejected`] when a rule is broken and
+    /// [`ErrorKind::ConfirmationRequired`] when the amount needs a token that
+    /// is missing or wrong. Nothing is counted towards the daily cap.
+    pub fn check(&self, request: &WithdrawRequest, coins: &[CoinInfo], confirmation: Option<&str>) -> Result<()> {
+        let amount = self.validate(request, coins, confirmation)?;
+        let mut state = self.lock();
+        prune(&mut state);
+        self.check_cap(&state, &request.coin.to_lowercase(), amount)
+    }
+
+    /// Check `request` and send it with `Wallet::withdraw`
+    pub fn withdraw(
+        &self,
+        wallet: &Wallet,
+        request: &WithdrawRequest,
+        confirmation: Option<&str>,
+    ) -> Result<WithdrawResult> {
+        let coins = wallet.user_info()?;
+        let reserved = self.reserve(request, &coins, confirmation)?;
+        let result = wallet.withdraw(
+            &request.address,
+            &request.coin,
+            &request.amount,
+            &request.fee,
+            request.network_name.as_deref(),
+            request.memo.as_deref(),
+            request.mark.as_deref(),
+            request.name.as_deref(),
+            request.withdraw_order_id.as_deref(),
+        );
+        self.settle(reserved, &result);
+        result
+    }
+
+    /// Check `request` and send it with `AsyncWallet::withdraw`
+    pub async fn withdraw_async(
+        &self,
+        wallet: &AsyncWallet,
+        request: &WithdrawRequest,
+        confirmation: Option<&str>,
+    ) -> Result<WithdrawResult> {
+        let coins = wallet.user_info().await?;
+        let reserved = self.reserve(request, &coins, confirmation)?;
+        let result = wallet
+            .withdraw(
+                &request.address,
+                &request.coin,
+                &request.amount,
+                &request.fee,
+                request.network_name.as_deref(),
+                request.memo.as_deref(),
+                request.mark.as_deref(),
+                request.name.as_deref(),
+                request.withdraw_order_id.as_deref(),
+            )
+            .await;
+        self.settle(reserved, &result);
+        result
+    }
+
+    /// Check `request` and count it towards the daily cap, returning the reservation's ID
+    fn reserve(&self, request: &WithdrawRequest, coins: &[CoinInfo], confirmation: Option<&str>) -> Result<u64> {
+        let amount = self.validate(request, coins, confirmation)?;
+        let coin = request.coin.to_lowercase();
+        let mut state = self.lock();
+        prune(&mut state);
+        self.check_cap(&state, &coin, amount)?;
+        let id = state.next_id;
+        state.next_id += 1;
+        state.sent.push(Reservation {
+            id,
+            at: Instant::now(),
+            coin,
+            amount,
+        });
+        Ok(id)
+    }
+
+    /// Release the reservation if the withdrawal was definitely not sent
+    ///
+    /// After a timeout or an unreadable response the withdrawal may have gone
+    /// out, so it keeps counting towards the cap; otherwise retrying after
+    /// each timeout would get past it.
+    fn settle(&self, reserved: u64, result: &Result<WithdrawResult>) {
+        let rejected = result.as_ref().err().is_some_and(|e| !e.is_ambiguous() || e.is_connect());
+        if rejected {
+            self.lock().sent.retain(|reservation| reservation.id != reserved);
+        }
+    }
+
+    /// Run every check but the daily cap, returning the parsed amount
+    fn validate(&self, request: &WithdrawRequest, coins: &[CoinInfo], confirmation: Option<&str>) -> Result<f64> {
+        let amount = parse_amount("amount", &request.amount)?;
+        let fee = parse_amount("fee", &request.fee)?;
+        if amount <= 0.0 {
+            return Err(rejected("amount must be positive"));
+        }
+
+        let coin = coins
+            .iter()
+            .find(|c| c.coin.eq_ignore_ascii_case(&request.coin))
+            .ok_or_else(|| rejected(format!("unknown coin {}", request.coin)))?;
+        let network = coin.network(request.network_name.as_deref()).ok_or_else(|| {
+            rejected(format!(
+                "unknown network {} for {}",
+                request.network_name.as_deref().unwrap_or("(default)"),
+                request.coin
+            ))
+        })?;
+        let coin_key = request.coin.to_lowercase();
+        let allowed = self
+            .allowlist
+            .get(&(coin_key.clone(), network.name.to_lowercase()))
+            .is_some_and(|addresses| addresses.contains(&request.address));
+        if !allowed {
+            return Err(rejected(format!(
+                "address {} is not allowlisted for {} on {}",
+                request.address, request.coin, network.name
+            )));
+        }
+
+        // Checks the network is enabled and the amount above its minimum
+        let quote = WithdrawalQuote::new(coin, network, amount).map_err(|e| rejected(e.to_string()))?;
+        if (fee - quote.fee).abs() > FEE_TOLERANCE {
+            return Err(rejected(format!("fee {} does not match the network fee of {}", fee, quote.fee_param())));
+        }
+
+        if let Some(&threshold) = self.confirm_above.get(&coin_key) {
+            let token = self.confirmation_token(request);
+            if amount > threshold && confirmation != Some(token.as_str()) {
+                return Err(ErrorKind::ConfirmationRequired(token).into());
+            }
+        }
+        Ok(amount)
+    }
+
+    fn check_cap(&self, state: &State, coin: &str, amount: f64) -> Result<()> {
+        let Some(&cap) = self.daily_caps.get(coin) else {
+            return Ok(());
+        };
+        let used = used(state, coin);
+        if used + amount > cap + FEE_TOLERANCE {
+            return Err(rejected(format!(
+                "daily cap of {} {} exceeded ({} already withdrawn)",
+                cap, coin, used
+            )));
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for WithdrawGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_amount(field: &str, value: &str) -> Result<f64> {
+    value
+        .parse()
+        .map_err(|_| rejected(format!("{} is not a number: {}", field, value)))
+}
+
+fn prune(state: &mut State) {
+    state.sent.retain(|reservation| reservation.at.elapsed() < DAY);
+}
+
+fn used(state: &State, coin: &str) -> f64 {
+    state.sent.iter().filter(|r| r.coin == coin).map(|r| r.amount).sum()
+}
+
+fn rejected(reason: impl Into<String>) -> crate::errors::Error {
+    ErrorKind::WithdrawalRejected(reason.into()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coins() -> Vec<CoinInfo> {
+        serde_json::from_str(
+            r#"[{"coin":"usdt","usableAmt":"100","assetAmt":"100","networkList":[
+                {"name":"trc20","network":"TRX","isDefault":true,"withdrawEnable":true,"withdrawFee":"1","withdrawMin":"5","type":"1"},
+                {"name":"erc20","network":"ETH","isDefault":false,"withdrawEnable":true,"withdrawFee":"2","withdrawFeeRate":"1","withdrawMin":"10","type":"3"}
+            ]}]"#,
+        )
+        .unwrap()
+    }
+
+    fn guard() -> WithdrawGuard {
+        WithdrawGuard::new()
+            .allow_address("usdt", "trc20", "T-treasury")
+            .allow_address("usdt", "erc20", "0xtreasury")
+            .confirm_above("usdt", 50.0)
+    }
+
+    fn rejection(result: Result<()>) -> String {
+        match result.unwrap_err().0 {
+            ErrorKind::WithdrawalRejected(reason) => reason,
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let guard = guard();
+        let coins = coins();
+        let ok = WithdrawRequest::new("T-treasury", "usdt", "20", "1");
+        assert!(guard.check(&ok, &coins, None).is_ok());
+
+        let other = WithdrawRequest::new("T-attacker", "usdt", "20", "1");
+        assert!(rejection(guard.check(&other, &coins, None)).contains("not allowlisted"));
+        // Allowlisted on trc20 only
+        let wrong_chain = WithdrawRequest::new("T-treasury", "usdt", "20", "4").network("erc20");
+        assert!(rejection(guard.check(&wrong_chain, &coins, None)).contains("not allowlisted"));
+
+        let low_fee = WithdrawRequest::new("T-treasury", "usdt", "20", "0.5");
+        assert!(rejection(guard.check(&low_fee, &coins, None)).contains("network fee"));
+        // Fixed plus percent: 2 + 1% of 20
+        let mixed = WithdrawRequest::new("0xtreasury", "usdt", "20", "2.2").network("erc20");
+        assert!(guard.check(&mixed, &coins, None).is_ok());
+
+        let dust = WithdrawRequest::new("T-treasury", "usdt", "1", "1");
+        assert!(rejection(guard.check(&dust, &coins, None)).contains("minimum"));
+    }
+
+    #[test]
+    fn test_confirmation_and_daily_cap() {
+        let guard = guard().daily_cap("usdt", 100.0);
+        let coins = coins();
+
+        let large = WithdrawRequest::new("T-treasury", "usdt", "80", "1");
+        let token = match guard.check(&large, &coins, None).unwrap_err().0 {
+            ErrorKind::ConfirmationRequired(token) => token,
+            other => panic!("unexpected error: {}", other),
+        };
+        assert!(guard.check(&large, &coins, Some("wrong")).is_err());
+        assert!(guard.check(&large, &coins, Some(&token)).is_ok());
+        // The token is bound to the request
+        let changed = WithdrawRequest::new("T-treasury", "usdt", "81", "1");
+        assert!(guard.check(&changed, &coins, Some(&token)).is_err());
+
+        guard.reserve(&large, &coins, Some(&token)).unwrap();
+        assert_eq!(guard.withdrawn_today("usdt"), 80.0);
+        let more = WithdrawRequest::new("T-treasury", "usdt", "30", "1");
+        assert!(rejection(guard.check(&more, &coins, None)).contains("daily cap"));
+    }
+
+    #[test]
+    fn test_coin_case_and_ambiguous_failures() {
+        use crate::errors::{Error, LBankContentError};
+
+        let guard = WithdrawGuard::new()
+            .allow_address("USDT", "TRC20", "T-treasury")
+            .daily_cap("usdt", 50.0)
+            .confirm_above("Usdt", 40.0);
+        let coins = coins();
+        let large = WithdrawRequest::new("T-treasury", "USDT", "45", "1");
+        assert!(matches!(guard.check(&large, &coins, None).unwrap_err().0, ErrorKind::ConfirmationRequired(_)));
+
+        let request = WithdrawRequest::new("T-treasury", "usdt", "30", "1");
+        let timeout: Error = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into();
+        let reserved = guard.reserve(&request, &coins, None).unwrap();
+        guard.settle(reserved, &Err(timeout));
+        assert_eq!(guard.withdrawn_today("USDT"), 30.0);
+
+        let more = WithdrawRequest::new("T-treasury", "usdt", "15", "1");
+        let reserved = guard.reserve(&more, &coins, None).unwrap();
+        let refused: Error = ErrorKind::LBankError(LBankContentError { code: 10014, msg: "insufficient".into() }).into();
+        guard.settle(reserved, &Err(refused));
+        assert_eq!(guard.withdrawn_today("usdt"), 30.0);
+        assert!(rejection(guard.check(&WithdrawRequest::new("T-treasury", "USDT", "25", "1"), &coins, None)).contains("daily cap"));
+    }
+
+    #[test]
+    fn test_settle_releases_only_its_reservation() {
+        let guard = guard().daily_cap("usdt", 100.0);
+        let coins = coins();
+        let request = WithdrawRequest::new("T-treasury", "usdt", "20", "1");
+        let first = guard.reserve(&request, &coins, None).unwrap();
+        let second = guard.reserve(&request, &coins, None).unwrap();
+        assert_ne!(first, second);
+        // Reserved within the same instant, as on a coarse clock
+        let mut state = guard.lock();
+        let at = state.sent[0].at;
+        state.sent[1].at = at;
+        drop(state);
+
+        let refused = ErrorKind::WithdrawalRejected("refused".into()).into();
+        guard.settle(second, &Err(refused));
+        assert_eq!(guard.withdrawn_today("usdt"), 20.0);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod mock_tests {
+    use super::*;
+    use crate::mock::MockServer;
+
+    #[test]
+    fn test_withdraw_through_guard() {
+        let server = MockServer::start_for_tests();
+        server.set_balance("usdt", 100.0);
+        server.set_withdraw_fee("usdt", 1.0);
+        let wallet = Wallet { client: server.test_client() };
+        let guard = WithdrawGuard::new().allow_address("usdt", "usdt", "treasury").daily_cap("usdt", 30.0);
+
+        let request = WithdrawRequest::new("treasury", "usdt", "20", "1");
+        assert!(guard.withdraw(&wallet, &request, None).unwrap().withdraw_id > 0);
+        assert_eq!(server.balance("usdt"), (80.0, 0.0));
+
+        assert!(guard.withdraw(&wallet, &request, None).is_err());
+        assert!(guard.withdraw(&wallet, &WithdrawRequest::new("treasury", "usdt", "5", "0"), None).is_err());
+        assert_eq!(server.balance("usdt"), (80.0, 0.0));
+        assert_eq!(guard.withdrawn_today("usdt"), 20.0);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::client::AsyncClient;
+use crate::websocket::requests;
+use crate::errors::Result;
+
+/// WebSocket endpoint and subscribeKey management for asynchronous operations
+#[derive(Clone)]
+pub struct AsyncWebSocket {
+    pub client: AsyncClient,
+    /// WebSocket URL (`Config::ws_endpoint`)
+    pub endpoint: String,
+}
+
+impl AsyncWebSocket {
+    /// URL to connect the WebSocket to
+    pub fn url(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Create a subscribeKey for private `orderUpdate` / `assetUpdate` channels (valid 60 minutes)
+    pub async fn subscribe_key(&self) -> Result<String> {
+        self.client.execute(requests::subscribe_key()).await
+    }
+
+    /// Extend the validity of a subscribeKey by 60 minutes
+    ///
+    /// # Parameters
+    /// * `subscribe_key` - Key returned by `subscribe_key`
+    pub async fn refresh_subscribe_key(&self, subscribe_key: &str) -> Result<String> {
+        self.client.execute(requests::refresh_subscribe_key(subscribe_key)).await
+    }
+
+    /// Close a subscribeKey
+    ///
+    /// # Parameters
+    /// * `subscribe_key` - Key returned by `subscribe_key`
+    pub async fn destroy_subscribe_key(&self, subscribe_key: &str) -> Result<String> {
+        self.client.execute(requests::destroy_subscribe_key(subscribe_key)).await
+    }
+}
@@ -0,0 +1,6 @@
+mod websocket;
+mod async_websocket;
+pub mod requests;
+
+pub use websocket::WebSocket;
+pub use async_websocket::AsyncWebSocket;
@@ -0,0 +1,16 @@
+//! Requests behind [`WebSocket`](super::WebSocket) and [`AsyncWebSocket`](super::AsyncWebSocket)
+
+use crate::api::{API, WebSocket};
+use crate::request::Request;
+
+pub fn subscribe_key() -> Request {
+    Request::signed(API::WebSocket(WebSocket::GetSubscribeKey))
+}
+
+pub fn refresh_subscribe_key(subscribe_key: &str) -> Request {
+    Request::signed(API::WebSocket(WebSocket::RefreshSubscribeKey)).param("subscribeKey", subscribe_key)
+}
+
+pub fn destroy_subscribe_key(subscribe_key: &str) -> Request {
+    Request::signed(API::WebSocket(WebSocket::DestroySubscribeKey)).param("subscribeKey", subscribe_key)
+}
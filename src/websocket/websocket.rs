@@ -0,0 +1,39 @@
+use crate::client::Client;
+use crate::websocket::requests;
+use crate::errors::Result;
+
+/// WebSocket endpoint and subscribeKey management for synchronous operations
+#[derive(Clone)]
+pub struct WebSocket {
+    pub client: Client,
+    /// WebSocket URL (`Config::ws_endpoint`)
+    pub endpoint: String,
+}
+
+impl WebSocket {
+    /// URL to connect the WebSocket to
+    pub fn url(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Create a subscribeKey for private `orderUpdate` / `assetUpdate` channels (valid 60 minutes)
+    pub fn subscribe_key(&self) -> Result<String> {
+        self.client.execute(requests::subscribe_key())
+    }
+
+    /// Extend the validity of a subscribeKey by 60 minutes
+    ///
+    /// # Parameters
+    /// * `subscribe_key` - Key returned by `subscribe_key`
+    pub fn refresh_subscribe_key(&self, subscribe_key: &str) -> Result<String> {
+        self.client.execute(requests::refresh_subscribe_key(subscribe_key))
+    }
+
+    /// Close a subscribeKey
+    ///
+    /// # Parameters
+    /// * `subscribe_key` - Key returned by `subscribe_key`
+    pub fn destroy_subscribe_key(&self, subscribe_key: &str) -> Result<String> {
+        self.client.execute(requests::destroy_subscribe_key(subscribe_key))
+    }
+}